@@ -0,0 +1,180 @@
+//! Accrual math of the claims
+//!
+//! Pure functions over explicit block numbers and amounts, so the payments owed to a beneficiary
+//! can be tested without the ink environment.
+//! The block numbers are timestamps in seconds in time based mode, the math is the same.
+
+use crate::multipliers::MultiplierValue;
+use crate::period;
+use crate::types::{Balance, BlockNumber, MultiplierId};
+use ink::prelude::collections::BTreeMap;
+
+/// Get the block until which payments accrue when reaching the block
+/// Past the end block nothing accrues, so only the periods ending at or before it are paid
+pub fn accrual_end(block: BlockNumber, end_block: Option<BlockNumber>) -> BlockNumber {
+    match end_block {
+        Some(end_block) => block.min(end_block),
+        None => block,
+    }
+}
+
+/// Get the block payments accrue from, the last update of the beneficiary
+/// but never before the first period starting at or after its vesting cliff
+pub fn accrual_start(
+    initial_block: BlockNumber,
+    periodicity: u32,
+    last_updated_period_block: BlockNumber,
+    start_block: Option<BlockNumber>,
+) -> BlockNumber {
+    match start_block {
+        Some(start_block) => last_updated_period_block.max(period::first_period_block_from(
+            initial_block,
+            periodicity,
+            start_block,
+        )),
+        None => last_updated_period_block,
+    }
+}
+
+/// Get the payment of one period for the multipliers of a beneficiary, counting only the multipliers accepted by the filter
+/// A beneficiary without multipliers is paid 1% of the base payment
+/// Returns None if the payment overflows
+pub fn payment_for_one_period(
+    base_payment: Balance,
+    multipliers: &BTreeMap<MultiplierId, MultiplierValue>,
+    is_counted: impl Fn(MultiplierId) -> bool,
+) -> Option<Balance> {
+    // E.g (M1 + M2) * B / BPS_PER_UNIT
+    let final_multiplier: MultiplierValue = if multipliers.is_empty() {
+        MultiplierValue::from_percent(1)
+    } else {
+        multipliers
+            .iter()
+            .filter(|(multiplier_id, _)| is_counted(**multiplier_id))
+            .map(|(_, multiplier)| *multiplier)
+            .sum()
+    };
+
+    final_multiplier.apply_to(base_payment)
+}
+
+/// Get the unclaimed payments plus the payment of each unclaimed period
+/// Returns None if the amount overflows
+pub fn accrued_amount(
+    unclaimed_payments: Balance,
+    unclaimed_periods: u32,
+    payment_per_period: Balance,
+) -> Option<Balance> {
+    payment_per_period
+        .checked_mul(unclaimed_periods.into())?
+        .checked_add(unclaimed_payments)
+}
+
+/// Check if unclaimed payments last updated at a block expired at the accrual end,
+/// which is when more periods than the expiry elapsed, not counting the paused blocks
+pub fn is_expired(
+    last_updated_period_block: BlockNumber,
+    accrual_end: BlockNumber,
+    paused_blocks: BlockNumber,
+    periodicity: u32,
+    unclaimed_expiry_periods: u32,
+) -> bool {
+    period::elapsed_periods(
+        last_updated_period_block,
+        accrual_end.saturating_sub(paused_blocks),
+        periodicity,
+    ) > unclaimed_expiry_periods
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multipliers(values: &[(MultiplierId, u128)]) -> BTreeMap<MultiplierId, MultiplierValue> {
+        values
+            .iter()
+            .map(|(multiplier_id, percent)| {
+                (*multiplier_id, MultiplierValue::from_percent(*percent))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn accrual_end_is_not_after_the_block_nor_the_end_block() {
+        for block in 0..30 {
+            assert_eq!(accrual_end(block, None), block);
+            for end_block in 0..30 {
+                let end = accrual_end(block, Some(end_block));
+                assert!(end <= block && end <= end_block);
+                assert!(end == block || end == end_block);
+            }
+        }
+    }
+
+    #[test]
+    fn accrual_start_is_a_period_start_not_before_the_cliff() {
+        for (initial_block, periodicity) in [(0, 1), (0, 2), (7, 3), (100, 10)] {
+            for last_updated in (0..6).map(|n| initial_block + n * periodicity) {
+                assert_eq!(
+                    accrual_start(initial_block, periodicity, last_updated, None),
+                    last_updated
+                );
+                for start_block in 0..initial_block + 6 * periodicity {
+                    let start =
+                        accrual_start(initial_block, periodicity, last_updated, Some(start_block));
+                    assert!(start >= last_updated && start >= start_block);
+                    assert!(period::is_period_start(initial_block, periodicity, start));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn payment_for_one_period_sums_the_counted_multipliers() {
+        let values = multipliers(&[(0, 100), (1, 3), (2, 50)]);
+        assert_eq!(payment_for_one_period(1000, &values, |_| true), Some(1530));
+        assert_eq!(
+            payment_for_one_period(1000, &values, |multiplier_id| multiplier_id != 2),
+            Some(1030)
+        );
+        assert_eq!(payment_for_one_period(1000, &values, |_| false), Some(0));
+        assert_eq!(
+            payment_for_one_period(1000, &BTreeMap::new(), |_| true),
+            Some(10)
+        );
+        assert_eq!(payment_for_one_period(u128::MAX, &values, |_| true), None);
+    }
+
+    #[test]
+    fn accrued_amount_adds_each_period() {
+        for unclaimed_payments in [0, 1, 999] {
+            for unclaimed_periods in 0..10 {
+                assert_eq!(
+                    accrued_amount(unclaimed_payments, unclaimed_periods, 1030),
+                    Some(unclaimed_payments + u128::from(unclaimed_periods) * 1030)
+                );
+            }
+        }
+        assert_eq!(accrued_amount(1, 1, u128::MAX), None);
+        assert_eq!(accrued_amount(0, 2, u128::MAX), None);
+    }
+
+    #[test]
+    fn expiry_does_not_count_the_paused_blocks() {
+        for last_updated in [0, 2, 10] {
+            for elapsed in 0..20 {
+                let accrual_end = last_updated + elapsed;
+                assert_eq!(
+                    is_expired(last_updated, accrual_end, 0, 2, 3),
+                    elapsed / 2 > 3
+                );
+                for paused_blocks in 0..=elapsed {
+                    assert_eq!(
+                        is_expired(last_updated, accrual_end, paused_blocks, 2, 3),
+                        (elapsed - paused_blocks) / 2 > 3
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,28 +1,73 @@
 #![cfg_attr(not(feature = "std"), no_std, no_main)]
 
+// The pure math and the stored types live in their own modules,
+// the storage and the messages stay in the contract module since ink! requires them in it
+mod claims;
+mod multipliers;
+mod period;
+mod types;
+
 #[ink::contract]
 mod open_payroll {
-    use ink::prelude::collections::BTreeMap;
+    use crate::claims;
+    use crate::multipliers;
+    pub use crate::multipliers::MultiplierValue;
+    use crate::period;
+    pub use crate::types::{
+        BaseMultiplier, Beneficiary, ClaimsInPeriod, GroupId, InitialBeneficiary, MultiplierId,
+    };
+    #[cfg(not(test))]
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::prelude::collections::{BTreeMap, BTreeSet};
     use ink::prelude::string::String;
     use ink::prelude::vec::Vec;
     use ink::storage::traits::StorageLayout;
     use ink::storage::Mapping;
 
     //----------------------------------------------------------------------------------------
-    // Custom Types
+    // Constants
     //----------------------------------------------------------------------------------------
 
-    // Define the types that will be used in the contract
-    type Multiplier = u128;
-    type MultiplierId = u32;
+    // Establish the upper bounds for the configurable maximum number of beneficiaries and multipliers
+    const MAX_BENEFICIARIES_LIMIT: u32 = 1000;
+    const MAX_MULTIPLIERS_LIMIT: u32 = 50;
 
-    //----------------------------------------------------------------------------------------
-    // Constants
-    //----------------------------------------------------------------------------------------
+    // Establish the maximum value of a single multiplier, 100 times the base payment
+    const MAX_MULTIPLIER: MultiplierValue = MultiplierValue::from_percent(10_000);
+
+    // Establish the maximum number of characters in the name of a multiplier
+    const MAX_MULTIPLIER_NAME_LENGTH: usize = 64;
+
+    // Establish the default number of claims kept in the history of each beneficiary, older claims are evicted
+    const CLAIM_HISTORY_CAPACITY: u32 = 32;
+
+    // Establish the maximum number of claims the owner can keep in the history of each beneficiary
+    const MAX_CLAIM_HISTORY_CAPACITY: u32 = 256;
 
-    // Establish the maximum number of beneficiaries and multipliers that can be added to the contract
-    const MAX_BENEFICIARIES: usize = 100;
-    const MAX_MULTIPLIERS: usize = 10;
+    // Establish the number of bonuses kept in the history of each beneficiary, older bonuses are evicted
+    const BONUS_HISTORY_CAPACITY: usize = 16;
+
+    // Establish the maximum length in bytes of the reason of a bonus
+    const MAX_BONUS_REASON_LENGTH: usize = 128;
+
+    // Establish the maximum length in bytes of the name and the notes of a beneficiary
+    const MAX_BENEFICIARY_METADATA_LENGTH: usize = 128;
+
+    // Establish the version of the storage expected by this code, see migrate
+    const CONTRACT_VERSION: u32 = 1;
+
+    // Establish the number of pauses kept in the pause history, older pauses are evicted
+    const PAUSE_HISTORY_CAPACITY: usize = 20;
+
+    // Establish the maximum number of items returned by a paged read message
+    const MAX_PAGE_SIZE: u32 = 50;
+
+    // Identifier of the build, embedded at compile time from the OPEN_PAYROLL_BUILD_ID env var
+    // as 64 hex characters. It is all zeros when the env var is not set
+    const BUILD_ID: [u8; 32] = match option_env!("OPEN_PAYROLL_BUILD_ID") {
+        Some(build_id) => parse_build_id(build_id),
+        None => [0; 32],
+    };
 
     //----------------------------------------------------------------------------------------
     // Errors
@@ -73,6 +118,70 @@ mod open_payroll {
         AccountAlreadyExists,
         /// The multiplier ID overflowed
         MultiplierIdOverflow,
+        /// The caller is neither the owner nor an admin of the contract
+        NotAdmin,
+        /// The beneficiary is already suspended
+        BeneficiaryAlreadySuspended,
+        /// The beneficiary is not suspended
+        BeneficiaryNotSuspended,
+        /// The transfer of the PSP22 token failed
+        TokenTransferFailed,
+        /// The multiplier value is bigger than the maximum allowed
+        MultiplierValueTooLarge,
+        /// There is already an active multiplier with the same name
+        DuplicatedMultiplierName,
+        /// The caller is neither the beneficiary nor its claim delegate
+        NotAllowedToClaim,
+        /// The period is older than the retained claim history
+        PeriodOutsideHistory,
+        /// The beneficiary is suspended and can't claim
+        BeneficiaryIsSuspended,
+        /// The group of the beneficiary is paused
+        GroupIsPaused,
+        /// The beneficiary is already deactivated
+        BeneficiaryAlreadyDeactivated,
+        /// The beneficiary still has payments to claim that can't be paid now
+        BeneficiaryHasUnclaimedPayments,
+        /// The payment calculation overflowed
+        PaymentCalculationOverflow,
+        /// The caller is not allowed to read the data of the beneficiary
+        NotAuthorized,
+        /// A subtraction underflowed, e.g. the debts are bigger than the balance
+        ArithmeticUnderflow,
+        /// The amount to withdraw would leave the treasury without enough balance for the debts and the next period
+        WithdrawalExceedsSafeLimit,
+        /// The multiplier is already expired
+        MultiplierExpired,
+        /// No refund address was set when the contract was created
+        RefundAddressNotSet,
+        /// The unclaimed payments can't expire after 0 periods
+        ExpiryPeriodsCannotBeZero,
+        /// The name of the multiplier is longer than the maximum allowed
+        NameTooLong,
+        /// The account can't be assigned the role
+        InvalidRoleAssignment,
+        /// No ownership transfer is pending
+        NoPendingOwnershipTransfer,
+        /// The beneficiary would have more multipliers than the maximum allowed
+        MaxMultipliersPerBeneficiaryExceeded,
+        /// The storage is already at the version of the code or could not be migrated
+        MigrationFailed,
+        /// The amount to claim is smaller than the amount to leave unclaimed
+        NotEnoughToLeaveUnclaimed,
+        /// The claim would take the amount claimed in the period over the period budget
+        PeriodBudgetExceeded,
+    }
+
+    /// Errors returned by a PSP22 token contract
+    #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
     }
 
     //----------------------------------------------------------------------------------------
@@ -80,6 +189,7 @@ mod open_payroll {
     //----------------------------------------------------------------------------------------
 
     /// Emitted when a beneficiary claims their payment
+    /// clamped is true when the amount was reduced to the beneficiary's max_per_claim
     #[ink(event)]
     pub struct Claimed {
         #[ink(topic)]
@@ -87,6 +197,7 @@ mod open_payroll {
         amount: Balance,
         total_payment: Balance,
         claiming_period_block: BlockNumber,
+        clamped: bool,
     }
 
     /// Emitted when a multiplier is deactivated
@@ -97,6 +208,13 @@ mod open_payroll {
         valid_until_block: BlockNumber,
     }
 
+    /// Emitted when a deactivated multiplier is reactivated before its expiration
+    #[ink(event)]
+    pub struct MultiplierReactivated {
+        #[ink(topic)]
+        multiplier_id: MultiplierId,
+    }
+
     /// Emitted when a multiplier is deleted
     #[ink(event)]
     pub struct MultiplierDeleted {
@@ -123,12 +241,47 @@ mod open_payroll {
         new_owner: AccountId,
     }
 
+    /// Emitted when a pending ownership transfer is cancelled
+    #[ink(event)]
+    pub struct OwnershipTransferCancelled {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        proposed_owner: AccountId,
+    }
+
+    /// Emitted when the ownership of the contract is renounced
+    #[ink(event)]
+    pub struct OwnershipRenounced {
+        #[ink(topic)]
+        previous_owner: AccountId,
+    }
+
+    /// Emitted when the code of the contract is updated
+    #[ink(event)]
+    pub struct CodeUpdated {
+        code_hash: Hash,
+    }
+
+    /// Emitted when the storage is migrated to a new version
+    #[ink(event)]
+    pub struct Migrated {
+        from_version: u32,
+        to_version: u32,
+    }
+
     /// Emitted when a beneficiary is added
     #[ink(event)]
     pub struct BeneficiaryAdded {
         #[ink(topic)]
         account_id: AccountId,
-        multipliers_vec: Vec<(MultiplierId, Multiplier)>,
+        multipliers_vec: Vec<(MultiplierId, MultiplierValue)>,
+    }
+
+    /// Emitted when the number of claims kept in the history of each beneficiary is updated
+    #[ink(event)]
+    pub struct ClaimHistoryCapacityUpdated {
+        claim_history_capacity: u32,
     }
 
     /// Emitted when a beneficiary is updated
@@ -136,7 +289,34 @@ mod open_payroll {
     pub struct BeneficiaryUpdated {
         #[ink(topic)]
         account_id: AccountId,
-        multipliers_vec: Vec<(MultiplierId, Multiplier)>,
+        multipliers_vec: Vec<(MultiplierId, MultiplierValue)>,
+    }
+
+    /// Emitted when new multipliers are scheduled for a beneficiary from a future period
+    #[ink(event)]
+    pub struct BeneficiaryUpdateScheduled {
+        #[ink(topic)]
+        account_id: AccountId,
+        multipliers_vec: Vec<(MultiplierId, MultiplierValue)>,
+        activation_block: BlockNumber,
+    }
+
+    /// Emitted when the name or the notes of a beneficiary are updated
+    #[ink(event)]
+    pub struct BeneficiaryMetadataUpdated {
+        #[ink(topic)]
+        account_id: AccountId,
+        name: Option<String>,
+        notes: Option<String>,
+    }
+
+    /// Emitted when a beneficiary moves to a new address
+    #[ink(event)]
+    pub struct BeneficiaryAddressUpdated {
+        #[ink(topic)]
+        previous_account_id: AccountId,
+        #[ink(topic)]
+        account_id: AccountId,
     }
 
     /// Emitted when a beneficiary is removed
@@ -144,6 +324,54 @@ mod open_payroll {
     pub struct BeneficiaryRemoved {
         #[ink(topic)]
         account_id: AccountId,
+        settled_amount: Balance,
+    }
+
+    /// Emitted when the max amount per claim of a beneficiary is updated
+    #[ink(event)]
+    pub struct MaxPerClaimUpdated {
+        #[ink(topic)]
+        account_id: AccountId,
+        max_per_claim: Option<Balance>,
+    }
+
+    /// Emitted when the block from which a beneficiary accrues payments is set by the owner
+    #[ink(event)]
+    pub struct BeneficiaryAccrualStartUpdated {
+        #[ink(topic)]
+        account_id: AccountId,
+        block: BlockNumber,
+    }
+
+    /// Emitted when a beneficiary is deactivated
+    #[ink(event)]
+    pub struct BeneficiaryDeactivated {
+        #[ink(topic)]
+        account_id: AccountId,
+        unclaimed_payments: Balance,
+    }
+
+    /// Emitted when the unclaimed payments of a beneficiary expire during a claim or before an update keeps its earnings
+    #[ink(event)]
+    pub struct BeneficiaryPaymentExpired {
+        #[ink(topic)]
+        account_id: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted when a beneficiary is suspended
+    #[ink(event)]
+    pub struct BeneficiarySuspended {
+        #[ink(topic)]
+        account_id: AccountId,
+    }
+
+    /// Emitted when a beneficiary is unsuspended
+    #[ink(event)]
+    pub struct BeneficiaryUnsuspended {
+        #[ink(topic)]
+        account_id: AccountId,
+        pruned_multipliers: Vec<MultiplierId>,
     }
 
     /// Emitted when a multiplier is added
@@ -153,6 +381,29 @@ mod open_payroll {
         name: String,
     }
 
+    /// Emitted when a multiplier is renamed
+    #[ink(event)]
+    pub struct BaseMultiplierRenamed {
+        multiplier_id: MultiplierId,
+        name: String,
+    }
+
+    /// Emitted when the rename of a multiplier is scheduled
+    #[ink(event)]
+    pub struct MultiplierUpdateScheduled {
+        multiplier_id: MultiplierId,
+        new_name: String,
+        activation_block: BlockNumber,
+    }
+
+    /// Emitted when the sponsor of a multiplier is updated
+    #[ink(event)]
+    pub struct MultiplierSponsorUpdated {
+        #[ink(topic)]
+        multiplier_id: MultiplierId,
+        sponsor: Option<AccountId>,
+    }
+
     /// Emitted when the preiodicity is updated
     #[ink(event)]
     pub struct PeriodicityUpdated {
@@ -163,55 +414,248 @@ mod open_payroll {
     #[ink(event)]
     pub struct Paused {}
 
+    /// Emitted when the contract is paused until a block
+    #[ink(event)]
+    pub struct PausedUntil {
+        resume_at: BlockNumber,
+    }
+
     /// Emitted when the contract is resumed
     #[ink(event)]
     pub struct Resumed {}
 
+    /// Emitted when the payments of a group are paused
+    #[ink(event)]
+    pub struct GroupPaused {
+        #[ink(topic)]
+        group_id: GroupId,
+    }
+
+    /// Emitted when the payments of a group are resumed
+    #[ink(event)]
+    pub struct GroupResumed {
+        #[ink(topic)]
+        group_id: GroupId,
+    }
+
+    /// Emitted when a beneficiary is moved to a group, or out of any group
+    #[ink(event)]
+    pub struct BeneficiaryGroupUpdated {
+        #[ink(topic)]
+        account_id: AccountId,
+        group_id: Option<GroupId>,
+    }
+
+    /// Emitted when an admin is added
+    #[ink(event)]
+    pub struct AdminAdded {
+        #[ink(topic)]
+        account_id: AccountId,
+    }
+
+    /// Emitted when an admin is removed
+    #[ink(event)]
+    pub struct AdminRemoved {
+        #[ink(topic)]
+        account_id: AccountId,
+    }
+
+    /// Emitted when funds are added to the treasury
+    #[ink(event)]
+    pub struct TreasuryToppedUp {
+        #[ink(topic)]
+        from: AccountId,
+        amount: Balance,
+        new_balance: Balance,
+    }
+
+    /// Emitted when someone donates to the treasury
+    #[ink(event)]
+    pub struct TreasuryFunded {
+        amount: Balance,
+        #[ink(topic)]
+        from: AccountId,
+    }
+
+    /// Emitted when the owner withdraws excess balance from the treasury
+    #[ink(event)]
+    pub struct ExcessBalanceWithdrawn {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted when the owner withdraws the surplus of the treasury
+    #[ink(event)]
+    pub struct SurplusWithdrawn {
+        #[ink(topic)]
+        owner: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted when the maximum runway is updated
+    #[ink(event)]
+    pub struct MaxRunwayPeriodsUpdated {
+        max_runway_periods: Option<u32>,
+    }
+
+    /// Emitted when the end block of the payroll is updated
+    #[ink(event)]
+    pub struct EndBlockUpdated {
+        end_block: Option<BlockNumber>,
+    }
+
+    /// Emitted when the period budget is updated
+    #[ink(event)]
+    pub struct PeriodBudgetUpdated {
+        period_budget: Option<Balance>,
+    }
+
+    /// Emitted when the balance above the maximum runway is returned to the refund address
+    #[ink(event)]
+    pub struct ExcessReturned {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    /// Emitted when the existential deposit hint is updated
+    #[ink(event)]
+    pub struct ExistentialDepositHintUpdated {
+        existential_deposit_hint: Balance,
+    }
+
+    /// Emitted when the target block time is updated
+    #[ink(event)]
+    pub struct TargetBlockTimeUpdated {
+        target_block_time: u64,
+    }
+
+    /// Emitted when a one-time bonus is added to a beneficiary
+    #[ink(event)]
+    pub struct BonusAdded {
+        #[ink(topic)]
+        account_id: AccountId,
+        amount: Balance,
+        reason: String,
+    }
+
+    /// Emitted when a beneficiary sets a claim delegate
+    #[ink(event)]
+    pub struct ClaimDelegateSet {
+        #[ink(topic)]
+        account_id: AccountId,
+        #[ink(topic)]
+        delegate: AccountId,
+    }
+
+    /// Emitted when a beneficiary revokes its claim delegate
+    #[ink(event)]
+    pub struct ClaimDelegateRevoked {
+        #[ink(topic)]
+        account_id: AccountId,
+    }
+
+    /// Emitted when a beneficiary sets or clears the account its payments are sent to
+    #[ink(event)]
+    pub struct PayoutAddressUpdated {
+        #[ink(topic)]
+        account_id: AccountId,
+        payout_address: Option<AccountId>,
+    }
+
     //----------------------------------------------------------------------------------------
     // Structs
     //----------------------------------------------------------------------------------------
 
-    /// Base multiplier structure containg a name and an option block number for being used when deactivating the multiplier
+    /// Roles that can be assigned to an account, see ensure_valid_role_assignment
+    #[derive(Eq, PartialEq, Debug, Clone, Copy)]
+    enum Role {
+        Owner,
+        Admin,
+        ClaimDelegate,
+        ProposedOwner,
+        RefundAddress,
+        Beneficiary,
+        PayoutAddress,
+    }
+
+    /// Claim record structure containing the block of the claim, the amount transferred,
+    /// the periods it settled as (from_period_block, to_period_block) with to excluded,
+    /// and the amount left unclaimed after it
     #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
-    pub struct BaseMultiplier {
-        name: String,
-        valid_until_block: Option<BlockNumber>,
+    pub struct ClaimRecord {
+        claimed_at_block: BlockNumber,
+        amount: Balance,
+        covered_periods: (BlockNumber, BlockNumber),
+        unclaimed_after: Balance,
     }
-    impl BaseMultiplier {
-        pub fn new(name: String) -> Self {
-            Self {
-                name,
-                valid_until_block: None,
-            }
-        }
+
+    /// Bonus record structure containing the block the bonus was added in, its amount and its reason
+    #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+    pub struct BonusRecord {
+        added_at_block: BlockNumber,
+        amount: Balance,
+        reason: String,
     }
 
-    /// Beneficiary structure containing the account id, the multipliers, the unclaimed payments, and the last updated period block
+    /// Scheduled multiplier change structure containing the multiplier, its new name and the block it applies from
     #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout,))]
-    pub struct Beneficiary {
-        account_id: AccountId,
-        multipliers: BTreeMap<MultiplierId, Multiplier>,
-        unclaimed_payments: Balance,
-        last_updated_period_block: BlockNumber,
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+    pub struct ScheduledMultiplierChange {
+        multiplier_id: MultiplierId,
+        new_name: String,
+        activation_block: BlockNumber,
     }
 
-    /// Initial beneficiary structure containing the account id and the multipliers
+    /// Scheduled beneficiary update structure containing the new multipliers of a beneficiary and the block they apply from
     #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
-    pub struct InitialBeneficiary {
-        account_id: AccountId,
-        // Vector rather than BTreeMap because its easier to buid from the frontend
-        multipliers: Vec<(MultiplierId, Multiplier)>,
+    pub struct ScheduledBeneficiaryUpdate {
+        activation_block: BlockNumber,
+        multipliers: BTreeMap<MultiplierId, MultiplierValue>,
+    }
+
+    /// Contract info structure containing the owner, the proposed owner, the periodicity, the base payment,
+    /// the initial block, the block the contract was paused at, the number of beneficiaries and multipliers,
+    /// and the initial block of the current period
+    #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+    pub struct ContractInfo {
+        owner: Option<AccountId>,
+        proposed_owner: Option<AccountId>,
+        periodicity: u32,
+        base_payment: Balance,
+        initial_block: BlockNumber,
+        paused_block_at: Option<BlockNumber>,
+        beneficiary_count: u32,
+        multiplier_count: u32,
+        current_period_initial_block: BlockNumber,
+    }
+
+    /// Contract info display structure containing the balances of ContractInfo as decimal strings,
+    /// for frontends that lose precision reading big numbers
+    #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+    pub struct ContractInfoDisplay {
+        base_payment: String,
     }
 
-    /// Claims in period structure containing the period and the total claims
+    /// Lifecycle state of the contract, see get_lifecycle_state
     #[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
-    pub struct ClaimsInPeriod {
-        period: u32,
-        total_claims: u32,
+    pub enum LifecycleState {
+        /// The beneficiaries can claim their payments
+        Active,
+        /// No one can claim since the given block, until the resume block if the pause has a duration
+        Paused {
+            since: BlockNumber,
+            resume_at: Option<BlockNumber>,
+        },
+        /// The code was updated and the storage still has to be migrated from the given version, see migrate
+        MigrationInProgress { from_version: u32 },
     }
 
     /// OpenPayroll contract structure containing the owner, the beneficiaries, the multipliers, the base payment, the periodicity,
@@ -229,12 +673,13 @@ mod open_payroll {
         /// The account to be transfered to, until the new owner accept it
         proposed_owner: Option<AccountId>,
         /// The accountId of the creator of the contract, who has 'priviliged' access to do administrative tasks
-        owner: AccountId,
+        /// None once the ownership is renounced
+        owner: Option<AccountId>,
         /// Mapping from the accountId to the beneficiary information
         beneficiaries: Mapping<AccountId, Beneficiary>,
         /// Vector of Accounts
         beneficiaries_accounts: Vec<AccountId>,
-        /// The payment periodicity in blocks
+        /// The payment periodicity in blocks, or in seconds in time based mode
         periodicity: u32,
         /// The amount of each base payment
         base_payment: Balance,
@@ -242,6 +687,20 @@ mod open_payroll {
         initial_block: u32,
         /// The block number when the contract was paused
         paused_block_at: Option<u32>,
+        /// The block number when a pause with a duration ends, or None if it lasts until resumed
+        resume_at: Option<BlockNumber>,
+        /// The latest ended pauses as (paused_at, resumed_at)
+        pause_history: Vec<(BlockNumber, BlockNumber)>,
+        /// The number of blocks the contract was paused in the ended pauses, it is never evicted
+        total_paused_blocks: u32,
+        /// The version of the storage, it lags behind CONTRACT_VERSION after a code update until migrated
+        contract_version: u32,
+        /// The multiplier renames waiting for their activation block, at most one per multiplier
+        scheduled_multiplier_changes: Vec<ScheduledMultiplierChange>,
+        /// The groups whose payments are paused, with the block they were paused at
+        paused_groups: Mapping<GroupId, BlockNumber>,
+        /// The number of blocks each group was paused in its ended pauses, it is never evicted
+        group_total_paused_blocks: Mapping<GroupId, BlockNumber>,
         /// The id of the next multiplier to be added
         next_multiplier_id: MultiplierId,
         /// The multipliers to apply to the base payment
@@ -250,19 +709,72 @@ mod open_payroll {
         multipliers_list: Vec<MultiplierId>,
         /// Current claims in period
         claims_in_period: ClaimsInPeriod,
+        /// The accounts that claimed in the period of 'claims_in_period'
+        claimed_accounts_in_period: Vec<AccountId>,
+        /// Accounts allowed to do operational tasks besides the owner
+        admins: Mapping<AccountId, ()>,
+        /// Accounts allowed to claim on behalf of a beneficiary
+        claim_delegates: Mapping<AccountId, AccountId>,
+        /// The latest claims of each beneficiary
+        claim_history: Mapping<AccountId, Vec<ClaimRecord>>,
+        /// The number of claims kept in the history of each beneficiary
+        claim_history_capacity: u32,
+        /// The amount claimed by each beneficiary since it was added, it is never evicted
+        total_claimed: Mapping<AccountId, Balance>,
+        /// The amount paid to all the beneficiaries since the creation of the contract
+        total_paid_out: Balance,
+        /// The latest bonuses of each beneficiary
+        bonus_history: Mapping<AccountId, Vec<BonusRecord>>,
+        /// The expired multipliers removed from each beneficiary while it was suspended
+        pruned_multipliers: Mapping<AccountId, Vec<MultiplierId>>,
+        /// The multipliers each beneficiary gets from a future period, at most one update per beneficiary
+        scheduled_beneficiary_updates: Mapping<AccountId, ScheduledBeneficiaryUpdate>,
+        /// The maximum number of beneficiaries that can be added to the contract
+        max_beneficiaries: u32,
+        /// The maximum number of multipliers that can be added to the contract
+        max_multipliers: u32,
+        /// The maximum number of multipliers of a single beneficiary
+        max_multipliers_per_beneficiary: u32,
+        /// The PSP22 token used to pay the beneficiaries, or None to pay in the native token
+        token: Option<AccountId>,
+        /// Whether the periods are measured with the block timestamp in seconds instead of the block number
+        time_based: bool,
+        /// The existential deposit of the chain as known by the owner
+        existential_deposit_hint: Balance,
+        /// The expected time between blocks in milliseconds, or 0 if unknown
+        target_block_time: u64,
+        /// Whether the data of each beneficiary is only readable by the owner, the admins and the beneficiary
+        private: bool,
+        /// The maximum number of periods the treasury may fund on top of the debts, or None for no limit
+        max_runway_periods: Option<u32>,
+        /// The account receiving the balance above the maximum runway, usually the funding DAO
+        refund_address: Option<AccountId>,
+        /// The number of periods after which the unclaimed payments of a beneficiary expire, or None if they never expire
+        unclaimed_expiry_periods: Option<u32>,
+        /// The block after which no payment accrues, or None if the payroll has no end
+        end_block: Option<BlockNumber>,
+        /// The maximum amount claimed by all the beneficiaries in a period, or None for no limit
+        period_budget: Option<Balance>,
     }
 
     /// implementation of the OpenPayroll contract
     impl OpenPayroll {
-        pub fn default(periodicity: u32, base_payment: Balance) -> Self {
+        pub fn default(
+            periodicity: u32,
+            base_payment: Balance,
+            max_beneficiaries: u32,
+            max_multipliers: u32,
+            token: Option<AccountId>,
+        ) -> Self {
             // Defines the claims in period
             let claims_in_period = ClaimsInPeriod {
                 period: 0,
                 total_claims: 0,
+                total_amount: 0,
             };
             let base_multipliers = Mapping::new();
             let initial_block = Self::env().block_number();
-            let owner = Self::env().caller();
+            let owner = Some(Self::env().caller());
 
             Self {
                 owner,
@@ -273,10 +785,40 @@ mod open_payroll {
                 base_payment,
                 initial_block,
                 paused_block_at: None,
+                resume_at: None,
+                pause_history: Vec::new(),
+                total_paused_blocks: 0,
+                contract_version: CONTRACT_VERSION,
+                scheduled_multiplier_changes: Vec::new(),
+                paused_groups: Mapping::new(),
+                group_total_paused_blocks: Mapping::new(),
                 next_multiplier_id: 0,
                 base_multipliers,
                 multipliers_list: Default::default(),
                 claims_in_period,
+                claimed_accounts_in_period: Default::default(),
+                admins: Mapping::new(),
+                claim_delegates: Mapping::new(),
+                claim_history: Mapping::new(),
+                claim_history_capacity: CLAIM_HISTORY_CAPACITY,
+                total_claimed: Mapping::new(),
+                total_paid_out: 0,
+                bonus_history: Mapping::new(),
+                pruned_multipliers: Mapping::new(),
+                scheduled_beneficiary_updates: Mapping::new(),
+                max_beneficiaries,
+                max_multipliers,
+                max_multipliers_per_beneficiary: max_multipliers,
+                token,
+                time_based: false,
+                existential_deposit_hint: 0,
+                target_block_time: 0,
+                private: false,
+                max_runway_periods: None,
+                refund_address: None,
+                unclaimed_expiry_periods: None,
+                end_block: None,
+                period_budget: None,
             }
         }
         //----------------------------------------------------------------------------------------
@@ -284,54 +826,243 @@ mod open_payroll {
         //----------------------------------------------------------------------------------------
 
         /// Constructor that initializes the owner, the base payment, the periodicity, the initial block, the base multipliers,
-        /// and the initial beneficiaries
+        /// the initial beneficiaries, the maximum number of beneficiaries and multipliers,
+        /// the optional PSP22 token used for payments instead of the native token,
+        /// the existential deposit of the chain, see get_existential_deposit,
+        /// whether the data of each beneficiary is private, see ensure_can_read_beneficiary,
+        /// the account receiving the balance above the maximum runway, see return_excess,
+        /// the number of periods after which unclaimed payments expire, or None if they never expire,
+        /// the owner, or None for the caller. A contract deploying the payroll for someone else sets it,
+        /// so it keeps no rights over the instance and no ownership transfer is needed,
+        /// and the maximum number of multipliers of each beneficiary, or None for the maximum number of multipliers
         #[ink(constructor, payable)]
+        #[allow(clippy::too_many_arguments)]
         pub fn new(
             periodicity: u32,
             base_payment: Balance,
             initial_base_multipliers: Vec<String>,
             initial_beneficiaries: Vec<InitialBeneficiary>,
+            max_beneficiaries: u32,
+            max_multipliers: u32,
+            token: Option<AccountId>,
+            existential_deposit_hint: Balance,
+            private: bool,
+            refund_address: Option<AccountId>,
+            unclaimed_expiry_periods: Option<u32>,
+            owner: Option<AccountId>,
+            max_multipliers_per_beneficiary: Option<u32>,
+        ) -> Result<Self, Error> {
+            Self::_new(
+                periodicity,
+                base_payment,
+                initial_base_multipliers,
+                initial_beneficiaries,
+                max_beneficiaries,
+                max_multipliers,
+                token,
+                existential_deposit_hint,
+                private,
+                refund_address,
+                unclaimed_expiry_periods,
+                owner,
+                max_multipliers_per_beneficiary,
+                false,
+            )
+        }
+
+        /// Constructor like new but measuring the periods with the block timestamp instead of the block number
+        /// The periodicity is given in milliseconds and must be a multiple of 1000, as the contract clock counts seconds.
+        /// Every block number of the contract (initial block, period blocks, start blocks, valid until blocks...)
+        /// and the stored periodicity are then in seconds
+        #[ink(constructor, payable)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn new_time_based(
+            periodicity: u32,
+            base_payment: Balance,
+            initial_base_multipliers: Vec<String>,
+            initial_beneficiaries: Vec<InitialBeneficiary>,
+            max_beneficiaries: u32,
+            max_multipliers: u32,
+            token: Option<AccountId>,
+            existential_deposit_hint: Balance,
+            private: bool,
+            refund_address: Option<AccountId>,
+            unclaimed_expiry_periods: Option<u32>,
+            owner: Option<AccountId>,
+            max_multipliers_per_beneficiary: Option<u32>,
+        ) -> Result<Self, Error> {
+            Self::_new(
+                periodicity,
+                base_payment,
+                initial_base_multipliers,
+                initial_beneficiaries,
+                max_beneficiaries,
+                max_multipliers,
+                token,
+                existential_deposit_hint,
+                private,
+                refund_address,
+                unclaimed_expiry_periods,
+                owner,
+                max_multipliers_per_beneficiary,
+                true,
+            )
+        }
+
+        #[allow(clippy::too_many_arguments)]
+        fn _new(
+            periodicity: u32,
+            base_payment: Balance,
+            initial_base_multipliers: Vec<String>,
+            initial_beneficiaries: Vec<InitialBeneficiary>,
+            max_beneficiaries: u32,
+            max_multipliers: u32,
+            token: Option<AccountId>,
+            existential_deposit_hint: Balance,
+            private: bool,
+            refund_address: Option<AccountId>,
+            unclaimed_expiry_periods: Option<u32>,
+            owner: Option<AccountId>,
+            max_multipliers_per_beneficiary: Option<u32>,
+            time_based: bool,
         ) -> Result<Self, Error> {
-            let mut instance = Self::default(periodicity, base_payment);
+            let mut instance = Self::default(
+                periodicity,
+                base_payment,
+                max_beneficiaries,
+                max_multipliers,
+                token,
+            );
+
+            instance.existential_deposit_hint = existential_deposit_hint;
+            instance.private = private;
+            instance.refund_address = refund_address;
+            instance.unclaimed_expiry_periods = unclaimed_expiry_periods;
+
+            // The owner is set before the other roles are validated against it
+            if let Some(owner) = owner {
+                instance.ensure_valid_role_assignment(Role::Owner, owner)?;
+                instance.owner = Some(owner);
+            }
 
             // 0 payment or 0 periodicity make no sense
-            if base_payment == 0 || periodicity == 0 {
+            ensure_valid_base_payment(base_payment)?;
+            if periodicity == 0 {
                 return Err(Error::InvalidParams);
             }
 
+            // Unclaimed payments expiring right away would make partial claims lose the rest
+            if unclaimed_expiry_periods == Some(0) {
+                return Err(Error::ExpiryPeriodsCannotBeZero);
+            }
+
+            // In time based mode the clock is the block timestamp in seconds
+            if time_based {
+                instance.time_based = true;
+                instance.periodicity = instance._periodicity_to_clock(periodicity)?;
+                instance.initial_block = instance._now();
+            }
+
+            // The maximums must be at least 1 and not exceed their upper bounds
+            if !(1..=MAX_BENEFICIARIES_LIMIT).contains(&max_beneficiaries)
+                || !(1..=MAX_MULTIPLIERS_LIMIT).contains(&max_multipliers)
+            {
+                return Err(Error::InvalidParams);
+            }
+
+            // The multipliers of a beneficiary can't exceed the multipliers of the contract
+            if let Some(max) = max_multipliers_per_beneficiary {
+                if !(1..=max_multipliers).contains(&max) {
+                    return Err(Error::InvalidParams);
+                }
+                instance.max_multipliers_per_beneficiary = max;
+            }
+
             // Ensure for duplicate beneficiaries
             ensure_no_duplicate_beneficiaries(
                 &initial_beneficiaries.iter().map(|b| b.account_id).collect(),
             )?;
 
             // Ensure beneficiaries and multipliers limits
-            if initial_beneficiaries.len() > MAX_BENEFICIARIES {
+            if initial_beneficiaries.len() > max_beneficiaries as usize {
                 return Err(Error::MaxBeneficiariesExceeded);
             }
-            if initial_base_multipliers.len() > MAX_MULTIPLIERS {
+            if initial_base_multipliers.len() > max_multipliers as usize {
                 return Err(Error::MaxMultipliersExceeded);
             }
 
+            // Everything is validated before the first storage write, so a failing instantiation writes nothing
+            instance._validate_base_multipliers(&initial_base_multipliers)?;
+            instance._validate_initial_beneficiaries(
+                &initial_beneficiaries,
+                initial_base_multipliers.len(),
+            )?;
+            if let Some(refund_address) = refund_address {
+                instance.ensure_valid_role_assignment(Role::RefundAddress, refund_address)?;
+            }
+
             instance._create_base_multipliers(initial_base_multipliers);
 
-            instance._create_initial_beneficiaries(initial_beneficiaries)?;
+            instance._create_initial_beneficiaries(initial_beneficiaries);
 
             Ok(instance)
         }
 
-        fn _create_initial_beneficiaries(
-            &mut self,
-            initial_beneficiaries: Vec<InitialBeneficiary>,
+        // Validate the initial beneficiaries against the base multipliers to create, without reading the storage
+        // The base multipliers are created with the ids 0 to multiplier_count - 1
+        fn _validate_initial_beneficiaries(
+            &self,
+            initial_beneficiaries: &[InitialBeneficiary],
+            multiplier_count: usize,
         ) -> Result<(), Error> {
-            // Create the initial beneficiaries
             for beneficiary_data in initial_beneficiaries.iter() {
-                if beneficiary_data.multipliers.len() != self.multipliers_list.len() {
+                if beneficiary_data.multipliers.len() != multiplier_count {
                     return Err(Error::InvalidMultipliersLength);
                 }
 
-                // Ensure for duplicate multipliers
+                // Ensure the multipliers are valid and not duplicated
+                if multiplier_count > self.max_multipliers_per_beneficiary as usize {
+                    return Err(Error::MaxMultipliersPerBeneficiaryExceeded);
+                }
+                for (multiplier_id, multiplier) in beneficiary_data.multipliers.iter() {
+                    if *multiplier_id as usize >= multiplier_count {
+                        return Err(Error::MultiplierNotFound);
+                    }
+                    if *multiplier > MAX_MULTIPLIER {
+                        return Err(Error::MultiplierValueTooLarge);
+                    }
+                }
                 ensure_no_duplicate_multipliers(&beneficiary_data.multipliers)?;
+                ensure_valid_beneficiary_metadata(&beneficiary_data.name, &beneficiary_data.notes)?;
+
+                self.ensure_valid_role_assignment(Role::Beneficiary, beneficiary_data.account_id)?;
+            }
+
+            Ok(())
+        }
+
+        // Validate the names of the base multipliers to create, without reading the storage
+        fn _validate_base_multipliers(
+            &self,
+            initial_base_multipliers: &[String],
+        ) -> Result<(), Error> {
+            for (index, base_multiplier) in initial_base_multipliers.iter().enumerate() {
+                // Ensure the name is not empty nor duplicated
+                self.ensure_multiplier_name_is_valid(base_multiplier, None)?;
+                if initial_base_multipliers[..index].contains(base_multiplier) {
+                    return Err(Error::DuplicatedMultiplierName);
+                }
+            }
+
+            Ok(())
+        }
 
+        // Create the initial beneficiaries, validated by _validate_initial_beneficiaries
+        fn _create_initial_beneficiaries(
+            &mut self,
+            initial_beneficiaries: Vec<InitialBeneficiary>,
+        ) {
+            for beneficiary_data in initial_beneficiaries.iter() {
                 let multipliers = vec_to_btreemap(&beneficiary_data.multipliers);
 
                 let beneficiary = Beneficiary {
@@ -339,6 +1070,16 @@ mod open_payroll {
                     multipliers,
                     unclaimed_payments: 0,
                     last_updated_period_block: self.initial_block,
+                    paused_blocks_at_update: 0,
+                    suspended: false,
+                    start_block: beneficiary_data.start_block,
+                    max_per_claim: None,
+                    group_id: None,
+                    deactivated: false,
+                    name: beneficiary_data.name.clone(),
+                    notes: beneficiary_data.notes.clone(),
+                    payout_override: None,
+                    unclaimed_bonuses: 0,
                 };
 
                 self.beneficiaries
@@ -346,12 +1087,10 @@ mod open_payroll {
                 self.beneficiaries_accounts
                     .push(beneficiary_data.account_id);
             }
-
-            Ok(())
         }
 
+        // Create the base multipliers, validated by _validate_base_multipliers
         fn _create_base_multipliers(&mut self, initial_base_multipliers: Vec<String>) {
-            // Create the base multipliers
             for base_multiplier in initial_base_multipliers.iter() {
                 self.base_multipliers.insert(
                     self.next_multiplier_id,
@@ -369,12 +1108,79 @@ mod open_payroll {
         /// Claim payment for a single account id
         /// If the amount is 0 no money is transferred. However, the "unclaimed_payments" field is set to the total
         /// value that the beneficiary has yet to claim.
+        /// If the beneficiary has a max_per_claim, the amount transferred is clamped to it and the rest stays unclaimed.
+        /// It can be called by the beneficiary or by its claim delegate, the payment always goes to the beneficiary
+        /// or to its payout address if set.
+        /// Anyone can claim an amount of 0 to update the unclaimed payments, as the owner does before changing parameters.
+        /// Suspended beneficiaries can't claim a non zero amount until they are unsuspended.
         #[ink(message)]
         pub fn claim_payment(
             &mut self,
             account_id: AccountId,
             amount: Balance,
         ) -> Result<(), Error> {
+            self._claim_payment(account_id, amount, false)?;
+            Ok(())
+        }
+
+        /// Pay a beneficiary everything it can claim, so it gets paid without paying the transaction fee
+        /// Only the owner can push a payment, which goes to the beneficiary like a claim.
+        /// Returns the amount transferred, which is clamped to the max per claim of the beneficiary
+        #[ink(message)]
+        pub fn push_payment(&mut self, account_id: AccountId) -> Result<Balance, Error> {
+            self.ensure_owner()?;
+            if !self.beneficiaries.contains(account_id) {
+                return Err(Error::AccountNotFound);
+            }
+            let amount = self._get_amount_to_claim(account_id)?;
+            self._claim_payment(account_id, amount, true)
+        }
+
+        /// Claim everything available except leave_unclaimed, which stays unclaimed like after a partial claim
+        /// The amount is computed when the claim is executed, so it does not depend on the period it lands in.
+        /// It fails with NotEnoughToLeaveUnclaimed if less than leave_unclaimed is available
+        #[ink(message)]
+        pub fn claim_leaving(
+            &mut self,
+            account_id: AccountId,
+            leave_unclaimed: Balance,
+        ) -> Result<(), Error> {
+            self._claim_leaving(account_id, leave_unclaimed)?;
+            Ok(())
+        }
+
+        /// Claim everything available in a single call, and return the amount transferred
+        /// It is a claim_payment of the amount to claim computed when the claim is executed, so the same rules apply.
+        /// The amount is clamped to the max per claim of the beneficiary, and nothing is transferred if it is 0
+        #[ink(message)]
+        pub fn claim_all(&mut self, account_id: AccountId) -> Result<Balance, Error> {
+            self._claim_leaving(account_id, 0)
+        }
+
+        // Claim everything available except leave_unclaimed, see claim_leaving, and return the amount transferred
+        fn _claim_leaving(
+            &mut self,
+            account_id: AccountId,
+            leave_unclaimed: Balance,
+        ) -> Result<Balance, Error> {
+            if !self.beneficiaries.contains(account_id) {
+                return Err(Error::AccountNotFound);
+            }
+            let amount = self
+                ._get_amount_to_claim(account_id)?
+                .checked_sub(leave_unclaimed)
+                .ok_or(Error::NotEnoughToLeaveUnclaimed)?;
+            self._claim_payment(account_id, amount, false)
+        }
+
+        // Claim payment for a single account id, see claim_payment, and return the amount transferred
+        // A payment pushed by the owner does not need to be claimed by the beneficiary or its delegate
+        fn _claim_payment(
+            &mut self,
+            account_id: AccountId,
+            amount: Balance,
+            pushed_by_owner: bool,
+        ) -> Result<Balance, Error> {
             self.ensure_is_not_paused()?;
 
             let beneficiary_res = self.beneficiaries.get(account_id);
@@ -384,52 +1190,98 @@ mod open_payroll {
                 None => return Err(Error::AccountNotFound),
             };
 
-            let current_block = self.env().block_number();
+            // Claims are also blocked while the group of the beneficiary is paused
+            if let Some(group_id) = beneficiary.group_id {
+                if self.is_group_paused(group_id) {
+                    return Err(Error::GroupIsPaused);
+                }
+            }
+
+            // Only the beneficiary or its delegate can claim a non zero amount
+            let caller = self.env().caller();
+            if amount > 0
+                && !pushed_by_owner
+                && caller != account_id
+                && self.claim_delegates.get(account_id) != Some(caller)
+            {
+                return Err(Error::NotAllowedToClaim);
+            }
+
+            // The payments of a suspended beneficiary are frozen
+            if amount > 0 && beneficiary.suspended {
+                return Err(Error::BeneficiaryIsSuspended);
+            }
 
-            // If there are deactivated multipliers, remove them from the beneficiary
-            beneficiary.multipliers.retain(|&k, _| {
-                let multiplier_block_validity =
-                    self.base_multipliers.get(k).unwrap().valid_until_block;
+            let current_block = self._now();
 
-                // We keep the multiplier if it is not deactivated
-                // or if it is deactivated but the current block is before the deactivation block
-                multiplier_block_validity.is_none()
-                    || multiplier_block_validity.unwrap() > current_block
-            });
+            // Keep the period the beneficiary was last updated in before settling its multipliers
+            let last_updated_period_block = beneficiary.last_updated_period_block;
+
+            // Unclaimed payments older than the expiry are lost
+            let expired_payments = self._expire_unclaimed_payments(&mut beneficiary, current_block);
+            if expired_payments > 0 {
+                // Emit the BeneficiaryPaymentExpired event
+                self.env().emit_event(BeneficiaryPaymentExpired {
+                    account_id,
+                    amount: expired_payments,
+                });
+            }
+
+            // If there are expired multipliers, settle what was earned with them and remove them from the beneficiary
+            let unsettled_beneficiary = beneficiary.clone();
+            self._apply_scheduled_beneficiary_update(&mut beneficiary, current_block)?;
+            self._settle_expired_multipliers(&mut beneficiary, current_block)?;
+            self._record_pruned_multipliers(&unsettled_beneficiary, &beneficiary);
 
             // gets the total amount that the beneficiary can claim and ensure the amount is not bigger than that
-            let total_payment = self._get_amount_to_claim(account_id, true);
+            let total_payment = self._get_accrued_amount(&beneficiary, current_block)?;
             if amount > total_payment {
                 return Err(Error::ClaimedAmountIsBiggerThanAvailable);
             }
 
+            // Clamp the amount to the max allowed in a single claim
+            let (amount, clamped) = match beneficiary.max_per_claim {
+                Some(max_per_claim) if amount > max_per_claim => (max_per_claim, true),
+                _ => (amount, false),
+            };
+
             // Check if the treasury has enough balance
-            let treasury_balance = self.env().balance();
+            let treasury_balance = self.get_contract_balance();
             if amount > treasury_balance {
                 return Err(Error::NotEnoughBalanceInTreasury);
             }
 
             let claiming_period_block = self.get_current_period_initial_block();
 
-            // If the beneficiary has not claimed anything in the current period
-            if beneficiary.last_updated_period_block != claiming_period_block {
-                self._update_claims_in_period(claiming_period_block);
-            }
+            // Check the claim fits in the budget of the period
+            self.ensure_within_period_budget(claiming_period_block, amount)?;
+
+            // Count the beneficiary as claimed in the current period
+            self._update_claims_in_period(claiming_period_block, account_id, amount);
 
             // Update the beneficiary
-            self.beneficiaries.insert(
-                account_id,
-                &Beneficiary {
+            let unclaimed_payments = total_payment
+                .checked_sub(amount)
+                .ok_or(Error::ArithmeticUnderflow)?;
+            beneficiary.unclaimed_payments = unclaimed_payments;
+            // The claim is paid from the payments before the bonuses, which never expire
+            beneficiary.unclaimed_bonuses = beneficiary.unclaimed_bonuses.min(unclaimed_payments);
+            self._set_last_updated_period_block(&mut beneficiary, claiming_period_block);
+            self.beneficiaries.insert(account_id, &beneficiary);
+            self._remove_due_beneficiary_update(account_id, current_block);
+
+            // Transfer the amount to the beneficiary and record the claim if amount > 0
+            if amount > 0 {
+                self._transfer(beneficiary.payout_override.unwrap_or(account_id), amount)?;
+                self._add_paid_out(amount)?;
+                self._record_claim(
                     account_id,
-                    multipliers: beneficiary.multipliers,
-                    unclaimed_payments: total_payment - amount,
-                    last_updated_period_block: claiming_period_block,
-                },
-            );
-
-            // Transfer the amount to the beneficiary if amount > 0
-            if amount > 0 && self.env().transfer(account_id, amount).is_err() {
-                return Err(Error::TransferFailed);
+                    current_block,
+                    amount,
+                    last_updated_period_block,
+                    claiming_period_block,
+                    unclaimed_payments,
+                );
             }
 
             // Emit the Claimed event
@@ -438,15 +1290,133 @@ mod open_payroll {
                 amount,
                 total_payment,
                 claiming_period_block,
+                clamped,
+            });
+
+            Ok(amount)
+        }
+
+        /// Add the transferred value to the treasury
+        /// Anyone can call it, so donations are allowed
+        /// Contracts paying in a PSP22 token are funded by transferring the token instead
+        #[ink(message, payable)]
+        pub fn top_up(&mut self) -> Result<(), Error> {
+            let amount = self._get_native_top_up()?;
+
+            // Emit the TreasuryToppedUp event
+            self.env().emit_event(TreasuryToppedUp {
+                from: self.env().caller(),
+                amount,
+                new_balance: self.env().balance(),
+            });
+
+            Ok(())
+        }
+
+        /// Donate the transferred value to the treasury
+        /// Like top_up, but donations are rejected while the contract is paused
+        #[ink(message, payable)]
+        pub fn donate_to_treasury(&mut self) -> Result<(), Error> {
+            self.ensure_is_not_paused()?;
+            let amount = self._get_native_top_up()?;
+
+            // Emit the TreasuryFunded event
+            self.env().emit_event(TreasuryFunded {
+                amount,
+                from: self.env().caller(),
             });
 
             Ok(())
         }
 
-        /// Deactivate a multiplier
+        /// Withdraw balance that is not needed to pay the beneficiaries
+        /// The treasury keeps enough balance for all the debts up-to-date and the payments of the next period
+        #[ink(message)]
+        pub fn withdraw_excess_balance(
+            &mut self,
+            amount: Balance,
+            to: AccountId,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if amount == 0 {
+                return Err(Error::InvalidParams);
+            }
+
+            if amount > self.get_withdrawable_balance() {
+                return Err(Error::WithdrawalExceedsSafeLimit);
+            }
+
+            self._transfer(to, amount)?;
+
+            // Emit the ExcessBalanceWithdrawn event
+            self.env().emit_event(ExcessBalanceWithdrawn { to, amount });
+
+            Ok(())
+        }
+
+        /// Withdraw the surplus of the treasury to the owner
+        /// Only the balance left after all the debts up-to-date and the existential deposit can be withdrawn,
+        /// so that no payment is stranded
+        /// Unlike withdraw_excess_balance, nothing is kept for the next period, as it is meant for a payroll winding down
+        #[ink(message)]
+        pub fn withdraw_surplus(&mut self, amount: Balance) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if amount == 0 {
+                return Err(Error::InvalidParams);
+            }
+
+            let surplus = self
+                ._get_spendable_balance()
+                .saturating_sub(self.get_total_debts());
+            if amount > surplus {
+                return Err(Error::NotEnoughBalanceInTreasury);
+            }
+
+            let owner = self.env().caller();
+            self._transfer(owner, amount)?;
+
+            // Emit the SurplusWithdrawn event
+            self.env().emit_event(SurplusWithdrawn { owner, amount });
+
+            Ok(())
+        }
+
+        /// Return the balance above the maximum runway to the refund address
+        /// Anyone can call it, it does nothing if there is no maximum runway or no excess
+        /// Returns the amount sent
+        #[ink(message)]
+        pub fn return_excess(&mut self) -> Result<Balance, Error> {
+            let to = self.refund_address.ok_or(Error::RefundAddressNotSet)?;
+            let amount = self.get_returnable_excess();
+            if amount == 0 {
+                return Ok(0);
+            }
+
+            self._transfer(to, amount)?;
+
+            // Emit the ExcessReturned event
+            self.env().emit_event(ExcessReturned { to, amount });
+
+            Ok(amount)
+        }
+
+        /// Deactivate a multiplier at the end of the current period
         /// It can be deleted one period after deactivation if every beneficiary has claimed the payment
         #[ink(message)]
         pub fn deactivate_multiplier(&mut self, multiplier_id: MultiplierId) -> Result<(), Error> {
+            let valid_until_block = self.get_next_block_period();
+            self.schedule_multiplier_deactivation(multiplier_id, valid_until_block)
+        }
+
+        /// Deactivate a multiplier at a future period boundary
+        /// The periods before that block are still paid with the multiplier
+        #[ink(message)]
+        pub fn schedule_multiplier_deactivation(
+            &mut self,
+            multiplier_id: MultiplierId,
+            valid_until_block: BlockNumber,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
             // Fetch the multiplier
             let mut multiplier = self
                 .base_multipliers
@@ -457,8 +1427,12 @@ mod open_payroll {
                 return Err(Error::MultiplierAlreadyDeactivated);
             }
 
-            // Calculates deactivation on next period
-            let valid_until_block = self.get_current_period_initial_block() + self.periodicity;
+            // The block must start a period after the current one
+            if valid_until_block <= self._now()
+                || !period::is_period_start(self.initial_block, self.periodicity, valid_until_block)
+            {
+                return Err(Error::InvalidParams);
+            }
 
             // Set that value in the multiplier
             multiplier.valid_until_block = Some(valid_until_block);
@@ -473,13 +1447,54 @@ mod open_payroll {
             Ok(())
         }
 
+        /// Cancel the deactivation of a multiplier, the same as reactivate_multiplier
+        #[ink(message)]
+        pub fn cancel_multiplier_deactivation(
+            &mut self,
+            multiplier_id: MultiplierId,
+        ) -> Result<(), Error> {
+            self.reactivate_multiplier(multiplier_id)
+        }
+
+        /// Reactivate a deactivated multiplier
+        /// It is only possible before the multiplier expires
+        #[ink(message)]
+        pub fn reactivate_multiplier(&mut self, multiplier_id: MultiplierId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let mut multiplier = self
+                .base_multipliers
+                .get(multiplier_id)
+                .ok_or(Error::MultiplierNotFound)?;
+
+            // Ensure the multiplier is deactivated but not expired yet
+            let valid_until_block = multiplier
+                .valid_until_block
+                .ok_or(Error::MultiplierNotDeactivated)?;
+            if self._now() >= valid_until_block {
+                return Err(Error::MultiplierExpired);
+            }
+
+            // Another active multiplier could be using the name in the meantime
+            self.ensure_multiplier_name_is_valid(&multiplier.name, Some(multiplier_id))?;
+
+            multiplier.valid_until_block = None;
+            self.base_multipliers.insert(multiplier_id, &multiplier);
+
+            // Emit the MultiplierReactivated event
+            self.env()
+                .emit_event(MultiplierReactivated { multiplier_id });
+
+            Ok(())
+        }
+
         /// Delete a multiplier when conditions are met
         #[ink(message)]
         pub fn delete_unused_multiplier(
             &mut self,
             multiplier_id: MultiplierId,
         ) -> Result<(), Error> {
-            let current_block = self.env().block_number();
+            self.ensure_owner()?;
+            let current_block = self._now();
             let multiplier = self
                 .base_multipliers
                 .get(multiplier_id)
@@ -491,13 +1506,28 @@ mod open_payroll {
             }
 
             // Ensure if the multiplier is expired
-            if current_block <= multiplier.valid_until_block.unwrap() {
+            // At valid_until_block the multiplier is already expired, it is not paid for that period
+            if current_block < multiplier.valid_until_block.unwrap() {
                 return Err(Error::MultiplierNotExpired);
             }
 
             // Ensure if all beneficiaries have claimed the payment
             self.ensure_all_claimed_in_period()?;
 
+            // Settle what was earned with the multiplier before removing it from the beneficiaries
+            // This also sweeps any dangling multiplier ids left in the beneficiaries
+            for account_id in self.beneficiaries_accounts.clone().iter() {
+                let beneficiary = self.beneficiaries.get(account_id).unwrap();
+                let mut settled_beneficiary = beneficiary.clone();
+                self._apply_scheduled_beneficiary_update(&mut settled_beneficiary, current_block)?;
+                self._settle_expired_multipliers(&mut settled_beneficiary, current_block)?;
+                if settled_beneficiary != beneficiary {
+                    self._record_pruned_multipliers(&beneficiary, &settled_beneficiary);
+                    self.beneficiaries.insert(account_id, &settled_beneficiary);
+                }
+                self._remove_due_beneficiary_update(*account_id, current_block);
+            }
+
             // Remove multiplier from multipliers_list
             self.multipliers_list.retain(|x| *x != multiplier_id);
 
@@ -518,11 +1548,12 @@ mod open_payroll {
         #[ink(message)]
         pub fn propose_transfer_ownership(&mut self, new_owner: AccountId) -> Result<(), Error> {
             self.ensure_owner()?;
+            self.ensure_valid_role_assignment(Role::ProposedOwner, new_owner)?;
             self.proposed_owner = Some(new_owner);
 
             // Emit the OwnershipTransferred event
             self.env().emit_event(OwnershipProposed {
-                current_owner: self.owner,
+                current_owner: self.env().caller(),
                 proposed_owner: new_owner,
             });
 
@@ -534,1408 +1565,8423 @@ mod open_payroll {
         /// by the new owner. This prevents accidental ownership transfers.
         #[ink(message)]
         pub fn accept_ownership(&mut self) -> Result<(), Error> {
-            let old_owner = self.owner;
-            if self.proposed_owner == Some(self.env().caller()) {
-                self.owner = self.proposed_owner.unwrap();
-                self.proposed_owner = None;
-
-                self.env().emit_event(OwnershipAccepted {
-                    previous_owner: old_owner,
-                    new_owner: self.owner,
-                });
-
-                Ok(())
-            } else {
-                Err(Error::NotOwner)
+            // There is no proposed owner once the ownership is renounced
+            match (self.owner, self.proposed_owner) {
+                (Some(old_owner), Some(new_owner)) if new_owner == self.env().caller() => {
+                    self.owner = Some(new_owner);
+                    self.proposed_owner = None;
+
+                    self.env().emit_event(OwnershipAccepted {
+                        previous_owner: old_owner,
+                        new_owner,
+                    });
+
+                    Ok(())
+                }
+                _ => Err(Error::NotOwner),
             }
         }
 
-        /// Add a new beneficiary
+        /// Cancel the pending ownership transfer, so the proposed owner can no longer accept it
         #[ink(message)]
-        pub fn add_beneficiary(
-            &mut self,
-            account_id: AccountId,
-            multipliers: Vec<(MultiplierId, Multiplier)>,
-        ) -> Result<(), Error> {
-            // Calls the function to do the checking
-            self.ensure_beneficiary_to_add(account_id, &multipliers)?;
-
-            let multipliers_vec = multipliers.clone();
-            let multipliers = vec_to_btreemap(&multipliers);
+        pub fn cancel_ownership_transfer(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let proposed_owner = self
+                .proposed_owner
+                .take()
+                .ok_or(Error::NoPendingOwnershipTransfer)?;
+
+            self.env().emit_event(OwnershipTransferCancelled {
+                owner: self.env().caller(),
+                proposed_owner,
+            });
 
-            // insert the new beneficiary
-            self.beneficiaries.insert(
-                account_id,
-                &Beneficiary {
-                    account_id,
-                    multipliers,
-                    unclaimed_payments: 0,
-                    last_updated_period_block: self.get_current_period_initial_block(),
-                },
-            );
+            Ok(())
+        }
 
-            // Add the beneficiary to the list of beneficiaries
-            self.beneficiaries_accounts.push(account_id);
+        /// Renounce the ownership of the contract, cancelling any pending transfer
+        /// Every owner only message fails from then on, while the admins keep their rights
+        /// and the beneficiaries keep claiming their payments
+        #[ink(message)]
+        pub fn renounce_ownership(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.owner = None;
+            self.proposed_owner = None;
 
-            // Emit the BeneficiaryAdded event
-            self.env().emit_event(BeneficiaryAdded {
-                account_id,
-                multipliers_vec,
+            self.env().emit_event(OwnershipRenounced {
+                previous_owner: self.env().caller(),
             });
 
             Ok(())
         }
 
-        /// Update an existing beneficiary
+        /// Replace the code of the contract, keeping its storage and balance
+        /// The new code must keep the storage layout compatible, and migrate must be called right after
+        /// when it expects a new version of the storage
         #[ink(message)]
-        pub fn update_beneficiary(
-            &mut self,
-            account_id: AccountId,
-            multipliers: Vec<(MultiplierId, Multiplier)>,
-        ) -> Result<(), Error> {
+        pub fn set_code(&mut self, code_hash: Hash) -> Result<(), Error> {
             self.ensure_owner()?;
+            // It fails if no code was uploaded with that hash
+            self.env()
+                .set_code_hash(&code_hash)
+                .map_err(|_| Error::InvalidParams)?;
 
-            // Ensure that the beneficiary exists
-            if !self.beneficiaries.contains(account_id) {
-                return Err(Error::AccountNotFound);
-            }
-
-            // Check that the multipliers are valid
-            self.ensure_multipliers_are_valid(&multipliers)?;
-            ensure_no_duplicate_multipliers(&multipliers)?;
+            self.env().emit_event(CodeUpdated { code_hash });
 
-            let multipliers_vec = multipliers.clone();
-            let multipliers = vec_to_btreemap(&multipliers);
+            Ok(())
+        }
 
-            // calculate the amount to claim to be transferred to the uncleared payments
-            let unclaimed_payments = self._get_amount_to_claim(account_id, false);
+        /// Migrate the storage to the version of the current code
+        /// Each code revision changing the storage transforms it here from the stored version,
+        /// and it fails with MigrationFailed once the storage is up to date, so it only runs once per version
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let from_version = self.contract_version;
+            if from_version >= CONTRACT_VERSION {
+                return Err(Error::MigrationFailed);
+            }
 
-            // update de beneficiary with new multipliers and new unclaimed payments
-            self.beneficiaries.insert(
-                account_id,
-                &Beneficiary {
-                    account_id,
-                    multipliers,
-                    unclaimed_payments,
-                    last_updated_period_block: self.get_current_period_initial_block(),
-                },
-            );
+            // No storage changes yet
 
-            // Emit the BeneficiaryUpdated event
-            self.env().emit_event(BeneficiaryUpdated {
-                account_id,
-                multipliers_vec,
+            self.contract_version = CONTRACT_VERSION;
+            self.env().emit_event(Migrated {
+                from_version,
+                to_version: CONTRACT_VERSION,
             });
 
             Ok(())
         }
 
-        /// Remove a beneficiary
+        /// Add a new beneficiary
         #[ink(message)]
-        pub fn remove_beneficiary(&mut self, account_id: AccountId) -> Result<(), Error> {
-            self.ensure_owner()?;
-            if !self.beneficiaries.contains(account_id) {
-                return Err(Error::AccountNotFound);
+        pub fn add_beneficiary(
+            &mut self,
+            account_id: AccountId,
+            multipliers: Vec<(MultiplierId, MultiplierValue)>,
+        ) -> Result<(), Error> {
+            self._add_beneficiary(account_id, multipliers, None, None, None)
+        }
+
+        /// Add a new beneficiary with a name and notes, used in reports
+        #[ink(message)]
+        pub fn add_beneficiary_with_metadata(
+            &mut self,
+            account_id: AccountId,
+            multipliers: Vec<(MultiplierId, MultiplierValue)>,
+            name: Option<String>,
+            notes: Option<String>,
+        ) -> Result<(), Error> {
+            self._add_beneficiary(account_id, multipliers, None, name, notes)
+        }
+
+        /// Add a new beneficiary with a vesting cliff
+        /// No payments are accrued before the first period starting at or after start_block
+        /// The start block can't be before the current period, set_beneficiary_accrual_start grants back-pay
+        #[ink(message)]
+        pub fn add_beneficiary_with_start(
+            &mut self,
+            account_id: AccountId,
+            multipliers: Vec<(MultiplierId, MultiplierValue)>,
+            start_block: BlockNumber,
+        ) -> Result<(), Error> {
+            if start_block < self.get_current_period_initial_block() {
+                return Err(Error::InvalidParams);
             }
-            self.beneficiaries.remove(account_id);
+            self._add_beneficiary(account_id, multipliers, Some(start_block), None, None)
+        }
 
-            // Remove the beneficiary from the list of beneficiaries
-            self.beneficiaries_accounts.retain(|x| *x != account_id);
+        /// Add several beneficiaries at once
+        /// The whole batch is validated before adding any of them, so either all or none are added
+        #[ink(message)]
+        pub fn bulk_add_beneficiaries(
+            &mut self,
+            beneficiaries: Vec<InitialBeneficiary>,
+        ) -> Result<(), Error> {
+            self.ensure_owner_or_admin()?;
 
-            // Emit the BeneficiaryRemoved event
-            self.env().emit_event(BeneficiaryRemoved { account_id });
+            // Ensure there are no duplicates among the new beneficiaries
+            ensure_no_duplicate_beneficiaries(
+                &beneficiaries.iter().map(|b| b.account_id).collect(),
+            )?;
+
+            // Ensure that the number of beneficiaries does not exceed the maximum
+            if self.beneficiaries_accounts.len() + beneficiaries.len()
+                > self.max_beneficiaries as usize
+            {
+                return Err(Error::MaxBeneficiariesExceeded);
+            }
+
+            for beneficiary in beneficiaries.iter() {
+                self.ensure_beneficiary_to_add(
+                    beneficiary.account_id,
+                    &beneficiary.multipliers,
+                    &beneficiary.name,
+                    &beneficiary.notes,
+                )?;
+            }
+
+            for beneficiary in beneficiaries {
+                self._insert_beneficiary(
+                    beneficiary.account_id,
+                    beneficiary.multipliers,
+                    beneficiary.start_block,
+                    beneficiary.name,
+                    beneficiary.notes,
+                );
+            }
 
             Ok(())
         }
 
-        /// Update the base_payment
-        /// It makes sense once all the beneficiaries have claimed their payments
-        #[ink(message)]
-        pub fn update_base_payment(&mut self, base_payment: Balance) -> Result<(), Error> {
-            self.ensure_owner()?;
-            if base_payment == 0 {
-                return Err(Error::InvalidParams);
+        fn _add_beneficiary(
+            &mut self,
+            account_id: AccountId,
+            multipliers: Vec<(MultiplierId, MultiplierValue)>,
+            start_block: Option<BlockNumber>,
+            name: Option<String>,
+            notes: Option<String>,
+        ) -> Result<(), Error> {
+            self.ensure_owner_or_admin()?;
+
+            // Ensure that the number of beneficiaries does not exceed the maximum
+            if self.beneficiaries_accounts.len() + 1 > self.max_beneficiaries as usize {
+                return Err(Error::MaxBeneficiariesExceeded);
             }
 
-            // Ensure if all payments are up to date
-            self.ensure_all_claimed_in_period()?;
-            self.base_payment = base_payment;
+            self.ensure_beneficiary_to_add(account_id, &multipliers, &name, &notes)?;
+            self._insert_beneficiary(account_id, multipliers, start_block, name, notes);
 
             Ok(())
         }
 
-        /// Add a new base multiplier
-        /// It's not checking for duplicates because it's just a string
+        // Insert a beneficiary already checked by ensure_beneficiary_to_add
+        fn _insert_beneficiary(
+            &mut self,
+            account_id: AccountId,
+            multipliers: Vec<(MultiplierId, MultiplierValue)>,
+            start_block: Option<BlockNumber>,
+            name: Option<String>,
+            notes: Option<String>,
+        ) {
+            let multipliers_vec = multipliers.clone();
+            let multipliers = vec_to_btreemap(&multipliers);
+
+            // insert the new beneficiary
+            self.beneficiaries.insert(
+                account_id,
+                &Beneficiary {
+                    account_id,
+                    multipliers,
+                    unclaimed_payments: 0,
+                    last_updated_period_block: self.get_current_period_initial_block(),
+                    paused_blocks_at_update: self.get_total_paused_blocks(),
+                    suspended: false,
+                    start_block,
+                    max_per_claim: None,
+                    group_id: None,
+                    deactivated: false,
+                    name,
+                    notes,
+                    payout_override: None,
+                    unclaimed_bonuses: 0,
+                },
+            );
+
+            // Add the beneficiary to the list of beneficiaries
+            self.beneficiaries_accounts.push(account_id);
+
+            // Emit the BeneficiaryAdded event
+            self.env().emit_event(BeneficiaryAdded {
+                account_id,
+                multipliers_vec,
+            });
+        }
+
+        /// Update an existing beneficiary
+        /// The periods elapsed until now are kept as unclaimed payments, paid with the previous multipliers.
+        /// With effective_from_period the new multipliers are scheduled from the start of that future period instead,
+        /// so the current period is still paid with the previous multipliers. An update replaces the scheduled one if any
         #[ink(message)]
-        pub fn add_base_multiplier(&mut self, name: String) -> Result<(), Error> {
+        pub fn update_beneficiary(
+            &mut self,
+            account_id: AccountId,
+            multipliers: Vec<(MultiplierId, MultiplierValue)>,
+            effective_from_period: Option<u32>,
+        ) -> Result<(), Error> {
             self.ensure_owner()?;
 
-            // Ensure that the number of multipliers does not exceed the maximum
-            if self.multipliers_list.len() + 1 > MAX_MULTIPLIERS {
-                return Err(Error::MaxMultipliersExceeded);
+            // Ensure that the beneficiary exists
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+
+            // Check that the multipliers are valid
+            self.ensure_multipliers_are_valid(&multipliers)?;
+            ensure_no_duplicate_multipliers(&multipliers)?;
+
+            let multipliers_vec = multipliers.clone();
+            let multipliers = vec_to_btreemap(&multipliers);
+
+            if let Some(period_number) = effective_from_period {
+                let activation_block =
+                    period::period_block(self.initial_block, self.periodicity, period_number)
+                        .ok_or(Error::InvalidParams)?;
+                if activation_block <= self.get_current_period_initial_block() {
+                    return Err(Error::InvalidParams);
+                }
+                self.scheduled_beneficiary_updates.insert(
+                    account_id,
+                    &ScheduledBeneficiaryUpdate {
+                        activation_block,
+                        multipliers,
+                    },
+                );
+
+                // Emit the BeneficiaryUpdateScheduled event
+                self.env().emit_event(BeneficiaryUpdateScheduled {
+                    account_id,
+                    multipliers_vec,
+                    activation_block,
+                });
+
+                return Ok(());
             }
 
-            let base_multiplier = BaseMultiplier::new(name.clone());
+            // calculate the amount to claim to be transferred to the uncleared payments
+            // It is paid with the multipliers in effect in each period, so before replacing them
+            let unclaimed_payments = self._snapshot_amount_to_claim(&mut beneficiary)?;
 
-            self.base_multipliers
-                .insert(self.next_multiplier_id, &base_multiplier);
+            // update de beneficiary with new multipliers and new unclaimed payments
+            beneficiary.multipliers = multipliers;
+            beneficiary.unclaimed_payments = unclaimed_payments;
+            self._set_last_updated_period_block(
+                &mut beneficiary,
+                self.get_current_period_initial_block(),
+            );
+            self.beneficiaries.insert(account_id, &beneficiary);
+            self.scheduled_beneficiary_updates.remove(account_id);
 
-            self.multipliers_list.push(self.next_multiplier_id);
+            // Emit the BeneficiaryUpdated event
+            self.env().emit_event(BeneficiaryUpdated {
+                account_id,
+                multipliers_vec,
+            });
 
-            // Increment the next_multiplier_id checking for overflow
-            self.next_multiplier_id = match self.next_multiplier_id.checked_add(1) {
-                Some(val) => val,
-                None => return Err(Error::MultiplierIdOverflow),
-            };
+            Ok(())
+        }
 
-            // Emit the BaseMultiplierAdded event
-            self.env().emit_event(BaseMultiplierAdded {
-                multiplier_id: self.next_multiplier_id - 1,
+        /// Update the name and the notes of a beneficiary, keeping its multipliers and payments
+        #[ink(message)]
+        pub fn update_beneficiary_metadata(
+            &mut self,
+            account_id: AccountId,
+            name: Option<String>,
+            notes: Option<String>,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            ensure_valid_beneficiary_metadata(&name, &notes)?;
+
+            beneficiary.name = name.clone();
+            beneficiary.notes = notes.clone();
+            self.beneficiaries.insert(account_id, &beneficiary);
+
+            // Emit the BeneficiaryMetadataUpdated event
+            self.env().emit_event(BeneficiaryMetadataUpdated {
+                account_id,
                 name,
+                notes,
             });
 
             Ok(())
         }
 
-        /// Update the periodicity of the payments
-        /// All payments must be claimed before updating the periodicity
+        /// Deactivate a beneficiary before removing it
+        /// The payments earned until now are kept as unclaimed payments and can still be claimed,
+        /// but no new payments are accrued. Once they are claimed the beneficiary can be removed
         #[ink(message)]
-        pub fn update_periodicity(&mut self, periodicity: u32) -> Result<(), Error> {
+        pub fn deactivate_beneficiary(&mut self, account_id: AccountId) -> Result<(), Error> {
             self.ensure_owner()?;
-            if periodicity == 0 {
-                return Err(Error::InvalidParams);
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            if beneficiary.deactivated {
+                return Err(Error::BeneficiaryAlreadyDeactivated);
             }
 
-            // Ensure if all payments are up to date
-            // self.ensure_all_payments_uptodate()?;
-            self.ensure_all_claimed_in_period()?;
-            self.periodicity = periodicity;
+            // Keep what was earned before the deactivation
+            beneficiary.unclaimed_payments = self._snapshot_amount_to_claim(&mut beneficiary)?;
+            self._set_last_updated_period_block(
+                &mut beneficiary,
+                self.get_current_period_initial_block(),
+            );
+            beneficiary.deactivated = true;
+            self.beneficiaries.insert(account_id, &beneficiary);
 
-            // Emit the PeriodicityUpdated event
-            self.env().emit_event(PeriodicityUpdated { periodicity });
+            // Emit the BeneficiaryDeactivated event
+            self.env().emit_event(BeneficiaryDeactivated {
+                account_id,
+                unclaimed_payments: beneficiary.unclaimed_payments,
+            });
 
             Ok(())
         }
 
-        /// Pause the contract
-        /// Pausing will only avoid to call the claim function
+        /// Remove a beneficiary
+        /// The payments it has to claim are transferred to it like a claim pushed by the owner:
+        /// the treasury and the budget of the period must cover them, and it fails with
+        /// BeneficiaryHasUnclaimedPayments while the contract or its group is paused or the beneficiary is suspended
+        /// The max per claim of the beneficiary does not apply, since it is its last payment
+        /// If force is true those payments are dropped instead
         #[ink(message)]
-        pub fn pause(&mut self) -> Result<(), Error> {
+        pub fn remove_beneficiary(
+            &mut self,
+            account_id: AccountId,
+            force: bool,
+        ) -> Result<(), Error> {
             self.ensure_owner()?;
-            if self.is_paused() {
-                return Ok(());
+            let beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            let settled_amount = match force {
+                true => 0,
+                false => self._get_amount_to_claim(account_id)?,
+            };
+            let claiming_period_block = self.get_current_period_initial_block();
+            if settled_amount > 0 {
+                if !self._can_claim(&beneficiary) {
+                    return Err(Error::BeneficiaryHasUnclaimedPayments);
+                }
+                if settled_amount > self.get_contract_balance() {
+                    return Err(Error::NotEnoughBalanceInTreasury);
+                }
+                self.ensure_within_period_budget(claiming_period_block, settled_amount)?;
             }
-            self.paused_block_at = Some(self.env().block_number());
-            self.env().emit_event(Paused {});
-            Ok(())
-        }
+            let payout_address = beneficiary.payout_override.unwrap_or(account_id);
+            self.beneficiaries.remove(account_id);
+            self.claim_delegates.remove(account_id);
+            self.claim_history.remove(account_id);
+            self.total_claimed.remove(account_id);
+            self.bonus_history.remove(account_id);
+            self.pruned_multipliers.remove(account_id);
+            self.scheduled_beneficiary_updates.remove(account_id);
 
-        /// Resume the contract
-        /// Resuming will allow to call the claim function
-        #[ink(message)]
-        pub fn resume(&mut self) -> Result<(), Error> {
-            self.ensure_owner()?;
-            if !self.is_paused() {
-                return Ok(());
+            // Remove the beneficiary from the list of beneficiaries
+            self.beneficiaries_accounts.retain(|x| *x != account_id);
+
+            // Remove the beneficiary from the claims in period, the settlement is still counted in the amount
+            if settled_amount > 0 {
+                self._update_claims_in_period(claiming_period_block, account_id, settled_amount);
             }
-            self.paused_block_at = None;
-            self.env().emit_event(Resumed {});
+            self.claimed_accounts_in_period.retain(|x| *x != account_id);
+            self.claims_in_period.total_claims = self.claimed_accounts_in_period.len() as u32;
+
+            // Pay what was earned once the beneficiary is removed
+            if settled_amount > 0 {
+                self._transfer(payout_address, settled_amount)?;
+                self._add_paid_out(settled_amount)?;
+            }
+
+            // Emit the BeneficiaryRemoved event
+            self.env().emit_event(BeneficiaryRemoved {
+                account_id,
+                settled_amount,
+            });
+
             Ok(())
         }
 
-        //----------------------------------------------------------------------------------------
-        // Read messages
-        //----------------------------------------------------------------------------------------
+        /// Remove a beneficiary paying it what it has to claim
+        /// The same as remove_beneficiary without force, nothing changes if the treasury can't pay
+        #[ink(message)]
+        pub fn remove_and_settle_beneficiary(
+            &mut self,
+            account_id: AccountId,
+        ) -> Result<(), Error> {
+            self.remove_beneficiary(account_id, false)
+        }
 
-        /// Ensure if all payments up to date or storage unclaiumed_payments is up-to-date
-        /// TODO: this function should be renamed and separated in two different functions
-        /// The view function should just return a bool, and the ensure function should return an error
+        /// Add a one-time bonus to the unclaimed payments of a beneficiary
+        /// It does not change the periodic payments, so it can be added at any time
+        /// The treasury must have enough balance to pay the bonus on top of all the debts
+        /// The reason is kept in the bonus history of the beneficiary, see get_bonus_history
         #[ink(message)]
-        pub fn ensure_all_payments_uptodate(&self) -> Result<(), Error> {
-            let claimed_period_block = self.get_current_period_initial_block();
+        pub fn add_bonus(
+            &mut self,
+            account_id: AccountId,
+            amount: Balance,
+            reason: String,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if amount == 0 || reason.len() > MAX_BONUS_REASON_LENGTH {
+                return Err(Error::InvalidParams);
+            }
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
 
-            // iterates over each account_id
-            for account_id in self.beneficiaries_accounts.iter() {
-                let beneficiary = self.beneficiaries.get(account_id).unwrap();
+            // Ensure the treasury can pay the bonus besides what is already owed
+            if self.get_contract_balance() < self.get_total_debts().saturating_add(amount) {
+                return Err(Error::NotEnoughBalanceInTreasury);
+            }
 
-                if claimed_period_block > beneficiary.last_updated_period_block {
-                    return Err(Error::PaymentsNotUpToDate);
-                }
+            // The bonus is kept apart in unclaimed_bonuses too, so it never expires with the older payments
+            beneficiary.unclaimed_payments = beneficiary
+                .unclaimed_payments
+                .checked_add(amount)
+                .ok_or(Error::PaymentCalculationOverflow)?;
+            beneficiary.unclaimed_bonuses = beneficiary
+                .unclaimed_bonuses
+                .checked_add(amount)
+                .ok_or(Error::PaymentCalculationOverflow)?;
+            self.beneficiaries.insert(account_id, &beneficiary);
+
+            let mut records = self.bonus_history.get(account_id).unwrap_or_default();
+            if records.len() >= BONUS_HISTORY_CAPACITY {
+                records.remove(0);
             }
+            records.push(BonusRecord {
+                added_at_block: self._now(),
+                amount,
+                reason: reason.clone(),
+            });
+            self.bonus_history.insert(account_id, &records);
 
-            Ok(())
-        }
+            // Emit the BonusAdded event
+            self.env().emit_event(BonusAdded {
+                account_id,
+                amount,
+                reason,
+            });
 
-        /// Reads the paused state from the contract
-        #[ink(message)]
-        pub fn is_paused(&self) -> bool {
-            self.paused_block_at.is_some()
+            Ok(())
         }
 
-        /// Get amount in storage without transferring the funds
-        /// Read Only function
+        /// Set an account allowed to claim on behalf of the caller, replacing the previous one
+        /// Only a beneficiary can set its delegate
         #[ink(message)]
-        pub fn get_amount_to_claim(&self, account_id: AccountId) -> Option<Balance> {
+        pub fn set_claim_delegate(&mut self, delegate: AccountId) -> Result<(), Error> {
+            let account_id = self.env().caller();
             if !self.beneficiaries.contains(account_id) {
-                return None;
+                return Err(Error::AccountNotFound);
+            }
+            if delegate == account_id {
+                return Err(Error::InvalidParams);
             }
+            self.ensure_valid_role_assignment(Role::ClaimDelegate, delegate)?;
 
-            let result = self._get_amount_to_claim(account_id, false);
-            Some(result)
-        }
+            self.claim_delegates.insert(account_id, &delegate);
 
-        /// Get beneficiary only read
-        /// Read Only function
-        #[ink(message)]
-        pub fn get_beneficiary(&mut self, account_id: AccountId) -> Option<Beneficiary> {
-            self.beneficiaries.get(account_id)
-        }
+            // Emit the ClaimDelegateSet event
+            self.env().emit_event(ClaimDelegateSet {
+                account_id,
+                delegate,
+            });
 
-        /// Get current block period
-        /// Read Only function
-        /// The calculation current_block - ((current_block - self.initial_block) % self.periodicity) might be a bit tricky to understand at first glance.
-        /// Let's use an example to understand it. Assume self.initial_block to be 10, self.periodicity to be 20, and the current_block to be 65.
-        /// current_block - self.initial_block = 65 - 10 = 55 55 % self.periodicity = 55 % 20 = 15.
-        /// This gives us the number of blocks past the last "period start" in relation to initial_block and periodicity.  current_block - 15 = 65 - 15 = 50.
-        /// This is the block number where the current period started.
-        #[ink(message)]
-        pub fn get_current_period_initial_block(&self) -> BlockNumber {
-            let current_block = self.env().block_number();
-            current_block - ((current_block - self.initial_block) % self.periodicity)
+            Ok(())
         }
 
-        /// Get next block period
+        /// Revoke the claim delegate of the caller
         #[ink(message)]
-        pub fn get_next_block_period(&self) -> BlockNumber {
-            self.get_current_period_initial_block() + self.periodicity
+        pub fn revoke_claim_delegate(&mut self) -> Result<(), Error> {
+            let account_id = self.env().caller();
+            if !self.claim_delegates.contains(account_id) {
+                return Err(Error::AccountNotFound);
+            }
+
+            self.claim_delegates.remove(account_id);
+
+            // Emit the ClaimDelegateRevoked event
+            self.env().emit_event(ClaimDelegateRevoked { account_id });
+
+            Ok(())
         }
 
-        /// Get all the debts up-to-date
-        /// Read Only function
+        /// Set the account the payments of the caller, a beneficiary, are sent to, or None to send them to itself
+        /// The beneficiary keeps its identity: the claims, the records and the delegate stay with its own account
         #[ink(message)]
-        pub fn get_total_debts(&self) -> Balance {
-            let mut debts = 0;
-            for account_id in self.beneficiaries_accounts.iter() {
-                let beneficiary = self.beneficiaries.get(account_id).unwrap();
-                debts += self._get_amount_to_claim(beneficiary.account_id, false);
+        pub fn set_payout_address(&mut self, payout: Option<AccountId>) -> Result<(), Error> {
+            let account_id = self.env().caller();
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            if let Some(payout) = payout {
+                self.ensure_valid_role_assignment(Role::PayoutAddress, payout)?;
             }
 
-            debts
-        }
+            beneficiary.payout_override = payout;
+            self.beneficiaries.insert(account_id, &beneficiary);
 
-        /// Get all the debts for the next period
-        /// Read Only function
-        #[ink(message)]
-        pub fn get_total_debt_for_next_period(&self) -> Balance {
-            let mut total = 0;
-            for account_id in self.beneficiaries_accounts.iter() {
-                let beneficiary = self.beneficiaries.get(account_id).unwrap();
-                let amount = self._get_amount_to_claim_for_one_period(&beneficiary, false);
-                total += amount;
-            }
+            // Emit the PayoutAddressUpdated event
+            self.env().emit_event(PayoutAddressUpdated {
+                account_id,
+                payout_address: payout,
+            });
 
-            total
+            Ok(())
         }
 
-        /// Get all the debts including unclaimed for the next period
-        /// Read Only function
+        /// Move the caller, a beneficiary, to a new address, keeping its multipliers, payments and history
+        /// The claim delegate and the payout address are revoked, as they may have been set by whoever compromised the old address
         #[ink(message)]
-        pub fn get_total_debt_with_unclaimed_for_next_period(&self) -> Balance {
-            let block_next_period = self.get_next_block_period();
+        pub fn update_my_address(&mut self, new_account_id: AccountId) -> Result<(), Error> {
+            let account_id = self.env().caller();
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            if self.beneficiaries.contains(new_account_id) {
+                return Err(Error::AccountAlreadyExists);
+            }
+            self.ensure_valid_role_assignment(Role::Beneficiary, new_account_id)?;
 
-            let mut total = 0;
-            for account_id in self.beneficiaries_accounts.iter() {
-                let amount =
-                    self._get_amount_to_claim_in_block(*account_id, false, block_next_period);
-                total += amount;
+            beneficiary.account_id = new_account_id;
+            beneficiary.payout_override = None;
+            self.beneficiaries.remove(account_id);
+            self.beneficiaries.insert(new_account_id, &beneficiary);
+            for account in self
+                .beneficiaries_accounts
+                .iter_mut()
+                .chain(self.claimed_accounts_in_period.iter_mut())
+            {
+                if *account == account_id {
+                    *account = new_account_id;
+                }
             }
 
-            total
+            // Move the data kept apart from the beneficiary
+            if let Some(records) = self.claim_history.take(account_id) {
+                self.claim_history.insert(new_account_id, &records);
+            }
+            if let Some(total_claimed) = self.total_claimed.take(account_id) {
+                self.total_claimed.insert(new_account_id, &total_claimed);
+            }
+            if let Some(records) = self.bonus_history.take(account_id) {
+                self.bonus_history.insert(new_account_id, &records);
+            }
+            if let Some(pruned) = self.pruned_multipliers.take(account_id) {
+                self.pruned_multipliers.insert(new_account_id, &pruned);
+            }
+            if let Some(update) = self.scheduled_beneficiary_updates.take(account_id) {
+                self.scheduled_beneficiary_updates
+                    .insert(new_account_id, &update);
+            }
+            self.claim_delegates.remove(account_id);
+
+            // Emit the BeneficiaryAddressUpdated event
+            self.env().emit_event(BeneficiaryAddressUpdated {
+                previous_account_id: account_id,
+                account_id: new_account_id,
+            });
+
+            Ok(())
         }
 
-        /// Get all the beneficiaries
-        /// Read Only function
+        /// Update the number of claims kept in the history of each beneficiary
+        /// Lowering it evicts the oldest claims of a beneficiary on its next claim
         #[ink(message)]
-        pub fn get_list_beneficiaries(&self) -> Vec<AccountId> {
-            self.beneficiaries_accounts.clone()
+        pub fn set_claim_history_capacity(
+            &mut self,
+            claim_history_capacity: u32,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if !(1..=MAX_CLAIM_HISTORY_CAPACITY).contains(&claim_history_capacity) {
+                return Err(Error::InvalidParams);
+            }
+            self.claim_history_capacity = claim_history_capacity;
+
+            // Emit the ClaimHistoryCapacityUpdated event
+            self.env().emit_event(ClaimHistoryCapacityUpdated {
+                claim_history_capacity,
+            });
+
+            Ok(())
         }
 
-        /// Get contract balance
-        /// Read Only function
+        /// Set the max amount a beneficiary can receive in a single claim, or None for no limit
+        /// Anything above it stays unclaimed and can be claimed in later transactions
         #[ink(message)]
-        pub fn get_contract_balance(&self) -> Balance {
-            self.env().balance()
+        pub fn set_max_per_claim(
+            &mut self,
+            account_id: AccountId,
+            max_per_claim: Option<Balance>,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if max_per_claim == Some(0) {
+                return Err(Error::InvalidParams);
+            }
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+
+            beneficiary.max_per_claim = max_per_claim;
+            self.beneficiaries.insert(account_id, &beneficiary);
+
+            // Emit the MaxPerClaimUpdated event
+            self.env().emit_event(MaxPerClaimUpdated {
+                account_id,
+                max_per_claim,
+            });
+
+            Ok(())
         }
 
-        /// Get total balance after paying debts
-        /// Read Only function
+        /// Set the group of a beneficiary, or None to remove it from its group
         #[ink(message)]
-        pub fn get_balance_with_debts(&self) -> Balance {
-            self.get_contract_balance() - self.get_total_debts()
+        pub fn set_beneficiary_group(
+            &mut self,
+            account_id: AccountId,
+            group_id: Option<GroupId>,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+
+            // Keep the blocks paused since the last update when moving to the counter of the new group
+            let paused_blocks_since_update = self
+                ._get_paused_blocks_counter(beneficiary.group_id)
+                .saturating_sub(beneficiary.paused_blocks_at_update);
+            beneficiary.group_id = group_id;
+            beneficiary.paused_blocks_at_update = self
+                ._get_paused_blocks_counter(group_id)
+                .saturating_sub(paused_blocks_since_update);
+            self.beneficiaries.insert(account_id, &beneficiary);
+
+            // Emit the BeneficiaryGroupUpdated event
+            self.env().emit_event(BeneficiaryGroupUpdated {
+                account_id,
+                group_id,
+            });
+
+            Ok(())
         }
 
-        /// Get list of unclaimed beneficiaries
-        /// Read Only function
+        /// Set the period block from which a beneficiary accrues payments, to grant back-pay or fix a mistake
+        /// The block must start a period and can't be in the future nor before the initial block
+        /// The payments accrued until now are replaced by the ones accrued from the block, the unclaimed payments are kept
+        /// The block can't be before the last update of a beneficiary with unclaimed payments, which already cover those periods
         #[ink(message)]
-        pub fn get_unclaimed_beneficiaries(&self) -> Vec<AccountId> {
-            let claiming_period_block = self.get_current_period_initial_block();
-
-            let mut unclaimed_beneficiaries = Vec::new();
-            // iterate over all beneficiaries
-            // if last_updated_period_block < claiming_period_block
-            // then add to unclaimed_beneficiaries
-            for account_id in self.beneficiaries_accounts.iter() {
-                let beneficiary = self.beneficiaries.get(account_id).unwrap();
-                if beneficiary.last_updated_period_block < claiming_period_block {
-                    unclaimed_beneficiaries.push(beneficiary.account_id);
-                }
+        pub fn set_beneficiary_accrual_start(
+            &mut self,
+            account_id: AccountId,
+            block: BlockNumber,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if block > self._now()
+                || !period::is_period_start(self.initial_block, self.periodicity, block)
+            {
+                return Err(Error::InvalidParams);
+            }
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            if beneficiary.unclaimed_payments != 0 && block < beneficiary.last_updated_period_block
+            {
+                return Err(Error::InvalidParams);
             }
 
-            unclaimed_beneficiaries
+            self._set_last_updated_period_block(&mut beneficiary, block);
+            self.beneficiaries.insert(account_id, &beneficiary);
+
+            // Emit the BeneficiaryAccrualStartUpdated event
+            self.env()
+                .emit_event(BeneficiaryAccrualStartUpdated { account_id, block });
+
+            Ok(())
         }
 
-        /// Get count of unclaimed beneficiaries
-        /// Read Only function
+        /// Suspend a beneficiary without removing it
+        /// The payments earned until now are kept as unclaimed payments and can still be claimed,
+        /// but no new payments are accrued while the beneficiary is suspended
         #[ink(message)]
-        pub fn get_count_of_unclaim_beneficiaries(&self) -> u8 {
-            let claiming_period_block = self.get_current_period_initial_block();
-            let mut total: u8 = 0;
-            for account_id in self.beneficiaries_accounts.iter() {
-                let beneficiary = self.beneficiaries.get(account_id).unwrap();
-                if beneficiary.last_updated_period_block < claiming_period_block {
-                    total += 1;
-                }
+        pub fn suspend_beneficiary(&mut self, account_id: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            if beneficiary.suspended {
+                return Err(Error::BeneficiaryAlreadySuspended);
             }
 
-            total
+            // Keep what was earned before the suspension
+            beneficiary.unclaimed_payments = self._snapshot_amount_to_claim(&mut beneficiary)?;
+            self._set_last_updated_period_block(
+                &mut beneficiary,
+                self.get_current_period_initial_block(),
+            );
+            beneficiary.suspended = true;
+            self.beneficiaries.insert(account_id, &beneficiary);
+
+            // Emit the BeneficiarySuspended event
+            self.env().emit_event(BeneficiarySuspended { account_id });
+
+            Ok(())
         }
 
-        /// Get the base amount to claim for each beneficiary
+        /// Unsuspend a beneficiary
+        /// Payments are accrued again from the current period, without back-pay for the suspended periods
+        /// The multipliers that expired and were removed while it was suspended are reported in the
+        /// BeneficiaryUnsuspended event, as the beneficiary is not paid with them anymore
         #[ink(message)]
-        pub fn get_base_payment(&self) -> Balance {
-            self.base_payment
+        pub fn unsuspend_beneficiary(&mut self, account_id: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let mut beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+            if !beneficiary.suspended {
+                return Err(Error::BeneficiaryNotSuspended);
+            }
+
+            self._set_last_updated_period_block(
+                &mut beneficiary,
+                self.get_current_period_initial_block(),
+            );
+            beneficiary.suspended = false;
+            self.beneficiaries.insert(account_id, &beneficiary);
+
+            let pruned_multipliers = self.pruned_multipliers.take(account_id).unwrap_or_default();
+
+            // Emit the BeneficiaryUnsuspended event
+            self.env().emit_event(BeneficiaryUnsuspended {
+                account_id,
+                pruned_multipliers,
+            });
+
+            Ok(())
         }
 
-        /// Get the periodicity of the payments
+        /// Update the base_payment
+        /// It makes sense once all the beneficiaries have claimed their payments
         #[ink(message)]
-        pub fn get_periodicity(&self) -> BlockNumber {
-            self.periodicity
+        pub fn update_base_payment(&mut self, base_payment: Balance) -> Result<(), Error> {
+            self.ensure_owner()?;
+            ensure_valid_base_payment(base_payment)?;
+
+            // Ensure if all payments are up to date
+            self.ensure_all_claimed_in_period()?;
+            self.base_payment = base_payment;
+
+            Ok(())
         }
 
-        /// Get the initial block of the contract
+        /// Add a new base multiplier
+        /// The name can't be empty nor the name of another active multiplier
         #[ink(message)]
-        pub fn get_initial_block(&self) -> BlockNumber {
-            self.initial_block
+        pub fn add_base_multiplier(&mut self, name: String) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self._add_base_multiplier(name)?;
+            Ok(())
         }
 
-        /// Get the base multiplier
+        /// Replace an active base multiplier with a new one, moving the value of every beneficiary to it
+        /// What the beneficiaries are owed is folded into their unclaimed payments first, and the old multiplier
+        /// is deactivated at the end of the current period, so the payments are unchanged.
+        /// The new multiplier is added before the old one can be deleted, so both count towards the maximum
         #[ink(message)]
-        pub fn get_multipliers_list(&self) -> Vec<MultiplierId> {
-            self.multipliers_list.clone()
+        pub fn replace_multiplier(
+            &mut self,
+            old_id: MultiplierId,
+            new_name: String,
+        ) -> Result<MultiplierId, Error> {
+            self.ensure_owner()?;
+            let old_multiplier = self.get_base_multiplier(old_id)?;
+            if old_multiplier.valid_until_block.is_some() {
+                return Err(Error::MultiplierAlreadyDeactivated);
+            }
+            let new_id = self._add_base_multiplier(new_name)?;
+
+            let assigned: Vec<Beneficiary> = self
+                ._iter_beneficiaries()
+                .filter(|beneficiary| beneficiary.multipliers.contains_key(&old_id))
+                .collect();
+            for mut beneficiary in assigned {
+                let account_id = beneficiary.account_id;
+                let unclaimed_payments = self._snapshot_amount_to_claim(&mut beneficiary)?;
+
+                let value = beneficiary.multipliers.remove(&old_id).unwrap();
+                beneficiary.multipliers.insert(new_id, value);
+                beneficiary.unclaimed_payments = unclaimed_payments;
+                self._set_last_updated_period_block(
+                    &mut beneficiary,
+                    self.get_current_period_initial_block(),
+                );
+                self.beneficiaries.insert(account_id, &beneficiary);
+
+                // Emit the BeneficiaryUpdated event
+                self.env().emit_event(BeneficiaryUpdated {
+                    account_id,
+                    multipliers_vec: beneficiary.multipliers.into_iter().collect(),
+                });
+            }
+
+            // The scheduled updates get the new multiplier too
+            for account_id in self.beneficiaries_accounts.clone() {
+                if let Some(mut update) = self.scheduled_beneficiary_updates.get(account_id) {
+                    if let Some(value) = update.multipliers.remove(&old_id) {
+                        update.multipliers.insert(new_id, value);
+                        self.scheduled_beneficiary_updates
+                            .insert(account_id, &update);
+                    }
+                }
+            }
+
+            self.deactivate_multiplier(old_id)?;
+
+            Ok(new_id)
         }
 
-        /// Get a base multiplier based on its id
+        /// Set or clear the account sponsoring the payments made with a base multiplier
+        /// It is informative, the payments are always made from the treasury
         #[ink(message)]
-        pub fn get_base_multiplier(&self, multiplier_id: MultiplierId) -> Option<BaseMultiplier> {
-            self.base_multipliers.get(multiplier_id)
+        pub fn set_multiplier_sponsor(
+            &mut self,
+            multiplier_id: MultiplierId,
+            sponsor: Option<AccountId>,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+
+            let mut base_multiplier = self
+                .base_multipliers
+                .get(multiplier_id)
+                .ok_or(Error::MultiplierNotFound)?;
+            base_multiplier.sponsor = sponsor;
+            self.base_multipliers
+                .insert(multiplier_id, &base_multiplier);
+
+            // Emit the MultiplierSponsorUpdated event
+            self.env().emit_event(MultiplierSponsorUpdated {
+                multiplier_id,
+                sponsor,
+            });
+
+            Ok(())
         }
 
-        /// Get the owner of the contract
+        /// Rename a base multiplier
+        /// The name can't be empty nor the name of another active multiplier
         #[ink(message)]
-        pub fn get_owner(&self) -> AccountId {
-            self.owner
+        pub fn rename_base_multiplier(
+            &mut self,
+            multiplier_id: MultiplierId,
+            new_name: String,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self._rename_base_multiplier(multiplier_id, new_name)
         }
 
-        //----------------------------------------------------------------------------------------
-        // Internal functions
-        //----------------------------------------------------------------------------------------
-
-        // Ensure_owner ensures that the caller is the owner of the contract
-        fn ensure_owner(&self) -> Result<(), Error> {
-            let account = self.env().caller();
-            // Only owners can call this function
-            if self.owner != account {
-                return Err(Error::NotOwner);
+        /// Rename an active base multiplier
+        /// Like rename_base_multiplier, but deactivated multipliers keep the name they were deactivated with
+        #[ink(message)]
+        pub fn update_base_multiplier_name(
+            &mut self,
+            multiplier_id: MultiplierId,
+            name: String,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let base_multiplier = self
+                .base_multipliers
+                .get(multiplier_id)
+                .ok_or(Error::MultiplierNotFound)?;
+            if base_multiplier.valid_until_block.is_some() {
+                return Err(Error::MultiplierAlreadyDeactivated);
             }
-            Ok(())
+
+            self.rename_base_multiplier(multiplier_id, name)
         }
 
-        // ensure_is_not_paused ensures that the contract is not paused
-        fn ensure_is_not_paused(&self) -> Result<(), Error> {
-            if self.is_paused() {
-                return Err(Error::ContractIsPaused);
+        /// Schedule the rename of an active base multiplier from a future block
+        /// It is applied by process_scheduled_multiplier_updates once the block is reached,
+        /// and replaces the change already scheduled for the multiplier if any
+        #[ink(message)]
+        pub fn schedule_multiplier_update(
+            &mut self,
+            multiplier_id: MultiplierId,
+            new_name: String,
+            activation_block: BlockNumber,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            let base_multiplier = self.get_base_multiplier(multiplier_id)?;
+            if base_multiplier.valid_until_block.is_some() {
+                return Err(Error::MultiplierAlreadyDeactivated);
+            }
+            if activation_block <= self._now() {
+                return Err(Error::InvalidParams);
             }
+            self.ensure_multiplier_name_is_valid(&new_name, Some(multiplier_id))?;
+
+            self.scheduled_multiplier_changes
+                .retain(|change| change.multiplier_id != multiplier_id);
+            self.scheduled_multiplier_changes
+                .push(ScheduledMultiplierChange {
+                    multiplier_id,
+                    new_name: new_name.clone(),
+                    activation_block,
+                });
+
+            self.env().emit_event(MultiplierUpdateScheduled {
+                multiplier_id,
+                new_name,
+                activation_block,
+            });
+
             Ok(())
         }
 
-        // Ensure multipliers are valid
-        fn ensure_multipliers_are_valid(
-            &self,
-            multipliers: &[(MultiplierId, Multiplier)],
-        ) -> Result<(), Error> {
-            for (multiplier_id, _) in multipliers.iter() {
-                if !self.base_multipliers.contains(multiplier_id) {
-                    return Err(Error::MultiplierNotFound);
-                }
-                if self
-                    .base_multipliers
-                    .get(multiplier_id)
-                    .unwrap()
-                    .valid_until_block
-                    .is_some()
+        /// Apply the scheduled multiplier changes whose activation block is reached, and return how many were applied
+        /// Anyone can call it. A due change is dropped without being applied if its multiplier was deactivated
+        /// or deleted, or if its name is taken by another multiplier since it was scheduled
+        #[ink(message)]
+        pub fn process_scheduled_multiplier_updates(&mut self) -> Result<u32, Error> {
+            let now = self._now();
+            let (due, pending): (Vec<_>, Vec<_>) = self
+                .scheduled_multiplier_changes
+                .drain(..)
+                .partition(|change| change.activation_block <= now);
+            self.scheduled_multiplier_changes = pending;
+
+            let mut applied = 0;
+            for change in due {
+                if self.is_active_multiplier(change.multiplier_id)
+                    && self
+                        ._rename_base_multiplier(change.multiplier_id, change.new_name)
+                        .is_ok()
                 {
-                    return Err(Error::MultiplierAlreadyDeactivated);
+                    applied += 1;
                 }
             }
-            Ok(())
+            Ok(applied)
         }
 
-        // Function for doing the ensurance before adding a new beneficiary
-        fn ensure_beneficiary_to_add(
-            &self,
-            account_id: AccountId,
-            multipliers: &[(MultiplierId, Multiplier)],
-        ) -> Result<(), Error> {
-            self.ensure_owner()?;
+        /// Get the multiplier changes waiting for their activation block, in the order they were scheduled
+        #[ink(message)]
+        pub fn get_scheduled_multiplier_updates(&self) -> Vec<ScheduledMultiplierChange> {
+            self.scheduled_multiplier_changes.clone()
+        }
 
-            // Ensure that the beneficiary does not exist
-            if self.beneficiaries.contains(account_id) {
-                return Err(Error::AccountAlreadyExists);
+        /// Update the periodicity of the payments
+        /// All payments must be claimed before updating the periodicity
+        /// In time based mode the periodicity is given in milliseconds and must be a multiple of 1000
+        #[ink(message)]
+        pub fn update_periodicity(&mut self, periodicity: u32) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if periodicity == 0 {
+                return Err(Error::InvalidParams);
             }
+            let periodicity = self._periodicity_to_clock(periodicity)?;
 
-            // Ensure that the number of beneficiaries does not exceed the maximum
-            if self.beneficiaries_accounts.len() + 1 > MAX_BENEFICIARIES {
-                return Err(Error::MaxBeneficiariesExceeded);
-            }
+            // Ensure if all payments are up to date
+            // self.ensure_all_payments_uptodate()?;
+            self.ensure_all_claimed_in_period()?;
+            self.periodicity = periodicity;
 
-            // Ensure that the multipliers are valid
-            self.ensure_multipliers_are_valid(multipliers)?;
-            ensure_no_duplicate_multipliers(&Vec::from(multipliers))?;
+            // Emit the PeriodicityUpdated event
+            self.env().emit_event(PeriodicityUpdated { periodicity });
 
             Ok(())
         }
 
-        // Get the amount of tokens that can be claimed by a beneficiary with specific block_numer
-        fn _get_amount_to_claim_in_block(
-            &self,
-            account_id: AccountId,
-            filtered_multipliers: bool,
-            block: BlockNumber,
-        ) -> Balance {
-            // The check that beneficiary exists is done in the caller function
-            let beneficiary = self.beneficiaries.get(account_id).unwrap();
+        /// Update the maximum number of periods the treasury may fund on top of the debts
+        /// None removes the limit
+        #[ink(message)]
+        pub fn set_max_runway_periods(
+            &mut self,
+            max_runway_periods: Option<u32>,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.max_runway_periods = max_runway_periods;
 
-            // Calculates the number of blocks that have elapsed since the last payment
-            let blocks_since_last_payment = block - beneficiary.last_updated_period_block;
+            // Emit the MaxRunwayPeriodsUpdated event
+            self.env()
+                .emit_event(MaxRunwayPeriodsUpdated { max_runway_periods });
 
-            // Calculates the number of periods that are due based on the elapsed blocks
-            let unclaimed_periods: u128 = (blocks_since_last_payment / self.periodicity).into();
+            Ok(())
+        }
 
-            // If there's no unclaimed periods, return the unclaimed payments
-            // Otherwise, calculate the amount to claim and add the unclaimed payments
-            if unclaimed_periods == 0 {
-                beneficiary.unclaimed_payments
-            } else {
-                let payment_per_period =
-                    self._get_amount_to_claim_for_one_period(&beneficiary, filtered_multipliers);
+        /// Set the block after which no payment accrues, or None to remove the end
+        /// Only the whole periods ending at or before the end block are paid, a period straddling it is not.
+        /// The payments accrued until then stay claimable and no longer expire.
+        /// The end block can't be in the past, but moving or removing it once passed pays the periods since then
+        #[ink(message)]
+        pub fn set_end_block(&mut self, end_block: Option<BlockNumber>) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if let Some(end_block) = end_block {
+                if end_block < self._now() {
+                    return Err(Error::InvalidParams);
+                }
+            }
+            self.end_block = end_block;
+
+            // Emit the EndBlockUpdated event
+            self.env().emit_event(EndBlockUpdated { end_block });
+
+            Ok(())
+        }
+
+        /// Set the maximum amount claimed by all the beneficiaries in a period, or None to remove the limit
+        /// A claim taking the amount claimed in the current period over the budget fails with PeriodBudgetExceeded,
+        /// the amount not claimed stays owed and can be claimed in a later period
+        #[ink(message)]
+        pub fn set_period_budget(&mut self, period_budget: Option<Balance>) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.period_budget = period_budget;
+
+            // Emit the PeriodBudgetUpdated event
+            self.env().emit_event(PeriodBudgetUpdated { period_budget });
 
-                payment_per_period * unclaimed_periods + beneficiary.unclaimed_payments
+            Ok(())
+        }
+
+        /// Update the existential deposit hint
+        /// It only changes how much native balance the treasury keeps to stay alive, so it can be done at any time
+        #[ink(message)]
+        pub fn set_existential_deposit_hint(
+            &mut self,
+            existential_deposit_hint: Balance,
+        ) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.existential_deposit_hint = existential_deposit_hint;
+
+            // Emit the ExistentialDepositHintUpdated event
+            self.env().emit_event(ExistentialDepositHintUpdated {
+                existential_deposit_hint,
+            });
+
+            Ok(())
+        }
+
+        /// Update the expected time between blocks in milliseconds, used to estimate when the next period starts
+        /// 0 means the block time is unknown
+        #[ink(message)]
+        pub fn set_target_block_time(&mut self, target_block_time: u64) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.target_block_time = target_block_time;
+
+            // Emit the TargetBlockTimeUpdated event
+            self.env()
+                .emit_event(TargetBlockTimeUpdated { target_block_time });
+
+            Ok(())
+        }
+
+        /// Pause the contract
+        /// Pausing will only avoid to call the claim function
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<(), Error> {
+            self.ensure_owner_or_admin()?;
+            self._end_expired_pause();
+            if self.is_paused() && self.resume_at.is_none() {
+                return Ok(());
+            }
+            if !self.is_paused() {
+                self.paused_block_at = Some(self._now());
             }
+            self.resume_at = None;
+            self.env().emit_event(Paused {});
+            Ok(())
         }
 
-        // check the amount to claim for one beneficiary in any period
-        // without unclaimed payments
-        fn _get_amount_to_claim_for_one_period(
-            &self,
-            beneficiary: &Beneficiary,
-            filtered_multipliers: bool,
-        ) -> Balance {
-            // E.g (M1 + M2) * B / 100
-            // Sum all active multipliers
-            let final_multiplier: u128 = if beneficiary.multipliers.is_empty() {
-                1
-            } else {
-                match filtered_multipliers {
-                    true => beneficiary.multipliers.values().sum(),
-                    _ => beneficiary
-                        .multipliers
-                        .iter()
-                        .filter(|(k, _)| {
-                            self.base_multipliers
-                                .get(k)
-                                .unwrap()
-                                .valid_until_block
-                                .is_none()
-                        })
-                        .map(|(_, v)| v)
-                        .sum(),
+        /// Pause the contract for a number of blocks, or seconds in time based mode
+        /// The contract is active again once the resume block is reached, without calling resume
+        /// Pausing an already paused contract replaces the resume block
+        #[ink(message)]
+        pub fn pause_for(&mut self, blocks: u32) -> Result<(), Error> {
+            self.ensure_owner_or_admin()?;
+            if blocks == 0 {
+                return Err(Error::InvalidParams);
+            }
+            let resume_at = self
+                ._now()
+                .checked_add(blocks)
+                .ok_or(Error::InvalidParams)?;
+            self._end_expired_pause();
+            if !self.is_paused() {
+                self.paused_block_at = Some(self._now());
+            }
+            self.resume_at = Some(resume_at);
+            self.env().emit_event(PausedUntil { resume_at });
+            Ok(())
+        }
+
+        /// Resume the contract
+        /// Resuming will allow to call the claim function
+        #[ink(message)]
+        pub fn resume(&mut self) -> Result<(), Error> {
+            self.ensure_owner_or_admin()?;
+            self._end_expired_pause();
+            if !self.is_paused() {
+                return Ok(());
+            }
+            self._record_pause();
+            self.paused_block_at = None;
+            self.resume_at = None;
+            self.env().emit_event(Resumed {});
+            Ok(())
+        }
+
+        /// Pause the payments of a group
+        /// Like the global pause, it will only avoid to call the claim function for the members of the group
+        #[ink(message)]
+        pub fn pause_group(&mut self, group_id: GroupId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if self.is_group_paused(group_id) {
+                return Ok(());
+            }
+            self.paused_groups.insert(group_id, &self._now());
+            self.env().emit_event(GroupPaused { group_id });
+            Ok(())
+        }
+
+        /// Resume the payments of a group
+        /// The members still can't claim while the contract is paused
+        #[ink(message)]
+        pub fn resume_group(&mut self, group_id: GroupId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if !self.is_group_paused(group_id) {
+                return Ok(());
+            }
+            if let Some(paused_at) = self.paused_groups.take(group_id) {
+                let total_paused_blocks = self
+                    .group_total_paused_blocks
+                    .get(group_id)
+                    .unwrap_or(0)
+                    .saturating_add(self._now().saturating_sub(paused_at));
+                self.group_total_paused_blocks
+                    .insert(group_id, &total_paused_blocks);
+            }
+            self.env().emit_event(GroupResumed { group_id });
+            Ok(())
+        }
+
+        /// Add an admin
+        /// Admins can do operational tasks like pausing the contract or adding beneficiaries,
+        /// but only the owner can manage the admins or transfer the ownership
+        #[ink(message)]
+        pub fn add_admin(&mut self, account_id: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            self.ensure_valid_role_assignment(Role::Admin, account_id)?;
+            if self.admins.contains(account_id) {
+                return Err(Error::AccountAlreadyExists);
+            }
+            self.admins.insert(account_id, &());
+
+            // Emit the AdminAdded event
+            self.env().emit_event(AdminAdded { account_id });
+
+            Ok(())
+        }
+
+        /// Remove an admin
+        #[ink(message)]
+        pub fn remove_admin(&mut self, account_id: AccountId) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if !self.admins.contains(account_id) {
+                return Err(Error::AccountNotFound);
+            }
+            self.admins.remove(account_id);
+
+            // Emit the AdminRemoved event
+            self.env().emit_event(AdminRemoved { account_id });
+
+            Ok(())
+        }
+
+        //----------------------------------------------------------------------------------------
+        // Read messages
+        //----------------------------------------------------------------------------------------
+
+        /// Ensure if all payments up to date or storage unclaiumed_payments is up-to-date
+        /// TODO: this function should be renamed and separated in two different functions
+        /// The view function should just return a bool, and the ensure function should return an error
+        #[ink(message)]
+        pub fn ensure_all_payments_uptodate(&self) -> Result<(), Error> {
+            let claimed_period_block = self.get_current_period_initial_block();
+
+            // iterates over each beneficiary
+            for beneficiary in self._iter_beneficiaries() {
+                if claimed_period_block > beneficiary.last_updated_period_block {
+                    return Err(Error::PaymentsNotUpToDate);
                 }
-            };
+            }
 
-            final_multiplier * self.base_payment / 100
+            Ok(())
         }
 
-        // internal function to get the amount to claim
-        // filtered multipliers in true means that all multipliers are active
-        fn _get_amount_to_claim(
+        /// Check if all the beneficiaries have claimed in the current period
+        /// Beneficiaries that had nothing to claim from previous periods, like the ones added in the current period,
+        /// and deactivated beneficiaries that claimed all their payments don't need to claim
+        /// It is computed from the current beneficiaries, so adding or removing one never leaves the period incomplete
+        #[ink(message)]
+        pub fn is_all_claimed_in_period(&self) -> bool {
+            let claiming_period_block = self.get_current_period_initial_block();
+            let claimed_accounts: BTreeSet<AccountId> =
+                self.get_claimed_accounts_in_period().into_iter().collect();
+
+            self._iter_beneficiaries().all(|beneficiary| {
+                beneficiary.last_updated_period_block == claiming_period_block
+                    || (beneficiary.deactivated && beneficiary.unclaimed_payments == 0)
+                    || claimed_accounts.contains(&beneficiary.account_id)
+            })
+        }
+
+        /// Get the period of the last claims and the number of beneficiaries that claimed in it
+        #[ink(message)]
+        pub fn get_claims_in_period(&self) -> ClaimsInPeriod {
+            self.claims_in_period.clone()
+        }
+
+        /// Get the accounts that claimed in the current period
+        #[ink(message)]
+        pub fn get_claimed_accounts_in_period(&self) -> Vec<AccountId> {
+            if self.claims_in_period.period != self.get_current_period_initial_block() {
+                return Vec::new();
+            }
+            self.claimed_accounts_in_period.clone()
+        }
+
+        /// Reads the paused state from the contract
+        /// A pause with a duration is over from its resume block on
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            if self.paused_block_at.is_none() {
+                return false;
+            }
+            match self.resume_at {
+                Some(resume_at) => self._now() < resume_at,
+                None => true,
+            }
+        }
+
+        /// Get the lifecycle state of the contract
+        /// A paused contract is reported as paused even while a migration is pending, as that is what blocks the claims
+        #[ink(message)]
+        pub fn get_lifecycle_state(&self) -> LifecycleState {
+            if let Some(since) = self.get_paused_at() {
+                return LifecycleState::Paused {
+                    since,
+                    resume_at: self.get_resume_at(),
+                };
+            }
+            if self.contract_version < CONTRACT_VERSION {
+                return LifecycleState::MigrationInProgress {
+                    from_version: self.contract_version,
+                };
+            }
+            LifecycleState::Active
+        }
+
+        /// Get the block the contract was paused at, or None if it is not paused
+        #[ink(message)]
+        pub fn get_paused_at(&self) -> Option<BlockNumber> {
+            match self.is_paused() {
+                true => self.paused_block_at,
+                false => None,
+            }
+        }
+
+        /// Get the block a pause with a duration ends at, or None if the contract is not paused for a duration
+        #[ink(message)]
+        pub fn get_resume_at(&self) -> Option<BlockNumber> {
+            match self.is_paused() {
+                true => self.resume_at,
+                false => None,
+            }
+        }
+
+        /// Get the latest ended pauses as (paused_at, resumed_at), from the oldest to the newest
+        /// A pause with a duration is included once its resume block is reached
+        #[ink(message)]
+        pub fn get_pause_history(&self) -> Vec<(BlockNumber, BlockNumber)> {
+            let mut history = self.pause_history.clone();
+            if let (Some(pause), false) = (self._get_pause_interval(), self.is_paused()) {
+                if history.len() >= PAUSE_HISTORY_CAPACITY {
+                    history.remove(0);
+                }
+                history.push(pause);
+            }
+            history
+        }
+
+        /// Get the number of blocks, or seconds in time based mode, the contract has been paused since its creation,
+        /// including the current pause
+        #[ink(message)]
+        pub fn get_total_paused_blocks(&self) -> u32 {
+            match self._get_pause_interval() {
+                Some((paused_at, ended_at)) => self
+                    .total_paused_blocks
+                    .saturating_add(ended_at.saturating_sub(paused_at)),
+                None => self.total_paused_blocks,
+            }
+        }
+
+        /// Reads the paused state of a group
+        #[ink(message)]
+        pub fn is_group_paused(&self, group_id: GroupId) -> bool {
+            self.paused_groups.contains(group_id)
+        }
+
+        /// Get the number of full periods a beneficiary has not claimed yet
+        /// Periods before the start block of the beneficiary are not counted
+        #[ink(message)]
+        pub fn get_claimable_periods_count(&self, account_id: AccountId) -> Result<u32, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            let beneficiary = self
+                .beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)?;
+
+            Ok(self._get_unclaimed_periods(&beneficiary, self._now()))
+        }
+
+        /// Get amount in storage without transferring the funds
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_amount_to_claim(&self, account_id: AccountId) -> Result<Balance, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+
+            self._get_amount_to_claim(account_id)
+        }
+
+        /// Get the amount a beneficiary will be able to claim at a block, if nothing is claimed until then
+        /// The multipliers expiring before the block stop counting from their expiry
+        /// The block can't be before the current block
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_amount_to_claim_at_block(
             &self,
             account_id: AccountId,
-            filtered_multipliers: bool,
-        ) -> Balance {
-            let current_block = self.env().block_number();
+            block: BlockNumber,
+        ) -> Result<Balance, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            if block < self._now() {
+                return Err(Error::InvalidParams);
+            }
 
-            self._get_amount_to_claim_in_block(account_id, filtered_multipliers, current_block)
+            self._get_amount_to_claim_in_block(account_id, block)
         }
 
-        // Updates the number of claims in a period
-        // If the period is the same, it increments the number of claims
-        // Otherwise, it resets the number of claims and set it to 1
-        fn _update_claims_in_period(&mut self, claiming_period_block: BlockNumber) {
-            if claiming_period_block == self.claims_in_period.period {
-                // Updates current claims in period
-                self.claims_in_period.total_claims += 1;
-            } else {
-                // Reset the claims in period
-                self.claims_in_period.period = claiming_period_block;
-                self.claims_in_period.total_claims = 1;
+        /// Get beneficiary only read
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_beneficiary(&self, account_id: AccountId) -> Result<Beneficiary, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            self.beneficiaries
+                .get(account_id)
+                .ok_or(Error::AccountNotFound)
+        }
+
+        /// Check if an account is a beneficiary without fetching the full struct
+        /// It only checks the storage key exists, so it is cheaper than get_beneficiary for cross-contract guards
+        /// It is not restricted in private mode, like the list of beneficiaries, only their data is
+        /// Read Only function
+        #[ink(message)]
+        pub fn is_beneficiary(&self, account_id: AccountId) -> bool {
+            self.beneficiaries.contains(account_id)
+        }
+
+        /// Check if a multiplier exists and is not deactivated
+        /// It returns a bool instead of the multiplier and its name, so it is cheaper than get_multiplier
+        /// for cross-contract guards
+        /// Read Only function
+        #[ink(message)]
+        pub fn is_active_multiplier(&self, multiplier_id: MultiplierId) -> bool {
+            matches!(
+                self.base_multipliers.get(multiplier_id),
+                Some(base_multiplier) if base_multiplier.valid_until_block.is_none()
+            )
+        }
+
+        /// Get current block period
+        /// Read Only function
+        /// The calculation current_block - ((current_block - self.initial_block) % self.periodicity) might be a bit tricky to understand at first glance.
+        /// Let's use an example to understand it. Assume self.initial_block to be 10, self.periodicity to be 20, and the current_block to be 65.
+        /// current_block - self.initial_block = 65 - 10 = 55 55 % self.periodicity = 55 % 20 = 15.
+        /// This gives us the number of blocks past the last "period start" in relation to initial_block and periodicity.  current_block - 15 = 65 - 15 = 50.
+        /// This is the block number where the current period started.
+        #[ink(message)]
+        pub fn get_current_period_initial_block(&self) -> BlockNumber {
+            period::period_initial_block(self.initial_block, self.periodicity, self._now())
+        }
+
+        /// Get the index of the period a block belongs to, the first period being 0
+        /// It fails with InvalidParams for a block before the initial block
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_period_number(&self, block: BlockNumber) -> Result<u32, Error> {
+            period::period_number(self.initial_block, self.periodicity, block)
+                .ok_or(Error::InvalidParams)
+        }
+
+        /// Get next block period
+        #[ink(message)]
+        pub fn get_next_block_period(&self) -> BlockNumber {
+            self.get_current_period_initial_block() + self.periodicity
+        }
+
+        /// Get all the debts up-to-date
+        /// A payment that overflows is counted as Balance::MAX, so the treasury is never considered to have surplus
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_total_debts(&self) -> Balance {
+            let mut debts: Balance = 0;
+            for beneficiary in self._iter_beneficiaries() {
+                debts = debts.saturating_add(
+                    self._get_amount_to_claim(beneficiary.account_id)
+                        .unwrap_or(Balance::MAX),
+                );
             }
+
+            debts
         }
 
-        // Ensure if all beneficiaries claimed in period
-        fn ensure_all_claimed_in_period(&mut self) -> Result<(), Error> {
-            let claiming_period_block = self.get_current_period_initial_block();
+        /// Get the debts up-to-date of each beneficiary, including the ones with nothing to claim
+        /// In private mode only the owner and the admins can read it
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_total_debt_breakdown(&self) -> Result<Vec<(AccountId, Balance)>, Error> {
+            self.ensure_can_read_all_beneficiaries()?;
+            Ok(self
+                ._iter_beneficiaries()
+                .map(|beneficiary| {
+                    let amount = self
+                        ._get_amount_to_claim(beneficiary.account_id)
+                        .unwrap_or(Balance::MAX);
+                    (beneficiary.account_id, amount)
+                })
+                .collect())
+        }
 
-            let claims_in_period = self.claims_in_period.clone();
+        /// Preview the amount each beneficiary would receive claiming everything now,
+        /// and whether the spendable balance of the treasury covers all of them
+        /// In private mode only the owner and the admins can read it
+        /// Read Only function
+        #[ink(message)]
+        pub fn simulate_period_payment(&self) -> Result<(Vec<(AccountId, Balance)>, bool), Error> {
+            self.ensure_can_read_all_beneficiaries()?;
+            let payments = self
+                ._iter_beneficiaries()
+                .map(|beneficiary| {
+                    let amount = self._get_amount_to_claim(beneficiary.account_id)?;
+                    Ok((beneficiary.account_id, amount))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let total = payments.iter().fold(0 as Balance, |total, (_, amount)| {
+                total.saturating_add(*amount)
+            });
+            let solvent = total <= self._get_spendable_balance();
+            Ok((payments, solvent))
+        }
+
+        /// Get all the debts up-to-date of the members of a group
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_group_total_debts(&self, group_id: GroupId) -> Balance {
+            let mut debts: Balance = 0;
+            for account_id in self.get_group_members(group_id).iter() {
+                debts = debts.saturating_add(
+                    self._get_amount_to_claim(*account_id)
+                        .unwrap_or(Balance::MAX),
+                );
+            }
+
+            debts
+        }
+
+        /// Get the payment of one period for the given multipliers, with the current base payment
+        /// The multipliers must be active, like when adding a beneficiary, but anyone can call it
+        /// Read Only function
+        #[ink(message)]
+        pub fn simulate_salary(
+            &self,
+            multipliers: Vec<(MultiplierId, MultiplierValue)>,
+        ) -> Result<Balance, Error> {
+            self.ensure_multipliers_are_valid(&multipliers)?;
+            ensure_no_duplicate_multipliers(&multipliers)?;
+
+            // Price the period like the one of a beneficiary with those multipliers
+            let beneficiary = Beneficiary {
+                account_id: self.env().caller(),
+                multipliers: vec_to_btreemap(&multipliers),
+                unclaimed_payments: 0,
+                last_updated_period_block: self.get_current_period_initial_block(),
+                paused_blocks_at_update: 0,
+                suspended: false,
+                start_block: None,
+                max_per_claim: None,
+                group_id: None,
+                deactivated: false,
+                name: None,
+                notes: None,
+                payout_override: None,
+                unclaimed_bonuses: 0,
+            };
+            self._get_amount_to_claim_for_one_period(&beneficiary, true)
+        }
+
+        /// Get all the debts for the next period
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_total_debt_for_next_period(&self) -> Balance {
+            let mut total: Balance = 0;
+            for beneficiary in self._iter_beneficiaries() {
+                let amount = self
+                    ._get_amount_to_claim_for_one_period(&beneficiary, false)
+                    .unwrap_or(Balance::MAX);
+                total = total.saturating_add(amount);
+            }
+
+            total
+        }
+
+        /// Get all the debts including unclaimed for the next period
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_total_debt_with_unclaimed_for_next_period(&self) -> Balance {
+            let block_next_period = self.get_next_block_period();
+
+            let mut total: Balance = 0;
+            for beneficiary in self._iter_beneficiaries() {
+                let amount = self
+                    ._get_amount_to_claim_in_block(beneficiary.account_id, block_next_period)
+                    .unwrap_or(Balance::MAX);
+                total = total.saturating_add(amount);
+            }
+
+            total
+        }
+
+        /// Get all the debts at a block, if nothing is claimed until then
+        /// The multipliers expiring before the block stop counting from their expiry
+        /// The block can't be before the current block
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_total_debts_at_block(&self, block: BlockNumber) -> Result<Balance, Error> {
+            if block < self._now() {
+                return Err(Error::InvalidParams);
+            }
+
+            let mut total: Balance = 0;
+            for beneficiary in self._iter_beneficiaries() {
+                let amount = self
+                    ._get_amount_to_claim_in_block(beneficiary.account_id, block)
+                    .unwrap_or(Balance::MAX);
+                total = total.saturating_add(amount);
+            }
+
+            Ok(total)
+        }
+
+        /// Get all the beneficiaries
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_list_beneficiaries(&self) -> Vec<AccountId> {
+            self.beneficiaries_accounts.clone()
+        }
+
+        /// Get the beneficiaries that belong to a group
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_group_members(&self, group_id: GroupId) -> Vec<AccountId> {
+            self._iter_beneficiaries()
+                .filter(|beneficiary| beneficiary.group_id == Some(group_id))
+                .map(|beneficiary| beneficiary.account_id)
+                .collect()
+        }
+
+        /// Get a page of the beneficiaries, starting at index start and with at most limit accounts
+        /// The limit is capped at MAX_PAGE_SIZE, and a start past the end returns an empty list
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_list_beneficiaries_paged(&self, start: u32, limit: u32) -> Vec<AccountId> {
+            self.beneficiaries_accounts
+                .iter()
+                .skip(start as usize)
+                .take(limit.min(MAX_PAGE_SIZE) as usize)
+                .cloned()
+                .collect()
+        }
+
+        /// Get a page of the beneficiaries with their data, starting at index offset and with at most limit beneficiaries
+        /// The limit is capped at MAX_PAGE_SIZE, and an offset past the end returns an empty list
+        /// In private mode only the owner and the admins can read it
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_beneficiaries_page(
+            &self,
+            offset: u32,
+            limit: u32,
+        ) -> Result<Vec<Beneficiary>, Error> {
+            self.ensure_can_read_all_beneficiaries()?;
+            Ok(self
+                .get_list_beneficiaries_paged(offset, limit)
+                .into_iter()
+                .filter_map(|account_id| self.beneficiaries.get(account_id))
+                .collect())
+        }
+
+        /// Get contract balance
+        /// It is the balance of the PSP22 token if the contract pays in one
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_contract_balance(&self) -> Balance {
+            match self.token {
+                Some(token) => self._token_balance_of(token, self.env().account_id()),
+                None => self.env().balance(),
+            }
+        }
+
+        /// Get the PSP22 token used for payments, None if the contract pays in the native token
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_token(&self) -> Option<AccountId> {
+            self.token
+        }
+
+        /// Get total balance after paying debts
+        /// It fails with ArithmeticUnderflow if the debts are bigger than the balance
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_balance_with_debts(&self) -> Result<Balance, Error> {
+            self.get_contract_balance()
+                .checked_sub(self.get_total_debts())
+                .ok_or(Error::ArithmeticUnderflow)
+        }
+
+        /// Get the number of periods the treasury can fund after paying the debts up-to-date
+        /// It is u32::MAX if the periods cost nothing, and 0 if the debts are bigger than the balance
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_liquidity_runway(&self) -> Result<u32, Error> {
+            let cost_per_period = self.get_total_debt_for_next_period();
+            if cost_per_period == 0 {
+                return Ok(u32::MAX);
+            }
+            let available_balance = self
+                ._get_spendable_balance()
+                .saturating_sub(self.get_total_debts());
+
+            Ok(u32::try_from(available_balance / cost_per_period).unwrap_or(u32::MAX))
+        }
+
+        /// Get the funds the payroll needs to pay the debts up-to-date and the next n periods
+        /// It is the whole obligation, the balance already in the treasury is not subtracted
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_funding_required_for_n_periods(&self, n: u32) -> Result<Balance, Error> {
+            self.get_total_debt_for_next_period()
+                .checked_mul(n.into())
+                .and_then(|periods_cost| periods_cost.checked_add(self.get_total_debts()))
+                .ok_or(Error::PaymentCalculationOverflow)
+        }
+
+        /// Get the balance above the debts up-to-date, the payments of the maximum runway and the existential deposit
+        /// It is 0 if there is no maximum runway
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_returnable_excess(&self) -> Balance {
+            let max_runway_periods = match self.max_runway_periods {
+                Some(max_runway_periods) => max_runway_periods,
+                None => return 0,
+            };
+            let runway_cost = self
+                .get_total_debt_for_next_period()
+                .saturating_mul(max_runway_periods.into());
+
+            self._get_spendable_balance()
+                .saturating_sub(self.get_total_debts())
+                .saturating_sub(runway_cost)
+        }
+
+        /// Get the balance the owner can withdraw
+        /// It is the balance left after the existential deposit, all the debts up-to-date and the debts for the next period
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_withdrawable_balance(&self) -> Balance {
+            self._get_spendable_balance()
+                .saturating_sub(self.get_total_debts())
+                .saturating_sub(self.get_total_debt_for_next_period())
+        }
+
+        /// Get the existential deposit the treasury keeps to stay alive
+        /// It is the hint set by the owner, or the minimum balance of the chain if it is bigger and
+        /// the contract is built with the "query-minimum-balance" feature.
+        /// It is 0 when the contract pays in a PSP22 token, as the treasury is then kept in the token
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_existential_deposit(&self) -> Balance {
+            if self.token.is_some() {
+                return 0;
+            }
+            #[cfg(feature = "query-minimum-balance")]
+            {
+                self.existential_deposit_hint
+                    .max(self.env().minimum_balance())
+            }
+            #[cfg(not(feature = "query-minimum-balance"))]
+            {
+                self.existential_deposit_hint
+            }
+        }
+
+        /// Get list of unclaimed beneficiaries
+        /// A beneficiary counts as claimed once it claims in the current period, even a partial or a zero amount,
+        /// see get_beneficiaries_with_outstanding for the beneficiaries that still have something to claim
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_unclaimed_beneficiaries(&self) -> Vec<AccountId> {
+            let claiming_period_block = self.get_current_period_initial_block();
+
+            let mut unclaimed_beneficiaries = Vec::new();
+            // iterate over all beneficiaries
+            // if last_updated_period_block < claiming_period_block
+            // then add to unclaimed_beneficiaries
+            for beneficiary in self._iter_beneficiaries() {
+                if beneficiary.last_updated_period_block < claiming_period_block {
+                    unclaimed_beneficiaries.push(beneficiary.account_id);
+                }
+            }
+
+            unclaimed_beneficiaries
+        }
+
+        /// Get count of unclaimed beneficiaries
+        /// Like get_unclaimed_beneficiaries, it counts the beneficiaries that did not claim in the current period
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_count_of_unclaim_beneficiaries(&self) -> u32 {
+            let claiming_period_block = self.get_current_period_initial_block();
+            let mut total: u32 = 0;
+            for beneficiary in self._iter_beneficiaries() {
+                if beneficiary.last_updated_period_block < claiming_period_block {
+                    total += 1;
+                }
+            }
+
+            total
+        }
+
+        /// Get the beneficiaries that have something to claim, with the amount
+        /// Unlike get_unclaimed_beneficiaries, a beneficiary that claimed part of its payments in the current period is included
+        /// In private mode only the owner and the admins can read it
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_beneficiaries_with_outstanding(
+            &self,
+        ) -> Result<Vec<(AccountId, Balance)>, Error> {
+            Ok(self
+                .get_total_debt_breakdown()?
+                .into_iter()
+                .filter(|(_, amount)| *amount > 0)
+                .collect())
+        }
+
+        /// Get the maximum number of beneficiaries
+        #[ink(message)]
+        pub fn get_max_beneficiaries(&self) -> u32 {
+            self.max_beneficiaries
+        }
+
+        /// Get the maximum number of multipliers
+        #[ink(message)]
+        pub fn get_max_multipliers(&self) -> u32 {
+            self.max_multipliers
+        }
+
+        /// Get the maximum number of multipliers of a single beneficiary
+        #[ink(message)]
+        pub fn get_max_multipliers_per_beneficiary(&self) -> u32 {
+            self.max_multipliers_per_beneficiary
+        }
+
+        /// Get the base amount to claim for each beneficiary
+        #[ink(message)]
+        pub fn get_base_payment(&self) -> Balance {
+            self.base_payment
+        }
+
+        /// Get the periodicity of the payments
+        #[ink(message)]
+        pub fn get_periodicity(&self) -> BlockNumber {
+            self.periodicity
+        }
+
+        /// Get the initial block of the contract
+        #[ink(message)]
+        pub fn get_initial_block(&self) -> BlockNumber {
+            self.initial_block
+        }
+
+        /// Get the base multiplier
+        #[ink(message)]
+        pub fn get_multipliers_list(&self) -> Vec<MultiplierId> {
+            self.multipliers_list.clone()
+        }
+
+        /// Get a base multiplier based on its id, failing if it does not exist
+        #[ink(message)]
+        pub fn get_base_multiplier(
+            &self,
+            multiplier_id: MultiplierId,
+        ) -> Result<BaseMultiplier, Error> {
+            self.base_multipliers
+                .get(multiplier_id)
+                .ok_or(Error::MultiplierNotFound)
+        }
+
+        /// Get a base multiplier based on its id, the same as get_base_multiplier
+        #[ink(message)]
+        pub fn get_multiplier(&self, multiplier_id: MultiplierId) -> Result<BaseMultiplier, Error> {
+            self.get_base_multiplier(multiplier_id)
+        }
+
+        /// Get the id of a base multiplier based on its name
+        /// Active multipliers have unique names and take precedence over deactivated ones,
+        /// among deactivated multipliers with the same name the last one added is returned
+        #[ink(message)]
+        pub fn get_multiplier_id_by_name(&self, name: String) -> Option<MultiplierId> {
+            let mut deactivated_id = None;
+            for multiplier_id in self.multipliers_list.iter() {
+                if let Some(base_multiplier) = self.base_multipliers.get(multiplier_id) {
+                    if base_multiplier.name != name {
+                        continue;
+                    }
+                    if base_multiplier.valid_until_block.is_none() {
+                        return Some(*multiplier_id);
+                    }
+                    deactivated_id = Some(*multiplier_id);
+                }
+            }
+            deactivated_id
+        }
+
+        /// Get all the base multipliers with their ids, in the order they were added
+        /// Deactivated multipliers are included with their valid_until_block set
+        #[ink(message)]
+        pub fn get_all_base_multipliers(&self) -> Vec<(MultiplierId, BaseMultiplier)> {
+            self.multipliers_list
+                .iter()
+                .filter_map(|multiplier_id| {
+                    self.base_multipliers
+                        .get(multiplier_id)
+                        .map(|base_multiplier| (*multiplier_id, base_multiplier))
+                })
+                .collect()
+        }
+
+        /// Get the latest claims of a beneficiary, oldest first
+        #[ink(message)]
+        pub fn get_claim_records(&self, account_id: AccountId) -> Result<Vec<ClaimRecord>, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            Ok(self.claim_history.get(account_id).unwrap_or_default())
+        }
+
+        /// Get the expired multipliers removed from a suspended beneficiary, reported when it is unsuspended
+        #[ink(message)]
+        pub fn get_pruned_multipliers(
+            &self,
+            account_id: AccountId,
+        ) -> Result<Vec<MultiplierId>, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            Ok(self.pruned_multipliers.get(account_id).unwrap_or_default())
+        }
+
+        /// Get the multipliers scheduled for a beneficiary from a future period, if any
+        /// They stay listed after their activation block until the beneficiary claims
+        #[ink(message)]
+        pub fn get_scheduled_beneficiary_update(
+            &self,
+            account_id: AccountId,
+        ) -> Result<Option<ScheduledBeneficiaryUpdate>, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            Ok(self.scheduled_beneficiary_updates.get(account_id))
+        }
+
+        /// Get the latest bonuses of a beneficiary, oldest first
+        #[ink(message)]
+        pub fn get_bonus_history(&self, account_id: AccountId) -> Result<Vec<BonusRecord>, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            Ok(self.bonus_history.get(account_id).unwrap_or_default())
+        }
+
+        /// Get the latest claims of a beneficiary as (block, amount), oldest first
+        /// The block is the block number or, in time based mode, the timestamp in seconds of the claim
+        #[ink(message)]
+        pub fn get_claim_history(
+            &self,
+            account_id: AccountId,
+        ) -> Result<Vec<(BlockNumber, Balance)>, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            Ok(self
+                .claim_history
+                .get(account_id)
+                .unwrap_or_default()
+                .iter()
+                .map(|record| (record.claimed_at_block, record.amount))
+                .collect())
+        }
+
+        /// Get the amount paid to all the beneficiaries since the creation of the contract,
+        /// by their claims and the settlements of their removal
+        #[ink(message)]
+        pub fn get_total_paid_out(&self) -> Balance {
+            self.total_paid_out
+        }
+
+        /// Get the amount claimed by a beneficiary since it was added, including the claims evicted from the history
+        #[ink(message)]
+        pub fn get_total_claimed(&self, account_id: AccountId) -> Result<Balance, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            Ok(self.total_claimed.get(account_id).unwrap_or(0))
+        }
+
+        /// Get the number of claims kept in the history of each beneficiary
+        #[ink(message)]
+        pub fn get_claim_history_capacity(&self) -> u32 {
+            self.claim_history_capacity
+        }
+
+        /// Check if the period starting at period_block was paid to a beneficiary
+        /// A period is paid once a claim settled it and no amount was left unclaimed afterwards,
+        /// as the oldest amounts owed are the first to be paid
+        /// It fails if the period is older than the retained claim history
+        #[ink(message)]
+        pub fn was_period_paid(
+            &self,
+            account_id: AccountId,
+            period_block: BlockNumber,
+        ) -> Result<bool, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            let records = self.claim_history.get(account_id).unwrap_or_default();
+
+            let first_record = match records.first() {
+                Some(record) => record,
+                None => return Ok(false),
+            };
+            if period_block < first_record.covered_periods.0 {
+                return Err(Error::PeriodOutsideHistory);
+            }
+
+            // Find the claim that settled the period and check whether it or a later claim paid everything owed
+            let settled_at = records.iter().position(|record| {
+                record.covered_periods.0 <= period_block && period_block < record.covered_periods.1
+            });
+            Ok(match settled_at {
+                Some(index) => records[index..]
+                    .iter()
+                    .any(|record| record.unclaimed_after == 0),
+                None => false,
+            })
+        }
+
+        /// Get the claim delegate of a beneficiary, if any
+        #[ink(message)]
+        pub fn get_claim_delegate(
+            &self,
+            account_id: AccountId,
+        ) -> Result<Option<AccountId>, Error> {
+            self.ensure_can_read_beneficiary(account_id)?;
+            Ok(self.claim_delegates.get(account_id))
+        }
+
+        /// Get the estimated timestamp in milliseconds when the next period starts
+        /// In time based mode it is exact. In block mode it is estimated from the current block timestamp
+        /// and the target block time, as blocks may not be produced at a steady pace (e.g. on-demand parachains),
+        /// and it is None if the target block time is unknown
+        #[ink(message)]
+        pub fn get_estimated_next_period_timestamp(&self) -> Option<Timestamp> {
+            let next_block_period = self.get_next_block_period();
+            if self.time_based {
+                return Some(u64::from(next_block_period) * 1000);
+            }
+            if self.target_block_time == 0 {
+                return None;
+            }
+            let blocks_to_next_period = u64::from(next_block_period - self._now());
+            Some(
+                self.env()
+                    .block_timestamp()
+                    .saturating_add(blocks_to_next_period.saturating_mul(self.target_block_time)),
+            )
+        }
+
+        /// Get the expected time between blocks in milliseconds, or 0 if unknown
+        #[ink(message)]
+        pub fn get_target_block_time(&self) -> u64 {
+            self.target_block_time
+        }
+
+        /// Get the maximum number of periods the treasury may fund on top of the debts
+        #[ink(message)]
+        pub fn get_max_runway_periods(&self) -> Option<u32> {
+            self.max_runway_periods
+        }
+
+        /// Get the account receiving the balance above the maximum runway
+        #[ink(message)]
+        pub fn get_refund_address(&self) -> Option<AccountId> {
+            self.refund_address
+        }
+
+        /// Get the number of periods after which the unclaimed payments expire, or None if they never expire
+        #[ink(message)]
+        pub fn get_unclaimed_expiry_periods(&self) -> Option<u32> {
+            self.unclaimed_expiry_periods
+        }
+
+        /// Get the maximum amount claimed by all the beneficiaries in a period, None if there is no limit
+        #[ink(message)]
+        pub fn get_period_budget(&self) -> Option<Balance> {
+            self.period_budget
+        }
+
+        /// Get the block after which no payment accrues, None if the payroll has no end
+        #[ink(message)]
+        pub fn get_end_block(&self) -> Option<BlockNumber> {
+            self.end_block
+        }
+
+        /// Check if the data of each beneficiary is private
+        #[ink(message)]
+        pub fn is_private(&self) -> bool {
+            self.private
+        }
+
+        /// Check if the periods are measured with the block timestamp instead of the block number
+        #[ink(message)]
+        pub fn is_time_based(&self) -> bool {
+            self.time_based
+        }
+
+        /// Get the owner of the contract, or None if the ownership was renounced
+        #[ink(message)]
+        pub fn get_owner(&self) -> Option<AccountId> {
+            self.owner
+        }
+
+        /// Get the account proposed as the new owner, or None if no transfer is pending
+        #[ink(message)]
+        pub fn get_pending_owner(&self) -> Option<AccountId> {
+            self.proposed_owner
+        }
+
+        /// Get the main configuration and state of the contract in a single call
+        #[ink(message)]
+        pub fn get_contract_info(&self) -> ContractInfo {
+            ContractInfo {
+                owner: self.get_owner(),
+                proposed_owner: self.get_pending_owner(),
+                periodicity: self.get_periodicity(),
+                base_payment: self.get_base_payment(),
+                initial_block: self.get_initial_block(),
+                paused_block_at: self.get_paused_at(),
+                beneficiary_count: self.get_list_beneficiaries().len() as u32,
+                multiplier_count: self.get_multipliers_list().len() as u32,
+                current_period_initial_block: self.get_current_period_initial_block(),
+            }
+        }
+
+        /// Get the balances of get_contract_info as decimal strings
+        /// Read Only function
+        #[ink(message)]
+        pub fn get_contract_info_display(&self) -> ContractInfoDisplay {
+            ContractInfoDisplay {
+                base_payment: balance_to_decimal_string(self.get_base_payment()),
+            }
+        }
+
+        /// Get the version of the storage of the contract
+        #[ink(message)]
+        pub fn get_version(&self) -> u32 {
+            self.contract_version
+        }
+
+        /// Get the identifier of the build the contract was compiled from
+        #[ink(message)]
+        pub fn get_build_id(&self) -> [u8; 32] {
+            BUILD_ID
+        }
+
+        /// Check if an account is an admin
+        /// The owner is always considered an admin
+        #[ink(message)]
+        pub fn is_admin(&self, account_id: AccountId) -> bool {
+            self.owner == Some(account_id) || self.admins.contains(account_id)
+        }
+
+        //----------------------------------------------------------------------------------------
+        // Internal functions
+        //----------------------------------------------------------------------------------------
+
+        // The current or the last pause as (paused_at, ended_at), ended_at being the current block while it lasts
+        fn _get_pause_interval(&self) -> Option<(BlockNumber, BlockNumber)> {
+            let paused_at = self.paused_block_at?;
+            let now = self._now();
+            match self.resume_at {
+                Some(resume_at) if resume_at <= now => Some((paused_at, resume_at)),
+                _ => Some((paused_at, now)),
+            }
+        }
+
+        // Add the current or the last pause to the pause history and the total paused blocks
+        fn _record_pause(&mut self) {
+            if let Some((paused_at, ended_at)) = self._get_pause_interval() {
+                if self.pause_history.len() >= PAUSE_HISTORY_CAPACITY {
+                    self.pause_history.remove(0);
+                }
+                self.pause_history.push((paused_at, ended_at));
+                self.total_paused_blocks = self
+                    .total_paused_blocks
+                    .saturating_add(ended_at.saturating_sub(paused_at));
+            }
+        }
+
+        // Get the number of blocks the contract or the group was paused since the creation of the contract,
+        // including the current pauses
+        // The counters are never evicted, so the difference of two readings is the number of blocks paused in between
+        // A block paused both for the contract and for the group is counted twice, which can only delay an expiry
+        fn _get_paused_blocks_counter(&self, group_id: Option<GroupId>) -> BlockNumber {
+            let group_paused_blocks = match group_id {
+                Some(group_id) => {
+                    let current_pause = match self.paused_groups.get(group_id) {
+                        Some(paused_at) => self._now().saturating_sub(paused_at),
+                        None => 0,
+                    };
+                    self.group_total_paused_blocks
+                        .get(group_id)
+                        .unwrap_or(0)
+                        .saturating_add(current_pause)
+                }
+                None => 0,
+            };
+            self.get_total_paused_blocks()
+                .saturating_add(group_paused_blocks)
+        }
+
+        // Set the period block a beneficiary was last updated in, from which its unclaimed payments expire,
+        // and keep the paused blocks counter so only the blocks paused from then on delay the expiry
+        fn _set_last_updated_period_block(
+            &self,
+            beneficiary: &mut Beneficiary,
+            block: BlockNumber,
+        ) {
+            beneficiary.last_updated_period_block = block;
+            beneficiary.paused_blocks_at_update =
+                self._get_paused_blocks_counter(beneficiary.group_id);
+        }
+
+        // A pause with a duration ends without calling resume, so it is recorded and cleared on the next pause or resume
+        fn _end_expired_pause(&mut self) {
+            if self.paused_block_at.is_some() && !self.is_paused() {
+                self._record_pause();
+                self.paused_block_at = None;
+                self.resume_at = None;
+            }
+        }
+
+        // Add a new base multiplier and return its id
+        fn _add_base_multiplier(&mut self, name: String) -> Result<MultiplierId, Error> {
+            // Ensure the name is not empty nor duplicated
+            self.ensure_multiplier_name_is_valid(&name, None)?;
+
+            // Ensure that the number of multipliers does not exceed the maximum
+            if self.multipliers_list.len() + 1 > self.max_multipliers as usize {
+                return Err(Error::MaxMultipliersExceeded);
+            }
+
+            let base_multiplier = BaseMultiplier::new(name.clone());
+
+            self.base_multipliers
+                .insert(self.next_multiplier_id, &base_multiplier);
+
+            self.multipliers_list.push(self.next_multiplier_id);
+
+            // Increment the next_multiplier_id checking for overflow
+            self.next_multiplier_id = match self.next_multiplier_id.checked_add(1) {
+                Some(val) => val,
+                None => return Err(Error::MultiplierIdOverflow),
+            };
+
+            // Emit the BaseMultiplierAdded event
+            let multiplier_id = self.next_multiplier_id - 1;
+            self.env().emit_event(BaseMultiplierAdded {
+                multiplier_id,
+                name,
+            });
+
+            Ok(multiplier_id)
+        }
+
+        // Rename a base multiplier, ensuring the name is not empty nor duplicated
+        fn _rename_base_multiplier(
+            &mut self,
+            multiplier_id: MultiplierId,
+            new_name: String,
+        ) -> Result<(), Error> {
+            let mut base_multiplier = self
+                .base_multipliers
+                .get(multiplier_id)
+                .ok_or(Error::MultiplierNotFound)?;
+
+            self.ensure_multiplier_name_is_valid(&new_name, Some(multiplier_id))?;
+
+            base_multiplier.name = new_name.clone();
+            self.base_multipliers
+                .insert(multiplier_id, &base_multiplier);
+
+            // Emit the BaseMultiplierRenamed event
+            self.env().emit_event(BaseMultiplierRenamed {
+                multiplier_id,
+                name: new_name,
+            });
+
+            Ok(())
+        }
+
+        // The contract clock, the block number or the block timestamp in seconds in time based mode
+        fn _now(&self) -> BlockNumber {
+            if self.time_based {
+                (self.env().block_timestamp() / 1000) as BlockNumber
+            } else {
+                self.env().block_number()
+            }
+        }
+
+        // Convert a periodicity to the unit of the contract clock
+        // In time based mode it is given in milliseconds and must be a whole number of seconds
+        fn _periodicity_to_clock(&self, periodicity: u32) -> Result<u32, Error> {
+            if !self.time_based {
+                return Ok(periodicity);
+            }
+            if periodicity % 1000 != 0 {
+                return Err(Error::InvalidParams);
+            }
+            Ok(periodicity / 1000)
+        }
+
+        // Get the value transferred to fund the treasury, which must be non zero and in the native token
+        fn _get_native_top_up(&self) -> Result<Balance, Error> {
+            let amount = self.env().transferred_value();
+            if amount == 0 || self.token.is_some() {
+                return Err(Error::InvalidParams);
+            }
+            Ok(amount)
+        }
+
+        // Iterate over the beneficiaries in the order of beneficiaries_accounts
+        // Accounts missing from the beneficiaries mapping are skipped, so the read messages never trap
+        fn _iter_beneficiaries(&self) -> impl Iterator<Item = Beneficiary> + '_ {
+            self.beneficiaries_accounts
+                .iter()
+                .filter_map(|account_id| self.beneficiaries.get(account_id))
+        }
+
+        // Get the balance of the treasury that can be spent without going below the existential deposit
+        fn _get_spendable_balance(&self) -> Balance {
+            self.get_contract_balance()
+                .saturating_sub(self.get_existential_deposit())
+        }
+
+        // Transfer the amount to the account in the native token or in the PSP22 token if set
+        fn _transfer(&self, account_id: AccountId, amount: Balance) -> Result<(), Error> {
+            match self.token {
+                Some(token) => self._token_transfer(token, account_id, amount),
+                None => self
+                    .env()
+                    .transfer(account_id, amount)
+                    .map_err(|_| Error::TransferFailed),
+            }
+        }
+
+        // Call PSP22::transfer on the token contract
+        #[cfg(not(test))]
+        fn _token_transfer(
+            &self,
+            token: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            let result = build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer")))
+                        .push_arg(to)
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke();
+            match result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(Error::TokenTransferFailed),
+            }
+        }
+
+        // Call PSP22::balance_of on the token contract, 0 if the call fails
+        #[cfg(not(test))]
+        fn _token_balance_of(&self, token: AccountId, owner: AccountId) -> Balance {
+            let result = build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::balance_of")))
+                        .push_arg(owner),
+                )
+                .returns::<Balance>()
+                .try_invoke();
+            match result {
+                Ok(Ok(balance)) => balance,
+                _ => 0,
+            }
+        }
+
+        // Off-chain tests can't call other contracts, so the token calls go to a mocked PSP22 ledger
+        #[cfg(test)]
+        fn _token_transfer(
+            &self,
+            token: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            psp22_mock::transfer(token, self.env().account_id(), to, amount)
+                .map_err(|_| Error::TokenTransferFailed)
+        }
+
+        #[cfg(test)]
+        fn _token_balance_of(&self, token: AccountId, owner: AccountId) -> Balance {
+            psp22_mock::balance_of(token, owner)
+        }
+
+        // Append a claim to the history of the beneficiary, evicting the oldest one when full
+        // The claim settles the periods from the end of the previous claim, so amounts moved to
+        // the unclaimed payments by zero claims or updates are attributed to the next claim
+        fn _record_claim(
+            &mut self,
+            account_id: AccountId,
+            claimed_at_block: BlockNumber,
+            amount: Balance,
+            last_updated_period_block: BlockNumber,
+            claiming_period_block: BlockNumber,
+            unclaimed_after: Balance,
+        ) {
+            let mut records = self.claim_history.get(account_id).unwrap_or_default();
+            let from_period_block = match records.last() {
+                Some(record) => record.covered_periods.1,
+                None => last_updated_period_block,
+            };
+            // The capacity may have been lowered since the last claim
+            let capacity = self.claim_history_capacity as usize;
+            if records.len() >= capacity {
+                records.drain(..=records.len() - capacity);
+            }
+            records.push(ClaimRecord {
+                claimed_at_block,
+                amount,
+                covered_periods: (from_period_block, claiming_period_block),
+                unclaimed_after,
+            });
+            self.claim_history.insert(account_id, &records);
+
+            let total_claimed = self.total_claimed.get(account_id).unwrap_or(0);
+            self.total_claimed
+                .insert(account_id, &total_claimed.saturating_add(amount));
+        }
+
+        // Add an amount paid to a beneficiary to the total paid out
+        fn _add_paid_out(&mut self, amount: Balance) -> Result<(), Error> {
+            self.total_paid_out = self
+                .total_paid_out
+                .checked_add(amount)
+                .ok_or(Error::PaymentCalculationOverflow)?;
+            Ok(())
+        }
+
+        // Ensure_owner ensures that the caller is the owner of the contract
+        fn ensure_owner(&self) -> Result<(), Error> {
+            let account = self.env().caller();
+            // Only owners can call this function
+            if self.owner != Some(account) {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        // Ensure_owner_or_admin ensures that the caller is the owner or an admin of the contract
+        fn ensure_owner_or_admin(&self) -> Result<(), Error> {
+            if !self.is_admin(self.env().caller()) {
+                return Err(Error::NotAdmin);
+            }
+            Ok(())
+        }
+
+        // ensure_can_read_beneficiary ensures the caller can read the data of a beneficiary and that it exists
+        // In private mode only the owner, the admins, the beneficiary and its claim delegate can read it.
+        // The caller is checked first, so an unauthorized caller can't tell whether the account is a beneficiary.
+        // This only hides the data from the messages: read-only calls can be dry-run with any caller
+        // and the storage is public, so it is not a confidentiality guarantee
+        fn ensure_can_read_beneficiary(&self, account_id: AccountId) -> Result<(), Error> {
+            if self.private {
+                let caller = self.env().caller();
+                if self.owner != Some(caller)
+                    && caller != account_id
+                    && !self.admins.contains(caller)
+                    && self.claim_delegates.get(account_id) != Some(caller)
+                {
+                    return Err(Error::NotAuthorized);
+                }
+            }
+            if !self.beneficiaries.contains(account_id) {
+                return Err(Error::AccountNotFound);
+            }
+            Ok(())
+        }
+
+        // ensure_can_read_all_beneficiaries ensures the caller can read the data of every beneficiary at once
+        // In private mode only the owner and the admins can, see ensure_can_read_beneficiary
+        fn ensure_can_read_all_beneficiaries(&self) -> Result<(), Error> {
+            if self.private && !self.is_admin(self.env().caller()) {
+                return Err(Error::NotAuthorized);
+            }
+            Ok(())
+        }
+
+        // ensure_valid_role_assignment ensures an account can be assigned a role
+        // - the contract itself can't hold a role: it never signs a call, and refunds to itself would never leave
+        // - the owner can't be an admin, it already has every right of an admin
+        // - the refund address can't be a beneficiary, the funds returned to the funding DAO would be claimable again
+        // The claim delegate of a beneficiary can't be the beneficiary, checked by set_claim_delegate
+        fn ensure_valid_role_assignment(
+            &self,
+            role: Role,
+            account_id: AccountId,
+        ) -> Result<(), Error> {
+            if account_id == self.env().account_id() {
+                return Err(Error::InvalidRoleAssignment);
+            }
+            match role {
+                Role::Owner if account_id == AccountId::from([0u8; 32]) => {
+                    Err(Error::InvalidRoleAssignment)
+                }
+                Role::Admin if self.owner == Some(account_id) => Err(Error::InvalidRoleAssignment),
+                Role::RefundAddress if self.beneficiaries.contains(account_id) => {
+                    Err(Error::InvalidRoleAssignment)
+                }
+                Role::Beneficiary if self.refund_address == Some(account_id) => {
+                    Err(Error::InvalidRoleAssignment)
+                }
+                _ => Ok(()),
+            }
+        }
+
+        // ensure_is_not_paused ensures that the contract is not paused
+        // It reads the lifecycle state so that the guard and get_lifecycle_state always agree
+        fn ensure_is_not_paused(&self) -> Result<(), Error> {
+            if let LifecycleState::Paused { .. } = self.get_lifecycle_state() {
+                return Err(Error::ContractIsPaused);
+            }
+            Ok(())
+        }
+
+        // Ensure multipliers are valid
+        fn ensure_multipliers_are_valid(
+            &self,
+            multipliers: &[(MultiplierId, MultiplierValue)],
+        ) -> Result<(), Error> {
+            if multipliers.len() > self.max_multipliers_per_beneficiary as usize {
+                return Err(Error::MaxMultipliersPerBeneficiaryExceeded);
+            }
+            for (multiplier_id, multiplier) in multipliers.iter() {
+                if !self.base_multipliers.contains(multiplier_id) {
+                    return Err(Error::MultiplierNotFound);
+                }
+                if *multiplier > MAX_MULTIPLIER {
+                    return Err(Error::MultiplierValueTooLarge);
+                }
+                if self
+                    .base_multipliers
+                    .get(multiplier_id)
+                    .unwrap()
+                    .valid_until_block
+                    .is_some()
+                {
+                    return Err(Error::MultiplierAlreadyDeactivated);
+                }
+            }
+            Ok(())
+        }
+
+        // Ensure a multiplier name is not empty, not too long and not used by another active multiplier
+        fn ensure_multiplier_name_is_valid(
+            &self,
+            name: &str,
+            multiplier_id: Option<MultiplierId>,
+        ) -> Result<(), Error> {
+            if name.is_empty() {
+                return Err(Error::InvalidParams);
+            }
+            if name.chars().count() > MAX_MULTIPLIER_NAME_LENGTH {
+                return Err(Error::NameTooLong);
+            }
+            for id in self.multipliers_list.iter() {
+                if Some(*id) == multiplier_id {
+                    continue;
+                }
+                if let Some(base_multiplier) = self.base_multipliers.get(id) {
+                    if base_multiplier.name == name && base_multiplier.valid_until_block.is_none() {
+                        return Err(Error::DuplicatedMultiplierName);
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        // Function for doing the ensurance of a single account before adding it as a new beneficiary
+        // The caller and the number of beneficiaries are checked once by the callers, for the whole batch
+        fn ensure_beneficiary_to_add(
+            &self,
+            account_id: AccountId,
+            multipliers: &[(MultiplierId, MultiplierValue)],
+            name: &Option<String>,
+            notes: &Option<String>,
+        ) -> Result<(), Error> {
+            // Ensure that the beneficiary does not exist
+            if self.beneficiaries.contains(account_id) {
+                return Err(Error::AccountAlreadyExists);
+            }
+
+            self.ensure_valid_role_assignment(Role::Beneficiary, account_id)?;
+
+            // Ensure that the multipliers and the metadata are valid
+            self.ensure_multipliers_are_valid(multipliers)?;
+            ensure_no_duplicate_multipliers(&Vec::from(multipliers))?;
+            ensure_valid_beneficiary_metadata(name, notes)?;
+
+            Ok(())
+        }
+
+        // Get the amount of tokens that can be claimed by a beneficiary with specific block_numer
+        fn _get_amount_to_claim_in_block(
+            &self,
+            account_id: AccountId,
+            block: BlockNumber,
+        ) -> Result<Balance, Error> {
+            // The check that beneficiary exists is done in the caller function
+            let mut beneficiary = self.beneficiaries.get(account_id).unwrap();
+
+            self._expire_unclaimed_payments(&mut beneficiary, block);
+
+            // Periods before a scheduled update or before a multiplier expired are still paid with the previous multipliers
+            self._apply_scheduled_beneficiary_update(&mut beneficiary, block)?;
+            self._settle_expired_multipliers(&mut beneficiary, block)?;
+
+            self._get_accrued_amount(&beneficiary, block)
+        }
+
+        // Get the unclaimed payments plus the periods elapsed until the block, paid with the
+        // multipliers the beneficiary currently holds
+        fn _get_accrued_amount(
+            &self,
+            beneficiary: &Beneficiary,
+            block: BlockNumber,
+        ) -> Result<Balance, Error> {
+            // Calculates the number of periods that are due based on the elapsed blocks
+            let unclaimed_periods = self._get_unclaimed_periods(beneficiary, block);
+
+            // If there's no unclaimed periods, return the unclaimed payments
+            // Otherwise, calculate the amount to claim and add the unclaimed payments
+            if unclaimed_periods == 0 {
+                Ok(beneficiary.unclaimed_payments)
+            } else {
+                let payment_per_period =
+                    self._get_amount_to_claim_for_one_period(beneficiary, true)?;
+
+                claims::accrued_amount(
+                    beneficiary.unclaimed_payments,
+                    unclaimed_periods,
+                    payment_per_period,
+                )
+                .ok_or(Error::PaymentCalculationOverflow)
+            }
+        }
+
+        // Records the multipliers removed from a suspended beneficiary when settling it
+        // What was earned before the suspension was already priced with them by suspend_beneficiary
+        fn _record_pruned_multipliers(&mut self, unsettled: &Beneficiary, settled: &Beneficiary) {
+            if !settled.suspended {
+                return;
+            }
+            let mut pruned_multipliers = self
+                .pruned_multipliers
+                .get(settled.account_id)
+                .unwrap_or_default();
+            let previous_len = pruned_multipliers.len();
+            pruned_multipliers.extend(
+                unsettled
+                    .multipliers
+                    .keys()
+                    .filter(|multiplier_id| !settled.multipliers.contains_key(multiplier_id)),
+            );
+            if pruned_multipliers.len() > previous_len {
+                self.pruned_multipliers
+                    .insert(settled.account_id, &pruned_multipliers);
+            }
+        }
+
+        // Get the amount a beneficiary earned until now, before it is kept as its unclaimed payments
+        // The unclaimed payments that expired are dropped from the beneficiary and reported like in a claim
+        fn _snapshot_amount_to_claim(
+            &self,
+            beneficiary: &mut Beneficiary,
+        ) -> Result<Balance, Error> {
+            let current_block = self._now();
+
+            let expired_payments = self._expire_unclaimed_payments(beneficiary, current_block);
+            if expired_payments > 0 {
+                // Emit the BeneficiaryPaymentExpired event
+                self.env().emit_event(BeneficiaryPaymentExpired {
+                    account_id: beneficiary.account_id,
+                    amount: expired_payments,
+                });
+            }
+
+            // The stored multipliers are only replaced by the caller
+            let mut settled_beneficiary = beneficiary.clone();
+            self._apply_scheduled_beneficiary_update(&mut settled_beneficiary, current_block)?;
+            self._settle_expired_multipliers(&mut settled_beneficiary, current_block)?;
+
+            self._get_accrued_amount(&settled_beneficiary, current_block)
+        }
+
+        // Drops the unclaimed payments of a beneficiary last updated more than the expiry periods before the block
+        // Only the stored unclaimed payments expire, the periods elapsed since the last update are still owed,
+        // and the unclaimed bonuses never expire
+        // Nothing expires while the beneficiary is suspended, and the blocks the contract or its group
+        // was paused are not counted, since the beneficiary could not claim during them
+        // Returns the expired amount
+        fn _expire_unclaimed_payments(
+            &self,
+            beneficiary: &mut Beneficiary,
+            block: BlockNumber,
+        ) -> Balance {
+            let unclaimed_expiry_periods = match self.unclaimed_expiry_periods {
+                Some(unclaimed_expiry_periods) => unclaimed_expiry_periods,
+                None => return 0,
+            };
+            if beneficiary.suspended {
+                return 0;
+            }
+            let paused_blocks = self
+                ._get_paused_blocks_counter(beneficiary.group_id)
+                .saturating_sub(beneficiary.paused_blocks_at_update);
+            if !claims::is_expired(
+                beneficiary.last_updated_period_block,
+                self._get_accrual_end(block),
+                paused_blocks,
+                self.periodicity,
+                unclaimed_expiry_periods,
+            ) {
+                return 0;
+            }
+
+            let expired_payments = beneficiary
+                .unclaimed_payments
+                .saturating_sub(beneficiary.unclaimed_bonuses);
+            beneficiary.unclaimed_payments = beneficiary.unclaimed_bonuses;
+            expired_payments
+        }
+
+        // Get the number of full periods elapsed since the last payment of a beneficiary until the block
+        fn _get_unclaimed_periods(&self, beneficiary: &Beneficiary, block: BlockNumber) -> u32 {
+            // Payments accrue from the last payment, but never before the vesting cliff
+            let accrual_start_block = claims::accrual_start(
+                self.initial_block,
+                self.periodicity,
+                beneficiary.last_updated_period_block,
+                beneficiary.start_block,
+            );
+
+            period::elapsed_periods(
+                accrual_start_block,
+                self._get_accrual_end(block),
+                self.periodicity,
+            )
+        }
+
+        // Get the block until which payments accrue when reaching the given block
+        // Past the end block nothing accrues, so only the periods ending at or before it are paid
+        fn _get_accrual_end(&self, block: BlockNumber) -> BlockNumber {
+            claims::accrual_end(block, self.end_block)
+        }
+
+        // Settles the multipliers of a beneficiary that expired at or before the given block
+        // Going from the oldest expiry, the periods elapsed before it are paid with the expired multiplier
+        // and moved to the unclaimed payments, then the multiplier is dropped from the beneficiary.
+        // This way the amount owed for a period is fixed once the period ends, no matter how late it is claimed.
+        fn _settle_expired_multipliers(
+            &self,
+            beneficiary: &mut Beneficiary,
+            block: BlockNumber,
+        ) -> Result<(), Error> {
+            // Multipliers that no longer exist have nothing left to settle, they are just dropped
+            beneficiary
+                .multipliers
+                .retain(|k, _| self.base_multipliers.contains(k));
+
+            let mut expired_multipliers: Vec<(BlockNumber, MultiplierId)> = beneficiary
+                .multipliers
+                .keys()
+                .filter_map(|k| {
+                    let valid_until_block = self.base_multipliers.get(k)?.valid_until_block?;
+                    (valid_until_block <= block).then_some((valid_until_block, *k))
+                })
+                .collect();
+            expired_multipliers.sort();
+
+            for (valid_until_block, multiplier_id) in expired_multipliers {
+                if valid_until_block > beneficiary.last_updated_period_block {
+                    let settled_periods = period::elapsed_periods(
+                        beneficiary.last_updated_period_block,
+                        valid_until_block,
+                        self.periodicity,
+                    );
+                    beneficiary.unclaimed_payments =
+                        self._get_accrued_amount(beneficiary, valid_until_block)?;
+                    beneficiary.last_updated_period_block += settled_periods * self.periodicity;
+                }
+                beneficiary.multipliers.remove(&multiplier_id);
+            }
+
+            Ok(())
+        }
+
+        // Applies the multipliers scheduled for a beneficiary if their activation block is at or before the given block
+        // Like for an expired multiplier, the periods elapsed before the activation are paid with the previous
+        // multipliers and moved to the unclaimed payments, so a scheduled raise never pays the periods before it
+        fn _apply_scheduled_beneficiary_update(
+            &self,
+            beneficiary: &mut Beneficiary,
+            block: BlockNumber,
+        ) -> Result<(), Error> {
+            let update = match self
+                .scheduled_beneficiary_updates
+                .get(beneficiary.account_id)
+            {
+                Some(update) if update.activation_block <= block => update,
+                _ => return Ok(()),
+            };
+
+            self._settle_expired_multipliers(beneficiary, update.activation_block)?;
+            if update.activation_block > beneficiary.last_updated_period_block {
+                let settled_periods = period::elapsed_periods(
+                    beneficiary.last_updated_period_block,
+                    update.activation_block,
+                    self.periodicity,
+                );
+                beneficiary.unclaimed_payments =
+                    self._get_accrued_amount(beneficiary, update.activation_block)?;
+                beneficiary.last_updated_period_block += settled_periods * self.periodicity;
+            }
+            beneficiary.multipliers = update.multipliers;
+
+            Ok(())
+        }
+
+        // Removes the update scheduled for a beneficiary once it is applied and stored in the beneficiary
+        fn _remove_due_beneficiary_update(&mut self, account_id: AccountId, block: BlockNumber) {
+            if let Some(update) = self.scheduled_beneficiary_updates.get(account_id) {
+                if update.activation_block <= block {
+                    self.scheduled_beneficiary_updates.remove(account_id);
+                }
+            }
+        }
+
+        // check the amount to claim for one beneficiary in any period
+        // without unclaimed payments
+        fn _get_amount_to_claim_for_one_period(
+            &self,
+            beneficiary: &Beneficiary,
+            filtered_multipliers: bool,
+        ) -> Result<Balance, Error> {
+            // Suspended and deactivated beneficiaries do not accrue payments
+            if beneficiary.suspended || beneficiary.deactivated {
+                return Ok(0);
+            }
+
+            // The unfiltered amount is the one of the next period, nothing is paid for it if it ends after the end block,
+            // whether the beneficiary has multipliers or not
+            let next_period_end_block = self.get_next_block_period() + self.periodicity;
+            if !filtered_multipliers
+                && self._get_accrual_end(next_period_end_block) < next_period_end_block
+            {
+                return Ok(0);
+            }
+
+            let payment = match filtered_multipliers {
+                true => claims::payment_for_one_period(
+                    self.base_payment,
+                    &beneficiary.multipliers,
+                    |_| true,
+                ),
+                // Only the multipliers still valid at the end of the next period are counted
+                _ => claims::payment_for_one_period(
+                    self.base_payment,
+                    &beneficiary.multipliers,
+                    // A multiplier that no longer exists is skipped as if it was expired
+                    |multiplier_id| match self.base_multipliers.get(multiplier_id) {
+                        Some(base_multiplier) => multipliers::is_paid_until(
+                            base_multiplier.valid_until_block,
+                            next_period_end_block,
+                        ),
+                        None => false,
+                    },
+                ),
+            };
+
+            payment.ok_or(Error::PaymentCalculationOverflow)
+        }
+
+        // internal function to get the amount to claim
+        fn _get_amount_to_claim(&self, account_id: AccountId) -> Result<Balance, Error> {
+            let current_block = self._now();
+
+            self._get_amount_to_claim_in_block(account_id, current_block)
+        }
+
+        // Updates the claims in a period
+        // If the period is the same, it adds the account if it has not claimed yet and adds the amount
+        // Otherwise, it resets the claims to only the account and its amount
+        fn _update_claims_in_period(
+            &mut self,
+            claiming_period_block: BlockNumber,
+            account_id: AccountId,
+            amount: Balance,
+        ) {
+            if claiming_period_block == self.claims_in_period.period {
+                // Updates current claims in period
+                if !self.claimed_accounts_in_period.contains(&account_id) {
+                    self.claimed_accounts_in_period.push(account_id);
+                }
+                self.claims_in_period.total_amount =
+                    self.claims_in_period.total_amount.saturating_add(amount);
+            } else {
+                // Reset the claims in period
+                self.claims_in_period.period = claiming_period_block;
+                self.claimed_accounts_in_period = vec![account_id];
+                self.claims_in_period.total_amount = amount;
+            }
+            self.claims_in_period.total_claims = self.claimed_accounts_in_period.len() as u32;
+        }
+
+        // Check if a beneficiary can claim a non zero amount now, it can't while the contract or its group
+        // is paused or while it is suspended
+        fn _can_claim(&self, beneficiary: &Beneficiary) -> bool {
+            let group_paused = match beneficiary.group_id {
+                Some(group_id) => self.is_group_paused(group_id),
+                None => false,
+            };
+            !self.is_paused() && !group_paused && !beneficiary.suspended
+        }
+
+        // Ensure an amount paid in the period starting at the block fits in the budget of the period
+        fn ensure_within_period_budget(
+            &self,
+            claiming_period_block: BlockNumber,
+            amount: Balance,
+        ) -> Result<(), Error> {
+            if let Some(period_budget) = self.period_budget {
+                let claimed_in_period = match self.claims_in_period.period == claiming_period_block
+                {
+                    true => self.claims_in_period.total_amount,
+                    false => 0,
+                };
+                if claimed_in_period.saturating_add(amount) > period_budget {
+                    return Err(Error::PeriodBudgetExceeded);
+                }
+            }
+            Ok(())
+        }
+
+        // Ensure if all beneficiaries claimed in period
+        fn ensure_all_claimed_in_period(&self) -> Result<(), Error> {
+            if self.is_all_claimed_in_period() {
+                return Ok(());
+            }
+
+            Err(Error::NotAllClaimedInPeriod)
+        }
+    }
+
+    //----------------------------------------------------------------------------------------
+    // Pure functions
+    //----------------------------------------------------------------------------------------
+
+    /// Given a vector of (id, multiplier) pairs, return a BTreeMap of (id, multiplier) pairs
+    fn vec_to_btreemap(
+        vec: &[(MultiplierId, MultiplierValue)],
+    ) -> BTreeMap<MultiplierId, MultiplierValue> {
+        let mut btree_map = BTreeMap::new();
+        for (id, multiplier) in vec.iter() {
+            btree_map.insert(*id, *multiplier);
+        }
+        btree_map
+    }
+
+    /// Ensures the base payment is not 0, shared by the constructor and update_base_payment
+    fn ensure_valid_base_payment(base_payment: Balance) -> Result<(), Error> {
+        if base_payment == 0 {
+            return Err(Error::InvalidParams);
+        }
+        Ok(())
+    }
+
+    /// Given a list of beneficiaries it ensures there are no duplicates
+    #[allow(clippy::all)]
+    fn ensure_no_duplicate_beneficiaries(beneficiaries: &Vec<AccountId>) -> Result<(), Error> {
+        let mut sorted_beneficiaries = beneficiaries.clone();
+        sorted_beneficiaries.sort_by_key(|&beneficiary| beneficiary);
+
+        for i in 1..sorted_beneficiaries.len() {
+            if sorted_beneficiaries[i - 1] == sorted_beneficiaries[i] {
+                return Err(Error::DuplicatedBeneficiaries);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Given a list of multipliers it ensures there are no duplicates
+    #[allow(clippy::all)]
+    fn ensure_no_duplicate_multipliers(
+        multipliers: &Vec<(MultiplierId, MultiplierValue)>,
+    ) -> Result<(), Error> {
+        let mut sorted_multipliers = multipliers.clone();
+        sorted_multipliers.sort_by_key(|&(multiplier_id, _)| multiplier_id);
+
+        for i in 1..sorted_multipliers.len() {
+            if sorted_multipliers[i - 1].0 == sorted_multipliers[i].0 {
+                return Err(Error::DuplicatedMultipliers);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Given the name and the notes of a beneficiary it ensures they are not too long
+    fn ensure_valid_beneficiary_metadata(
+        name: &Option<String>,
+        notes: &Option<String>,
+    ) -> Result<(), Error> {
+        for text in [name, notes].into_iter().flatten() {
+            if text.len() > MAX_BENEFICIARY_METADATA_LENGTH {
+                return Err(Error::InvalidParams);
+            }
+        }
+        Ok(())
+    }
+
+    /// Given a balance, return its decimal representation
+    /// A frontend reading numbers as 64 bit floats loses precision above 2^53, so the views also expose balances as strings
+    fn balance_to_decimal_string(balance: Balance) -> String {
+        // Balance::MAX has 39 digits
+        let mut digits = [0u8; 39];
+        let mut start = digits.len();
+        let mut value = balance;
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        digits[start..].iter().map(|digit| *digit as char).collect()
+    }
+
+    /// Given a string of 64 hex characters, return the 32 bytes it represents
+    /// It is evaluated at compile time, so an invalid build id fails the build
+    const fn parse_build_id(build_id: &str) -> [u8; 32] {
+        let hex = build_id.as_bytes();
+        assert!(
+            hex.len() == 64,
+            "OPEN_PAYROLL_BUILD_ID must be 64 hex characters"
+        );
+
+        let mut bytes = [0u8; 32];
+        let mut i = 0;
+        while i < 32 {
+            bytes[i] = (hex_value(hex[2 * i]) << 4) | hex_value(hex[2 * i + 1]);
+            i += 1;
+        }
+        bytes
+    }
+
+    /// Given an hex character, return its value
+    const fn hex_value(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("OPEN_PAYROLL_BUILD_ID must be 64 hex characters"),
+        }
+    }
+
+    //----------------------------------------------------------------------------------------
+    // Tests
+    //----------------------------------------------------------------------------------------
+    /// Mocked PSP22 ledger used by the unit tests instead of a token contract
+    #[cfg(test)]
+    mod psp22_mock {
+        use super::*;
+        use std::cell::RefCell;
+
+        thread_local! {
+            static BALANCES: RefCell<BTreeMap<(AccountId, AccountId), Balance>> =
+                const { RefCell::new(BTreeMap::new()) };
+        }
+
+        pub fn set_balance(token: AccountId, owner: AccountId, amount: Balance) {
+            BALANCES.with(|b| b.borrow_mut().insert((token, owner), amount));
+        }
+
+        pub fn balance_of(token: AccountId, owner: AccountId) -> Balance {
+            BALANCES.with(|b| *b.borrow().get(&(token, owner)).unwrap_or(&0))
+        }
+
+        pub fn transfer(
+            token: AccountId,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<(), PSP22Error> {
+            let from_balance = balance_of(token, from);
+            if from_balance < amount {
+                return Err(PSP22Error::InsufficientBalance);
+            }
+            set_balance(token, from, from_balance - amount);
+            set_balance(token, to, balance_of(token, to) + amount);
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use ink::{
+            env::{test::DefaultAccounts, DefaultEnvironment},
+            primitives::AccountId,
+        };
+
+        use super::*;
+
+        // UTILITY FUNCTIONS TO MAKE TESTING EASIER
+        fn create_contract(
+            initial_balance: Balance,
+            accounts: &DefaultAccounts<DefaultEnvironment>,
+        ) -> OpenPayroll {
+            set_balance(contract_id(), initial_balance);
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100)), (1, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let beneficiary_charlie = InitialBeneficiary {
+                account_id: accounts.charlie,
+                multipliers: vec![(0, percent(100)), (1, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![beneficiary_bob, beneficiary_charlie],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .expect("Cannot create contract")
+        }
+
+        fn create_contract_with_no_beneficiaries(initial_balance: Balance) -> OpenPayroll {
+            set_balance(contract_id(), initial_balance);
+            OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .expect("Cannot create contract")
+        }
+
+        fn create_contract_with_no_beneficiaries_periodicity(
+            initial_balance: Balance,
+            periodicity: u32,
+        ) -> OpenPayroll {
+            set_balance(contract_id(), initial_balance);
+            OpenPayroll::new(
+                periodicity,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .expect("Cannot create contract")
+        }
+
+        fn create_accounts_and_contract(
+            initial_balance: Balance,
+        ) -> (
+            ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment>,
+            OpenPayroll,
+        ) {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+
+            let contract = create_contract(initial_balance, &accounts);
+            (accounts, contract)
+        }
+
+        fn contract_id() -> AccountId {
+            ink::env::test::callee::<ink::env::DefaultEnvironment>()
+        }
+
+        fn set_sender(sender: AccountId) {
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(sender);
+        }
+
+        fn default_accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            ink::env::test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+
+        fn set_balance(account_id: AccountId, balance: Balance) {
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(account_id, balance)
+        }
+
+        fn advance_n_blocks(n: u32) {
+            for _ in 0..n {
+                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            }
+        }
+
+        fn get_current_block() -> u32 {
+            ink::env::block_number::<ink::env::DefaultEnvironment>()
+        }
+
+        fn get_balance(account_id: AccountId) -> Balance {
+            ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account_id)
+                .expect("Cannot get account balance")
+        }
+
+        fn percent(percent: u128) -> MultiplierValue {
+            MultiplierValue::from_percent(percent)
+        }
+
+        fn vec_to_btreemap(
+            vec: &[(MultiplierId, MultiplierValue)],
+        ) -> BTreeMap<MultiplierId, MultiplierValue> {
+            let mut btree_map = BTreeMap::new();
+            for (id, multiplier) in vec.iter() {
+                btree_map.insert(*id, *multiplier);
+            }
+            btree_map
+        }
+
+        /// We test if the default constructor does its job.
+        #[ink::test]
+        fn default_works() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            create_contract(100_000_000u128, &accounts)
+        }
+
+        #[ink::test]
+        fn create_contract_ok() {
+            let accounts = default_accounts();
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100)), (1, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let beneficiary_charlie = InitialBeneficiary {
+                account_id: accounts.charlie,
+                multipliers: vec![(0, percent(100)), (1, percent(10))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![beneficiary_bob, beneficiary_charlie],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+            assert!(matches!(res, Ok(_)));
+            let contract = res.unwrap();
+
+            // check that base_multipliers are set correctly
+            let data_0 = contract.base_multipliers.get(0).unwrap();
+            let data_1 = contract.base_multipliers.get(1).unwrap();
+            assert_eq!(
+                data_0,
+                BaseMultiplier {
+                    name: "Seniority".to_string(),
+                    valid_until_block: None,
+                    sponsor: None,
+                }
+            );
+            assert_eq!(
+                data_1,
+                BaseMultiplier {
+                    name: "Performance".to_string(),
+                    valid_until_block: None,
+                    sponsor: None,
+                }
+            );
+
+            // check that beneficiaries are set correctly
+            let data_bob = contract.beneficiaries.get(accounts.bob).unwrap();
+            let data_charlie = contract.beneficiaries.get(accounts.charlie).unwrap();
+            assert_eq!(
+                data_bob,
+                Beneficiary {
+                    account_id: accounts.bob,
+                    multipliers: vec_to_btreemap(&[(0, percent(100)), (1, percent(3))]),
+                    unclaimed_payments: 0,
+                    last_updated_period_block: 0,
+                    paused_blocks_at_update: 0,
+                    suspended: false,
+                    start_block: None,
+                    max_per_claim: None,
+                    group_id: None,
+                    deactivated: false,
+                    name: None,
+                    notes: None,
+                    payout_override: None,
+                    unclaimed_bonuses: 0,
+                }
+            );
+            assert_eq!(
+                data_charlie,
+                Beneficiary {
+                    account_id: accounts.charlie,
+                    multipliers: vec_to_btreemap(&[(0, percent(100)), (1, percent(10))]),
+                    unclaimed_payments: 0,
+                    last_updated_period_block: 0,
+                    paused_blocks_at_update: 0,
+                    suspended: false,
+                    start_block: None,
+                    max_per_claim: None,
+                    group_id: None,
+                    deactivated: false,
+                    name: None,
+                    notes: None,
+                    payout_override: None,
+                    unclaimed_bonuses: 0,
+                }
+            );
+
+            // check accounts are set correctly
+            assert_eq!(
+                contract.beneficiaries_accounts.get(0).unwrap(),
+                &accounts.bob
+            );
+            assert_eq!(
+                contract.beneficiaries_accounts.get(1).unwrap(),
+                &accounts.charlie
+            );
+
+            // check claims in period are set correctly
+            assert_eq!(
+                contract.claims_in_period,
+                ClaimsInPeriod {
+                    period: 0,
+                    total_claims: 0,
+                    total_amount: 0,
+                }
+            );
+        }
+
+        #[ink::test]
+        fn create_contract_with_invalid_amount_of_multipliers() {
+            let accounts = default_accounts();
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100)), (1, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let beneficiary_charlie = InitialBeneficiary {
+                account_id: accounts.charlie,
+                multipliers: vec![(0, percent(100))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![beneficiary_bob, beneficiary_charlie],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+
+            assert!(matches!(res, Err(Error::InvalidMultipliersLength)));
+
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let beneficiary_charlie = InitialBeneficiary {
+                account_id: accounts.charlie,
+                multipliers: vec![(0, percent(100))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![beneficiary_bob, beneficiary_charlie],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+
+            assert!(matches!(res, Err(Error::InvalidMultipliersLength)));
+
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let beneficiary_charlie = InitialBeneficiary {
+                account_id: accounts.charlie,
+                multipliers: vec![],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![beneficiary_bob, beneficiary_charlie],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+
+            assert!(matches!(res, Err(Error::InvalidMultipliersLength)));
+
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(10)), (1, percent(3)), (2, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let beneficiary_charlie = InitialBeneficiary {
+                account_id: accounts.charlie,
+                multipliers: vec![(0, percent(10)), (1, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec![
+                    "Seniority".to_string(),
+                    "Performance".to_string(),
+                    "Years_at_company".to_string(),
+                ],
+                vec![beneficiary_bob, beneficiary_charlie],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+
+            assert!(matches!(res, Err(Error::InvalidMultipliersLength)));
+        }
+
+        #[ink::test]
+        fn create_contract_with_invalid_multiplier_ids() {
+            let accounts = default_accounts();
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(7, percent(100)), (9, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![beneficiary_bob],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+
+            assert!(matches!(res, Err(Error::MultiplierNotFound)));
+
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100)), (2, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![beneficiary_bob],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+
+            assert!(matches!(res, Err(Error::MultiplierNotFound)));
+        }
+
+        #[ink::test]
+        fn create_contract_with_duplicated_beneficiaries() {
+            let accounts = default_accounts();
+            let beneficiary_1 = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100)), (1, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let beneficiary_2 = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100)), (1, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![beneficiary_1, beneficiary_2],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+
+            assert!(matches!(res, Err(Error::DuplicatedBeneficiaries)));
+        }
+
+        /// Add a new beneficiary and check that it is added
+        #[ink::test]
+        fn add_beneficiary() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(200)), (1, percent(100))])
+                .unwrap();
+            assert_eq!(
+                contract
+                    .beneficiaries
+                    .get(accounts.bob)
+                    .unwrap()
+                    .multipliers,
+                vec_to_btreemap(&[(0, percent(200)), (1, percent(100))])
+            );
+            contract
+                .update_beneficiary(
+                    accounts.bob,
+                    vec![(0, percent(200)), (1, percent(50))],
+                    None,
+                )
+                .unwrap();
+            assert_eq!(
+                contract
+                    .beneficiaries
+                    .get(accounts.bob)
+                    .unwrap()
+                    .multipliers,
+                vec_to_btreemap(&[(0, percent(200)), (1, percent(50))])
+            );
+
+            // check if account was added to the vector
+            assert_eq!(
+                contract.beneficiaries_accounts.get(0).unwrap(),
+                &accounts.bob
+            );
+        }
+
+        /// Update a beneficiary after unclaimed periods, which are paid with the previous multipliers
+        #[ink::test]
+        fn update_beneficiary_after_unclaimed_periods() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            // two periods at 1030
+            advance_n_blocks(4);
+            contract
+                .update_beneficiary(accounts.bob, vec![(0, percent(200))], None)
+                .unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2 * 1030));
+
+            // the next period is paid with the new multipliers
+            advance_n_blocks(2);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(2 * 1030 + 2000)
+            );
+        }
+
+        /// Schedule the update of a beneficiary from a future period
+        #[ink::test]
+        fn update_beneficiary_from_future_period() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(1);
+
+            // the current and past periods can't be scheduled
+            assert_eq!(
+                contract.update_beneficiary(accounts.bob, vec![(0, percent(200))], Some(0)),
+                Err(Error::InvalidParams)
+            );
+            assert_eq!(
+                contract.update_beneficiary(accounts.bob, vec![(0, percent(200))], Some(u32::MAX)),
+                Err(Error::InvalidParams)
+            );
+            contract
+                .update_beneficiary(accounts.bob, vec![(0, percent(200))], Some(1))
+                .unwrap();
+            assert_eq!(
+                contract.get_scheduled_beneficiary_update(accounts.bob),
+                Ok(Some(ScheduledBeneficiaryUpdate {
+                    activation_block: 2,
+                    multipliers: vec_to_btreemap(&[(0, percent(200))]),
+                }))
+            );
+
+            // the current period keeps the previous multipliers
+            advance_n_blocks(1);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030 + 2000));
+
+            // claiming stores the update in the beneficiary
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030 + 2000).unwrap();
+            assert_eq!(
+                contract.get_scheduled_beneficiary_update(accounts.bob),
+                Ok(None)
+            );
+            assert_eq!(
+                contract.get_beneficiary(accounts.bob).unwrap().multipliers,
+                vec_to_btreemap(&[(0, percent(200))])
+            );
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2000));
+
+            // an update replaces the scheduled one
+            set_sender(accounts.alice);
+            contract
+                .update_beneficiary(accounts.bob, vec![(0, percent(300))], Some(10))
+                .unwrap();
+            contract
+                .update_beneficiary(accounts.bob, vec![(0, percent(100))], None)
+                .unwrap();
+            assert_eq!(
+                contract.get_scheduled_beneficiary_update(accounts.bob),
+                Ok(None)
+            );
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2000 + 1000));
+        }
+
+        /// Add several beneficiaries at once
+        #[ink::test]
+        fn bulk_add_beneficiaries() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .bulk_add_beneficiaries(vec![
+                    InitialBeneficiary {
+                        account_id: accounts.bob,
+                        multipliers: vec![(0, percent(100)), (1, percent(3))],
+                        start_block: None,
+                        name: None,
+                        notes: None,
+                    },
+                    InitialBeneficiary {
+                        account_id: accounts.charlie,
+                        multipliers: vec![(0, percent(200))],
+                        start_block: Some(4),
+                        name: None,
+                        notes: None,
+                    },
+                ])
+                .unwrap();
+
+            assert_eq!(
+                contract.get_list_beneficiaries(),
+                vec![accounts.bob, accounts.charlie]
+            );
+            let data_charlie = contract.beneficiaries.get(accounts.charlie).unwrap();
+            assert_eq!(
+                data_charlie.multipliers,
+                vec_to_btreemap(&[(0, percent(200))])
+            );
+            assert_eq!(data_charlie.start_block, Some(4));
+        }
+
+        /// Add several beneficiaries at once and fails because one of them is invalid
+        #[ink::test]
+        fn bulk_add_beneficiaries_with_invalid_entry() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.django, vec![(0, percent(100))])
+                .unwrap();
+
+            let bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100)), (1, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let with_invalid_multiplier = InitialBeneficiary {
+                account_id: accounts.charlie,
+                multipliers: vec![(0, percent(100)), (5, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            assert_eq!(
+                contract.bulk_add_beneficiaries(vec![bob.clone(), with_invalid_multiplier]),
+                Err(Error::MultiplierNotFound)
+            );
+            assert_eq!(
+                contract.bulk_add_beneficiaries(vec![bob.clone(), bob.clone()]),
+                Err(Error::DuplicatedBeneficiaries)
+            );
+            let existing = InitialBeneficiary {
+                account_id: accounts.django,
+                multipliers: vec![(0, percent(100))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            assert_eq!(
+                contract.bulk_add_beneficiaries(vec![bob, existing]),
+                Err(Error::AccountAlreadyExists)
+            );
+
+            // the contract is left unchanged
+            assert_eq!(contract.get_list_beneficiaries(), vec![accounts.django]);
+            assert!(!contract.is_beneficiary(accounts.bob));
+            assert!(!contract.is_beneficiary(accounts.charlie));
+        }
+
+        /// Add several beneficiaries at once and fails because one of them is the refund address
+        #[ink::test]
+        fn bulk_add_beneficiaries_with_refund_address() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                Some(accounts.frank),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            let beneficiaries = [accounts.bob, accounts.frank]
+                .iter()
+                .map(|account_id| InitialBeneficiary {
+                    account_id: *account_id,
+                    multipliers: vec![(0, percent(100))],
+                    start_block: None,
+                    name: None,
+                    notes: None,
+                })
+                .collect();
+            assert_eq!(
+                contract.bulk_add_beneficiaries(beneficiaries),
+                Err(Error::InvalidRoleAssignment)
+            );
+
+            // the valid entry before the refund address is not added either
+            assert_eq!(contract.get_list_beneficiaries(), vec![]);
+            assert!(!contract.is_beneficiary(accounts.bob));
+        }
+
+        /// Add several beneficiaries at once and fails because the maximum is exceeded
+        #[ink::test]
+        fn bulk_add_beneficiaries_over_max() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![],
+                1,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            let beneficiaries = [accounts.bob, accounts.charlie]
+                .iter()
+                .map(|account_id| InitialBeneficiary {
+                    account_id: *account_id,
+                    multipliers: vec![(0, percent(100))],
+                    start_block: None,
+                    name: None,
+                    notes: None,
+                })
+                .collect();
+            assert_eq!(
+                contract.bulk_add_beneficiaries(beneficiaries),
+                Err(Error::MaxBeneficiariesExceeded)
+            );
+            assert_eq!(contract.get_list_beneficiaries().len(), 0);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.bulk_add_beneficiaries(vec![]),
+                Err(Error::NotAdmin)
+            );
+        }
+
+        /// Add a new beneficiary and fails because the sender is not the owner
+        #[ink::test]
+        fn add_beneficiary_without_access() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            set_sender(accounts.bob);
+            assert!(matches!(
+                contract.add_beneficiary(accounts.bob, vec![(0, percent(100)), (1, percent(100))]),
+                Err(Error::NotAdmin)
+            ));
+            // check if account was NOT added to the vector
+            assert_eq!(contract.beneficiaries_accounts.len(), 0);
+        }
+
+        /// Add a new beneficiary and fails because the multiplies is 0
+        #[ink::test]
+        fn add_beneficiary_with_no_multipliers() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            assert!(matches!(
+                contract.add_beneficiary(accounts.bob, vec![]),
+                Ok(_)
+            ));
+        }
+
+        /// Add a new beneficiary and fails because a multiplier is over the maximum
+        #[ink::test]
+        fn add_beneficiary_with_multiplier_too_large() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            assert_eq!(
+                contract.add_beneficiary(
+                    accounts.bob,
+                    vec![(0, MultiplierValue::from_bps(MAX_MULTIPLIER.bps() + 1))]
+                ),
+                Err(Error::MultiplierValueTooLarge)
+            );
+            assert!(!contract.is_beneficiary(accounts.bob));
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, MAX_MULTIPLIER)])
+                .unwrap();
+            assert_eq!(
+                contract.update_beneficiary(
+                    accounts.bob,
+                    vec![(0, MultiplierValue::from_bps(MAX_MULTIPLIER.bps() + 1))],
+                    None
+                ),
+                Err(Error::MultiplierValueTooLarge)
+            );
+        }
+
+        /// Create a contract and fails because a multiplier is over the maximum
+        #[ink::test]
+        fn create_contract_with_multiplier_too_large() {
+            let accounts = default_accounts();
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, MultiplierValue::from_bps(MAX_MULTIPLIER.bps() + 1))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![beneficiary_bob],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+            assert!(matches!(res, Err(Error::MultiplierValueTooLarge)));
+        }
+
+        /// Remove a beneficiary and check that it is removed
+        #[ink::test]
+        fn remove_beneficiary() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100)), (1, percent(20))])
+                .unwrap();
+            assert_eq!(contract.beneficiaries_accounts.len(), 1);
+            assert_eq!(
+                contract.beneficiaries_accounts.get(0).unwrap(),
+                &accounts.bob
+            );
+            assert_eq!(
+                contract
+                    .beneficiaries
+                    .get(accounts.bob)
+                    .unwrap()
+                    .multipliers,
+                vec_to_btreemap(&[(0, percent(100)), (1, percent(20))])
+            );
+            contract.remove_beneficiary(accounts.bob, false).unwrap();
+            assert!(!contract.beneficiaries.contains(accounts.bob));
+            // check if account was removed from the vector
+            assert_eq!(contract.beneficiaries_accounts.len(), 0);
+        }
+
+        /// Remove a beneficiary and fails because the sender is not the owner
+        #[ink::test]
+        fn remove_beneficiary_without_access() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100)), (1, percent(20))])
+                .unwrap();
+            set_sender(accounts.bob);
+            assert!(matches!(
+                contract.remove_beneficiary(accounts.bob, false),
+                Err(Error::NotOwner)
+            ));
+            assert_eq!(contract.beneficiaries_accounts.len(), 1);
+            assert_eq!(
+                contract.beneficiaries_accounts.get(0).unwrap(),
+                &accounts.bob
+            );
+        }
+
+        /// Remove a beneficiary and fails because the beneficiary does not exist
+        #[ink::test]
+        fn remove_beneficiary_not_found() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            assert!(matches!(
+                contract.remove_beneficiary(accounts.bob, false),
+                Err(Error::AccountNotFound)
+            ));
+        }
+
+        /// Deactivate a beneficiary, claim the remainder and remove it
+        #[ink::test]
+        fn deactivate_claim_and_remove_beneficiary() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(3);
+
+            contract.deactivate_beneficiary(accounts.bob).unwrap();
+            assert_eq!(
+                contract.deactivate_beneficiary(accounts.bob),
+                Err(Error::BeneficiaryAlreadyDeactivated)
+            );
+
+            // nothing more is accrued
+            advance_n_blocks(4);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
+            assert_eq!(contract.get_amount_to_claim(accounts.charlie), Ok(3090));
+
+            // a deactivated beneficiary is only waited for until it claims the remainder
+            set_sender(accounts.charlie);
+            contract.claim_payment(accounts.charlie, 3090).unwrap();
+            assert!(!contract.is_all_claimed_in_period());
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+            advance_n_blocks(2);
+            set_sender(accounts.charlie);
+            contract.claim_payment(accounts.charlie, 1030).unwrap();
+            assert!(contract.is_all_claimed_in_period());
+
+            set_sender(accounts.alice);
+            contract.remove_beneficiary(accounts.bob, false).unwrap();
+            assert!(!contract.is_beneficiary(accounts.bob));
+        }
+
+        /// Removing a beneficiary pays what it earned
+        #[ink::test]
+        fn remove_beneficiary_settles_payments() {
+            let (accounts, mut contract) = create_accounts_and_contract(1000u128);
+            advance_n_blocks(3);
+
+            // the treasury must be funded first
+            assert_eq!(
+                contract.remove_beneficiary(accounts.bob, false),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
+            assert!(contract.is_beneficiary(accounts.bob));
+
+            set_balance(contract_id(), 100_000_000u128);
+            let bob_balance = get_balance(accounts.bob);
+            contract.remove_beneficiary(accounts.bob, false).unwrap();
+            assert!(!contract.is_beneficiary(accounts.bob));
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1030);
+            assert_eq!(get_balance(contract_id()), 100_000_000u128 - 1030);
+        }
+
+        /// Remove and settle a beneficiary with a funded and an underfunded treasury
+        #[ink::test]
+        fn remove_and_settle_beneficiary() {
+            let (accounts, mut contract) = create_accounts_and_contract(1500u128);
+            advance_n_blocks(2);
+            set_sender(accounts.charlie);
+            contract.claim_payment(accounts.charlie, 1000).unwrap();
+
+            // charlie is owed 30, bob 1030 and the treasury holds 500
+            set_sender(accounts.alice);
+            assert_eq!(
+                contract.remove_and_settle_beneficiary(accounts.bob),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
+            assert!(contract.is_beneficiary(accounts.bob));
+            assert_eq!(
+                contract.get_list_beneficiaries(),
+                vec![accounts.bob, accounts.charlie]
+            );
+
+            let charlie_balance = get_balance(accounts.charlie);
+            contract
+                .remove_and_settle_beneficiary(accounts.charlie)
+                .unwrap();
+            assert_eq!(get_balance(accounts.charlie), charlie_balance + 30);
+            assert_eq!(contract.get_list_beneficiaries(), vec![accounts.bob]);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.remove_and_settle_beneficiary(accounts.bob),
+                Err(Error::NotOwner)
+            );
+        }
+
+        /// Removing a beneficiary the treasury can't settle changes nothing
+        #[ink::test]
+        fn remove_beneficiary_underfunded_treasury_keeps_state() {
+            let (accounts, mut contract) = create_accounts_and_contract(1000u128);
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 500).unwrap();
+            advance_n_blocks(2);
+
+            // bob is owed 530 + 1030 and the treasury holds 500
+            set_sender(accounts.alice);
+            let bob = contract.get_beneficiary(accounts.bob).unwrap();
+            let claim_records = contract.get_claim_records(accounts.bob).unwrap();
+            let claims_in_period = contract.get_claims_in_period();
+            let bob_balance = get_balance(accounts.bob);
+            assert_eq!(
+                contract.remove_beneficiary(accounts.bob, false),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
+
+            assert_eq!(contract.get_beneficiary(accounts.bob), Ok(bob));
+            assert_eq!(contract.get_claim_records(accounts.bob), Ok(claim_records));
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(530 + 1030));
+            assert_eq!(
+                contract.get_list_beneficiaries(),
+                vec![accounts.bob, accounts.charlie]
+            );
+            assert_eq!(contract.get_claims_in_period(), claims_in_period);
+            assert_eq!(get_balance(accounts.bob), bob_balance);
+            assert_eq!(get_balance(contract_id()), 500);
+        }
+
+        /// Removing a beneficiary is blocked like a claim while its payments can't be paid, unless forced
+        #[ink::test]
+        fn remove_beneficiary_settlement_follows_claim_checks() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+
+            // while the contract is paused
+            contract.pause().unwrap();
+            assert_eq!(
+                contract.remove_beneficiary(accounts.bob, false),
+                Err(Error::BeneficiaryHasUnclaimedPayments)
+            );
+            contract.resume().unwrap();
+
+            // while the group of the beneficiary is paused
+            contract
+                .set_beneficiary_group(accounts.bob, Some(1))
+                .unwrap();
+            contract.pause_group(1).unwrap();
+            assert_eq!(
+                contract.remove_beneficiary(accounts.bob, false),
+                Err(Error::BeneficiaryHasUnclaimedPayments)
+            );
+            contract.resume_group(1).unwrap();
+
+            // while the beneficiary is suspended
+            contract.suspend_beneficiary(accounts.bob).unwrap();
+            assert_eq!(
+                contract.remove_beneficiary(accounts.bob, false),
+                Err(Error::BeneficiaryHasUnclaimedPayments)
+            );
+            contract.unsuspend_beneficiary(accounts.bob).unwrap();
+
+            // beyond the budget of the period
+            contract.set_period_budget(Some(1000)).unwrap();
+            assert_eq!(
+                contract.remove_beneficiary(accounts.bob, false),
+                Err(Error::PeriodBudgetExceeded)
+            );
+            assert!(contract.is_beneficiary(accounts.bob));
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
+
+            // the settlement counts in the budget of the period
+            contract.set_period_budget(Some(2000)).unwrap();
+            let bob_balance = get_balance(accounts.bob);
+            contract.remove_beneficiary(accounts.bob, false).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1030);
+            assert_eq!(contract.get_claims_in_period().total_amount, 1030);
+            assert_eq!(contract.get_claims_in_period().total_claims, 0);
+
+            // forcing the removal drops the payments, so nothing blocks it
+            contract.pause().unwrap();
+            let charlie_balance = get_balance(accounts.charlie);
+            contract.remove_beneficiary(accounts.charlie, true).unwrap();
+            assert!(!contract.is_beneficiary(accounts.charlie));
+            assert_eq!(get_balance(accounts.charlie), charlie_balance);
+        }
+
+        /// Remove a beneficiary with unclaimed payments by forcing it
+        #[ink::test]
+        fn force_remove_beneficiary() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+            let bob_balance = get_balance(accounts.bob);
+            contract.remove_beneficiary(accounts.bob, true).unwrap();
+            assert!(!contract.is_beneficiary(accounts.bob));
+            assert_eq!(get_balance(accounts.bob), bob_balance);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.deactivate_beneficiary(accounts.charlie),
+                Err(Error::NotOwner)
+            );
+        }
+
+        /// Check the amount to claim projected at future blocks
+        #[ink::test]
+        fn get_amount_to_claim_at_block() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(
+                contract.get_amount_to_claim_at_block(accounts.bob, 0),
+                Ok(0)
+            );
+            assert_eq!(
+                contract.get_amount_to_claim_at_block(accounts.bob, 3),
+                Ok(1030)
+            );
+            assert_eq!(
+                contract.get_amount_to_claim_at_block(accounts.bob, 4),
+                Ok(2060)
+            );
+            assert_eq!(
+                contract.get_amount_to_claim_at_block(accounts.bob, 20),
+                Ok(10 * 1030)
+            );
+
+            // the projection matches the amount once the block is reached
+            advance_n_blocks(5);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2060));
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 2000).unwrap();
+            assert_eq!(
+                contract.get_amount_to_claim_at_block(accounts.bob, 6),
+                Ok(1090)
+            );
+
+            // before the last claim or for an unknown account
+            assert_eq!(
+                contract.get_amount_to_claim_at_block(accounts.bob, 2),
+                Err(Error::InvalidParams)
+            );
+            assert_eq!(
+                contract.get_amount_to_claim_at_block(accounts.django, 8),
+                Err(Error::AccountNotFound)
+            );
+        }
+
+        /// Check the debts projected at a future block match the debts once the block is reached
+        #[ink::test]
+        fn get_total_debts_at_block() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(1);
+            assert_eq!(
+                contract.get_total_debts_at_block(0),
+                Err(Error::InvalidParams)
+            );
+            assert_eq!(contract.get_total_debts_at_block(1), Ok(0));
+
+            // Performance expires at block 2, only the first period is paid with it
+            contract.deactivate_multiplier(1).unwrap();
+            let bob_projection = contract
+                .get_amount_to_claim_at_block(accounts.bob, 6)
+                .unwrap();
+            assert_eq!(bob_projection, 1030 + 2 * 1000);
+            assert_eq!(contract.get_total_debts_at_block(6), Ok(2 * bob_projection));
+
+            advance_n_blocks(5);
+            assert_eq!(contract.get_total_debts(), 2 * bob_projection);
+            let bob_balance = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+            contract
+                .claim_payment(accounts.bob, bob_projection)
+                .unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + bob_projection);
+        }
+
+        /// A simulation matches the payments of a beneficiary with the same multipliers
+        #[ink::test]
+        fn simulate_salary() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            let multipliers = vec![(0, percent(120)), (1, percent(10))];
+
+            set_sender(accounts.django);
+            let simulated = contract.simulate_salary(multipliers.clone()).unwrap();
+            assert_eq!(simulated, 1300);
+            assert_eq!(contract.simulate_salary(vec![]), Ok(10));
+
+            set_sender(accounts.alice);
+            contract
+                .add_beneficiary(accounts.django, multipliers.clone())
+                .unwrap();
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.django), Ok(simulated));
+
+            // the multipliers are validated
+            assert_eq!(
+                contract.simulate_salary(vec![(5, percent(100))]),
+                Err(Error::MultiplierNotFound)
+            );
+            assert_eq!(
+                contract.simulate_salary(vec![(0, percent(100)), (0, percent(10))]),
+                Err(Error::DuplicatedMultipliers)
+            );
+            contract.deactivate_multiplier(1).unwrap();
+            assert_eq!(
+                contract.simulate_salary(multipliers),
+                Err(Error::MultiplierAlreadyDeactivated)
+            );
+        }
+
+        /// Check the number of full periods a beneficiary has not claimed
+        #[ink::test]
+        fn get_claimable_periods_count() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(contract.get_claimable_periods_count(accounts.bob), Ok(0));
+            advance_n_blocks(1);
+            assert_eq!(contract.get_claimable_periods_count(accounts.bob), Ok(0));
+            advance_n_blocks(1);
+            assert_eq!(contract.get_claimable_periods_count(accounts.bob), Ok(1));
+            advance_n_blocks(5);
+            assert_eq!(contract.get_claimable_periods_count(accounts.bob), Ok(3));
+
+            // a claim settles the periods up to the current one
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+            assert_eq!(contract.get_claimable_periods_count(accounts.bob), Ok(0));
+            assert_eq!(
+                contract.get_claimable_periods_count(accounts.charlie),
+                Ok(3)
+            );
+            assert_eq!(
+                contract.get_claimable_periods_count(accounts.django),
+                Err(Error::AccountNotFound)
+            );
+        }
+
+        /// Check who can read the data of a beneficiary in private and public mode
+        #[ink::test]
+        fn private_mode_read_access() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let mut contract = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![beneficiary_bob],
+                100,
+                10,
+                None,
+                0,
+                true,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert!(contract.is_private());
+            contract.add_admin(accounts.charlie).unwrap();
+            set_sender(accounts.bob);
+            contract.set_claim_delegate(accounts.django).unwrap();
+            advance_n_blocks(2);
+
+            // owner, admin, beneficiary and delegate
+            for caller in [
+                accounts.alice,
+                accounts.charlie,
+                accounts.bob,
+                accounts.django,
+            ] {
+                set_sender(caller);
+                assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1000));
+                assert_eq!(
+                    contract.get_amount_to_claim_at_block(accounts.bob, 4),
+                    Ok(2000)
+                );
+                assert!(contract.get_beneficiary(accounts.bob).is_ok());
+                assert_eq!(contract.get_claim_records(accounts.bob), Ok(vec![]));
+                assert_eq!(contract.was_period_paid(accounts.bob, 0), Ok(false));
+                assert_eq!(
+                    contract.get_claim_delegate(accounts.bob),
+                    Ok(Some(accounts.django))
+                );
+                assert_eq!(contract.get_bonus_history(accounts.bob), Ok(vec![]));
+            }
+
+            // anyone else
+            set_sender(accounts.eve);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                contract.get_amount_to_claim_at_block(accounts.bob, 4),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                contract.get_beneficiary(accounts.bob),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                contract.get_claim_records(accounts.bob),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                contract.was_period_paid(accounts.bob, 0),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                contract.get_claim_delegate(accounts.bob),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                contract.get_bonus_history(accounts.bob),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                contract.get_total_debt_breakdown(),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                contract.get_beneficiaries_page(0, 10),
+                Err(Error::NotAuthorized)
+            );
+            set_sender(accounts.charlie);
+            assert_eq!(
+                contract.get_total_debt_breakdown(),
+                Ok(vec![(accounts.bob, 1000)])
+            );
+            assert_eq!(contract.get_beneficiaries_page(0, 10).unwrap().len(), 1);
+            set_sender(accounts.eve);
+
+            // the aggregates stay public and the claims are unaffected
+            assert_eq!(contract.get_total_debts(), 1000);
+            assert_eq!(contract.get_count_of_unclaim_beneficiaries(), 1);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+
+            // in public mode anyone can read
+            let (accounts, contract) = create_accounts_and_contract(100_000_000u128);
+            assert!(!contract.is_private());
+            set_sender(accounts.eve);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+            assert!(contract.get_beneficiary(accounts.bob).is_ok());
+            assert_eq!(contract.get_claim_delegate(accounts.bob), Ok(None));
+        }
+
+        /// Check every read of the data of a beneficiary fails for an unknown account,
+        /// and that in private mode an unauthorized caller can't tell it is unknown
+        #[ink::test]
+        fn beneficiary_reads_of_unknown_account() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            for (private, caller, error) in [
+                (false, accounts.eve, Error::AccountNotFound),
+                (true, accounts.alice, Error::AccountNotFound),
+                (true, accounts.eve, Error::NotAuthorized),
+            ] {
+                contract.private = private;
+                set_sender(caller);
+                let frank = accounts.frank;
+                assert_eq!(contract.get_amount_to_claim(frank), Err(error.clone()));
+                assert_eq!(
+                    contract.get_amount_to_claim_at_block(frank, 4),
+                    Err(error.clone())
+                );
+                assert_eq!(contract.get_beneficiary(frank), Err(error.clone()));
+                assert_eq!(
+                    contract.get_claimable_periods_count(frank),
+                    Err(error.clone())
+                );
+                assert_eq!(contract.get_claim_records(frank), Err(error.clone()));
+                assert_eq!(contract.get_bonus_history(frank), Err(error.clone()));
+                assert_eq!(contract.get_claim_history(frank), Err(error.clone()));
+                assert_eq!(contract.get_total_claimed(frank), Err(error.clone()));
+                assert_eq!(contract.get_pruned_multipliers(frank), Err(error.clone()));
+                assert_eq!(
+                    contract.get_scheduled_beneficiary_update(frank),
+                    Err(error.clone())
+                );
+                assert_eq!(contract.was_period_paid(frank, 0), Err(error.clone()));
+                assert_eq!(contract.get_claim_delegate(frank), Err(error));
+            }
+        }
+
+        /// Check the read messages skip an account missing from the beneficiaries mapping
+        #[ink::test]
+        fn read_messages_skip_inconsistent_storage() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+            contract.beneficiaries.remove(accounts.bob);
+            assert_eq!(contract.beneficiaries_accounts.len(), 2);
+
+            assert_eq!(contract.get_total_debts(), 1030);
+            assert_eq!(contract.get_total_debt_for_next_period(), 1030);
+            assert_eq!(
+                contract.get_total_debt_with_unclaimed_for_next_period(),
+                2060
+            );
+            assert_eq!(
+                contract.get_unclaimed_beneficiaries(),
+                vec![accounts.charlie]
+            );
+            assert_eq!(contract.get_count_of_unclaim_beneficiaries(), 1);
+            assert_eq!(
+                contract.ensure_all_payments_uptodate(),
+                Err(Error::PaymentsNotUpToDate)
+            );
+        }
+
+        /// Check is_beneficiary for added, unknown and removed accounts
+        #[ink::test]
+        fn check_is_beneficiary() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            assert!(contract.is_beneficiary(accounts.bob));
+            assert!(contract.is_beneficiary(accounts.charlie));
+            assert!(!contract.is_beneficiary(accounts.django));
+
+            contract.remove_beneficiary(accounts.bob, false).unwrap();
+            assert!(!contract.is_beneficiary(accounts.bob));
+        }
+
+        /// Check active, deactivated, deleted and unknown multipliers
+        #[ink::test]
+        fn check_is_active_multiplier() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert!(contract.is_active_multiplier(0));
+            assert!(contract.is_active_multiplier(1));
+            assert!(!contract.is_active_multiplier(5));
+
+            contract.deactivate_multiplier(1).unwrap();
+            assert!(!contract.is_active_multiplier(1));
+
+            advance_n_blocks(2);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            contract.claim_payment(accounts.charlie, 0).unwrap();
+            contract.delete_unused_multiplier(1).unwrap();
+            assert!(!contract.is_active_multiplier(1));
+            assert!(contract.is_active_multiplier(0));
+        }
+
+        /// Update the base payment and check that it is updated
+        #[ink::test]
+        fn update_base_payment_in_initial_block() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract.update_base_payment(200_000_000u128).unwrap();
+            assert_eq!(contract.base_payment, 200_000_000u128);
+        }
+
+        /// Update the base payment and check that it is updated
+        #[ink::test]
+        fn update_base_payment() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            advance_n_blocks(1);
+
+            contract.update_base_payment(200_000_000u128).unwrap();
+            assert_eq!(contract.base_payment, 200_000_000u128);
+        }
+
+        /// Test the claims in period with a beneficiary added in the middle of the period
+        #[ink::test]
+        fn claims_in_period_with_beneficiary_added_mid_period() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert!(contract.is_all_claimed_in_period());
+
+            advance_n_blocks(2);
+            assert!(!contract.is_all_claimed_in_period());
+
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            // claiming twice in the period is counted once
+            contract.claim_payment(accounts.bob, 30).unwrap();
+            assert_eq!(
+                contract.get_claims_in_period(),
+                ClaimsInPeriod {
+                    period: 2,
+                    total_claims: 1,
+                    total_amount: 1030,
+                }
+            );
+            assert_eq!(
+                contract.get_claimed_accounts_in_period(),
+                vec![accounts.bob]
+            );
+            assert!(!contract.is_all_claimed_in_period());
+
+            // django has nothing to claim from previous periods
+            set_sender(accounts.alice);
+            contract
+                .add_beneficiary(accounts.django, vec![(0, percent(100)), (1, percent(3))])
+                .unwrap();
+            assert_eq!(
+                contract.update_base_payment(2000),
+                Err(Error::NotAllClaimedInPeriod)
+            );
+
+            set_sender(accounts.charlie);
+            contract.claim_payment(accounts.charlie, 1030).unwrap();
+            assert_eq!(
+                contract.get_claimed_accounts_in_period(),
+                vec![accounts.bob, accounts.charlie]
+            );
+            assert!(contract.is_all_claimed_in_period());
+            set_sender(accounts.alice);
+            contract.update_base_payment(2000).unwrap();
+
+            // in the next period everyone has to claim again, django too
+            advance_n_blocks(2);
+            assert_eq!(contract.get_claimed_accounts_in_period(), vec![]);
+            assert!(!contract.is_all_claimed_in_period());
+            for account_id in [accounts.bob, accounts.charlie] {
+                contract.claim_payment(account_id, 0).unwrap();
+            }
+            assert!(!contract.is_all_claimed_in_period());
+            contract.claim_payment(accounts.django, 0).unwrap();
+            assert!(contract.is_all_claimed_in_period());
+
+            // a removed beneficiary is not counted anymore
+            contract.remove_beneficiary(accounts.bob, true).unwrap();
+            assert_eq!(contract.get_claims_in_period().total_claims, 2);
+            assert!(contract.is_all_claimed_in_period());
+        }
+
+        /// Test removing or adding beneficiaries mid-period never blocks the parameter updates
+        #[ink::test]
+        fn all_claimed_in_period_after_beneficiary_changes() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            // in the first period every beneficiary is up to date
+            advance_n_blocks(1);
+            assert!(contract.is_all_claimed_in_period());
+            contract.update_base_payment(1000).unwrap();
+
+            // charlie is removed after bob claimed
+            advance_n_blocks(1);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            assert_eq!(
+                contract.update_base_payment(2000),
+                Err(Error::NotAllClaimedInPeriod)
+            );
+            contract
+                .remove_beneficiary(accounts.charlie, false)
+                .unwrap();
+            contract.update_base_payment(2000).unwrap();
+
+            // django is added mid-period once everyone claimed
+            contract
+                .add_beneficiary(accounts.django, vec![(0, percent(100))])
+                .unwrap();
+            assert!(contract.is_all_claimed_in_period());
+            contract.update_base_payment(3000).unwrap();
+            contract.update_periodicity(4).unwrap();
+        }
+
+        #[ink::test]
+        fn update_base_payment_error() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            advance_n_blocks(3);
+
+            assert!(matches!(
+                contract.update_base_payment(200_000_000u128),
+                Err(Error::NotAllClaimedInPeriod)
+            ));
+        }
+
+        /// Update the base payment but fails because the sender is not the owner
+        #[ink::test]
+        fn update_base_payment_without_access() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.bob);
+            assert!(matches!(
+                contract.update_base_payment(200_000_000u128),
+                Err(Error::NotOwner)
+            ));
+        }
+
+        /// Update the base payment but fails because the base payment is 0
+        #[ink::test]
+        fn update_base_payment_invalid_base_payment() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert!(matches!(
+                contract.update_base_payment(0u128),
+                Err(Error::InvalidParams)
+            ));
+        }
+
+        /// Create a contract but fails because the base payment is 0
+        #[ink::test]
+        fn create_contract_with_invalid_base_payment() {
+            let res = OpenPayroll::new(
+                2,
+                0,
+                vec!["Seniority".to_string()],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+            assert!(matches!(res, Err(Error::InvalidParams)));
+        }
+
+        fn set_timestamp(timestamp: u64) {
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(timestamp);
+        }
+
+        /// Periods of a time based contract land on multiples of the periodicity from the initial timestamp
+        #[ink::test]
+        fn time_based_periods() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+            set_timestamp(1_000_000);
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let mut contract = OpenPayroll::new_time_based(
+                60_000,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![beneficiary_bob],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert!(contract.is_time_based());
+            assert_eq!(contract.get_initial_block(), 1000);
+            assert_eq!(contract.get_periodicity(), 60);
+
+            // new blocks without enough time passing do not start a new period
+            advance_n_blocks(5);
+            assert_eq!(contract.get_current_period_initial_block(), 1000);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+
+            set_timestamp(1_150_000);
+            assert_eq!(contract.get_current_period_initial_block(), 1120);
+            assert_eq!(contract.get_next_block_period(), 1180);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2000));
+
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 2000).unwrap();
+            set_timestamp(1_180_000);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1000));
+
+            // the periodicity is updated in milliseconds too
+            set_sender(accounts.alice);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            assert_eq!(contract.update_periodicity(1500), Err(Error::InvalidParams));
+            contract.update_periodicity(120_000).unwrap();
+            assert_eq!(contract.get_periodicity(), 120);
+        }
+
+        /// Many periods elapsed between two interactions are accrued in one step in time based mode
+        #[ink::test]
+        fn time_based_long_gap() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+            set_timestamp(1_000_000);
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let mut contract = OpenPayroll::new_time_based(
+                60_000,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![beneficiary_bob],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(
+                contract.get_estimated_next_period_timestamp(),
+                Some(1_060_000)
+            );
+
+            // a single block after 10 periods without blocks
+            set_timestamp(1_000_000 + 10 * 60_000 + 30_000);
+            advance_n_blocks(1);
+            assert_eq!(contract.get_current_period_initial_block(), 1600);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(10_000));
+            assert_eq!(
+                contract.get_estimated_next_period_timestamp(),
+                Some(1_660_000)
+            );
+
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 10_000).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+        }
+
+        /// Many periods elapsed between two interactions are accrued in one step in block mode,
+        /// and the next period start is estimated with the target block time
+        #[ink::test]
+        fn block_based_long_gap() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(contract.get_estimated_next_period_timestamp(), None);
+            contract.set_target_block_time(12_000).unwrap();
+            assert_eq!(contract.get_target_block_time(), 12_000);
+
+            advance_n_blocks(21);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(10 * 1030));
+            let now = ink::env::block_timestamp::<ink::env::DefaultEnvironment>();
+            assert_eq!(
+                contract.get_estimated_next_period_timestamp(),
+                Some(now + 12_000)
+            );
+
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 10 * 1030).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+            assert_eq!(contract.set_target_block_time(6000), Err(Error::NotOwner));
+        }
+
+        /// A time based contract needs a periodicity in whole seconds
+        #[ink::test]
+        fn time_based_with_invalid_periodicity() {
+            let res = OpenPayroll::new_time_based(
+                1500,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+            assert!(matches!(res, Err(Error::InvalidParams)));
+        }
+
+        /// Update the periodicity and check that it is updated
+        #[ink::test]
+        fn update_periodicity() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract.update_periodicity(100u32).unwrap();
+            assert_eq!(contract.periodicity, 100u32);
+        }
+
+        /// Update the periodicity but fails because the sender is not the owner
+        #[ink::test]
+        fn update_periodicity_without_access() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.bob);
+            assert!(matches!(
+                contract.update_periodicity(100u32),
+                Err(Error::NotOwner)
+            ));
+        }
+
+        /// Update the periodicity but fails because the periodicity is 0
+        #[ink::test]
+        fn update_periodicity_invalid_periodicity() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            assert!(matches!(
+                contract.update_periodicity(0u32),
+                Err(Error::InvalidParams)
+            ));
+        }
+
+        /// Test pausing and unpausing the contract
+        #[ink::test]
+        fn pause_and_resume() {
+            let starting_block = get_current_block();
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            contract.pause().unwrap();
+            assert!(contract.is_paused());
+            advance_n_blocks(1);
+            contract.resume().unwrap();
+            assert!(!contract.is_paused());
+            // check for the starting block to be the same
+            assert_eq!(contract.initial_block, starting_block);
+        }
+
+        /// Test pausing for a number of blocks
+        #[ink::test]
+        fn pause_for() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(contract.get_paused_at(), None);
+            assert_eq!(contract.pause_for(0), Err(Error::InvalidParams));
+
+            advance_n_blocks(1);
+            contract.pause_for(3).unwrap();
+            assert!(contract.is_paused());
+            assert_eq!(contract.get_paused_at(), Some(1));
+            assert_eq!(contract.get_resume_at(), Some(4));
+
+            // still paused the block before the resume block
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 1030),
+                Err(Error::ContractIsPaused)
+            );
+
+            // active again exactly at the resume block
+            advance_n_blocks(1);
+            assert!(!contract.is_paused());
+            assert_eq!(contract.get_paused_at(), None);
+            assert_eq!(contract.get_resume_at(), None);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+
+            // an explicit resume ends the pause before the deadline
+            set_sender(accounts.alice);
+            contract.pause_for(10).unwrap();
+            assert_eq!(contract.get_paused_at(), Some(4));
+            contract.resume().unwrap();
+            assert!(!contract.is_paused());
+
+            // a pause without duration replaces the deadline
+            contract.pause_for(2).unwrap();
+            contract.pause().unwrap();
+            advance_n_blocks(5);
+            assert!(contract.is_paused());
+            assert_eq!(contract.get_resume_at(), None);
+
+            set_sender(accounts.bob);
+            assert_eq!(contract.pause_for(2), Err(Error::NotAdmin));
+        }
+
+        /// Test the lifecycle state follows the pauses and the migrations
+        #[ink::test]
+        fn lifecycle_state() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(contract.get_lifecycle_state(), LifecycleState::Active);
+
+            advance_n_blocks(2);
+            contract.pause().unwrap();
+            assert_eq!(
+                contract.get_lifecycle_state(),
+                LifecycleState::Paused {
+                    since: 2,
+                    resume_at: None
+                }
+            );
+            contract.pause_for(3).unwrap();
+            assert_eq!(
+                contract.get_lifecycle_state(),
+                LifecycleState::Paused {
+                    since: 2,
+                    resume_at: Some(5)
+                }
+            );
+
+            // a pending migration is reported once the pause ends
+            contract.contract_version = CONTRACT_VERSION - 1;
+            advance_n_blocks(3);
+            assert_eq!(
+                contract.get_lifecycle_state(),
+                LifecycleState::MigrationInProgress {
+                    from_version: CONTRACT_VERSION - 1
+                }
+            );
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+
+            set_sender(accounts.alice);
+            contract.migrate().unwrap();
+            assert_eq!(contract.get_lifecycle_state(), LifecycleState::Active);
+        }
+
+        /// Test the pause history and the total paused blocks across pauses
+        #[ink::test]
+        fn pause_history() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(contract.get_pause_history(), vec![]);
+            assert_eq!(contract.get_total_paused_blocks(), 0);
+
+            // a pause is recorded when resumed, the total includes the current pause
+            advance_n_blocks(2);
+            contract.pause().unwrap();
+            advance_n_blocks(3);
+            assert_eq!(contract.get_pause_history(), vec![]);
+            assert_eq!(contract.get_total_paused_blocks(), 3);
+            contract.resume().unwrap();
+            assert_eq!(contract.get_pause_history(), vec![(2, 5)]);
+            assert_eq!(contract.get_total_paused_blocks(), 3);
+
+            // a pause with a duration ends at its resume block
+            advance_n_blocks(2);
+            contract.pause_for(4).unwrap();
+            advance_n_blocks(6);
+            assert_eq!(contract.get_pause_history(), vec![(2, 5), (7, 11)]);
+            assert_eq!(contract.get_total_paused_blocks(), 7);
+
+            // and stays recorded after the next pause
+            contract.pause().unwrap();
+            advance_n_blocks(2);
+            assert_eq!(contract.get_pause_history(), vec![(2, 5), (7, 11)]);
+            assert_eq!(contract.get_total_paused_blocks(), 9);
+            contract.resume().unwrap();
+            assert_eq!(
+                contract.get_pause_history(),
+                vec![(2, 5), (7, 11), (13, 15)]
+            );
+
+            // older pauses are evicted, the total is kept
+            for _ in 0..PAUSE_HISTORY_CAPACITY {
+                contract.pause().unwrap();
+                advance_n_blocks(1);
+                contract.resume().unwrap();
+            }
+            let history = contract.get_pause_history();
+            assert_eq!(history.len(), PAUSE_HISTORY_CAPACITY);
+            assert_eq!(history[0], (15, 16));
+            assert_eq!(
+                contract.get_total_paused_blocks(),
+                9 + PAUSE_HISTORY_CAPACITY as u32
+            );
+        }
+
+        /// Test pausing and resuming without access
+        #[ink::test]
+        fn pause_and_resume_without_access() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.bob);
+            assert!(matches!(contract.pause(), Err(Error::NotAdmin)));
+            assert!(matches!(contract.resume(), Err(Error::NotAdmin)));
+        }
+
+        /// Test an admin can pause and resume but cannot transfer ownership nor manage admins
+        #[ink::test]
+        fn admin_can_pause_but_not_transfer_ownership() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            assert!(contract.is_admin(accounts.alice));
+            assert!(!contract.is_admin(accounts.django));
+
+            contract.add_admin(accounts.django).unwrap();
+            assert!(contract.is_admin(accounts.django));
+            assert!(matches!(
+                contract.add_admin(accounts.django),
+                Err(Error::AccountAlreadyExists)
+            ));
+
+            set_sender(accounts.django);
+            contract.pause().unwrap();
+            assert!(contract.is_paused());
+            contract.resume().unwrap();
+            assert!(!contract.is_paused());
+            contract.add_beneficiary(accounts.eve, vec![]).unwrap();
+
+            assert!(matches!(
+                contract.propose_transfer_ownership(accounts.django),
+                Err(Error::NotOwner)
+            ));
+            assert!(matches!(
+                contract.add_admin(accounts.frank),
+                Err(Error::NotOwner)
+            ));
+            assert!(matches!(
+                contract.remove_admin(accounts.django),
+                Err(Error::NotOwner)
+            ));
+
+            // once removed the account cannot pause anymore
+            set_sender(accounts.alice);
+            contract.remove_admin(accounts.django).unwrap();
+            assert!(!contract.is_admin(accounts.django));
+            set_sender(accounts.django);
+            assert!(matches!(contract.pause(), Err(Error::NotAdmin)));
+        }
+
+        /// Test claiming a payment
+        #[ink::test]
+        fn claim_payment() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract
+                .update_beneficiary(
+                    accounts.bob,
+                    vec![(0, percent(100)), (1, percent(20))],
+                    None,
+                )
+                .unwrap();
+            // advance 3 blocks so a payment will be claimable
+            advance_n_blocks(3);
+
+            let contract_balance_before_payment = get_balance(contract.owner.unwrap());
+            let bob_balance_before_payment = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+
+            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            contract
+                .claim_payment(accounts.bob, amount_to_claim)
+                .unwrap();
+            assert!(get_balance(contract.owner.unwrap()) < contract_balance_before_payment);
+            assert!(get_balance(accounts.bob) > bob_balance_before_payment);
+        }
+
+        /// Test the owner pushing a payment to a beneficiary
+        #[ink::test]
+        fn push_payment() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+
+            set_sender(accounts.bob);
+            assert!(matches!(
+                contract.push_payment(accounts.bob),
+                Err(Error::NotOwner)
+            ));
+
+            set_sender(accounts.alice);
+            let bob_balance_before_payment = get_balance(accounts.bob);
+            assert_eq!(contract.push_payment(accounts.bob).unwrap(), 1030);
+            assert_eq!(get_balance(accounts.bob), bob_balance_before_payment + 1030);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob).unwrap(), 0);
+            assert_eq!(contract.get_total_claimed(accounts.bob).unwrap(), 1030);
+            assert_eq!(
+                contract.push_payment(accounts.django),
+                Err(Error::AccountNotFound)
+            );
+        }
+
+        /// Test claiming everything available in a single call
+        #[ink::test]
+        fn claim_all() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            let bob_balance = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+
+            // nothing to claim yet
+            assert_eq!(contract.claim_all(accounts.bob), Ok(0));
+            assert_eq!(get_balance(accounts.bob), bob_balance);
+
+            advance_n_blocks(4);
+            let claimed = contract.claim_all(accounts.bob).unwrap();
+            assert_eq!(claimed, 2 * 1030);
+            assert_eq!(get_balance(accounts.bob), bob_balance + claimed);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+
+            // the claim rules still apply
+            advance_n_blocks(2);
+            assert_eq!(
+                contract.claim_all(accounts.django),
+                Err(Error::AccountNotFound)
+            );
+            set_sender(accounts.alice);
+            contract.pause().unwrap();
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_all(accounts.bob),
+                Err(Error::ContractIsPaused)
+            );
+            set_sender(accounts.alice);
+            contract.resume().unwrap();
+            set_balance(contract_id(), 1000);
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_all(accounts.bob),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
+        }
+
+        /// Test claiming everything but an amount left unclaimed
+        #[ink::test]
+        fn claim_leaving() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+            let bob_balance = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_leaving(accounts.django, 0),
+                Err(Error::AccountNotFound)
+            );
+            assert_eq!(
+                contract.claim_leaving(accounts.bob, 1031),
+                Err(Error::NotEnoughToLeaveUnclaimed)
+            );
+
+            // leaving everything claims nothing
+            contract.claim_leaving(accounts.bob, 1030).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
+
+            contract.claim_leaving(accounts.bob, 500).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + 530);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(500));
+
+            // after the period rollover the new period is claimed too
+            advance_n_blocks(2);
+            contract.claim_leaving(accounts.bob, 500).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + 530 + 1030);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(500));
+
+            // the claim rules still apply
+            set_sender(accounts.django);
+            assert_eq!(
+                contract.claim_leaving(accounts.bob, 0),
+                Err(Error::NotAllowedToClaim)
+            );
+        }
+
+        /// Test claiming a payment
+        #[ink::test]
+        fn claim_parcial_payment() {
+            let total_amount = 100_000_000u128;
+            let total_not_claimed = 10;
+            let (accounts, mut contract) = create_accounts_and_contract(total_amount);
+            contract
+                .update_beneficiary(
+                    accounts.bob,
+                    vec![(0, percent(100)), (1, percent(20))],
+                    None,
+                )
+                .unwrap();
+
+            // advance 3 blocks so a payment will be claimable
+            advance_n_blocks(3);
+
+            let bob_balance_before_payment = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+
+            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            contract
+                .claim_payment(accounts.bob, amount_to_claim - total_not_claimed)
+                .unwrap();
+            assert!(
+                get_balance(contract.owner.unwrap())
+                    == total_amount - amount_to_claim + total_not_claimed
+            );
+            assert!(
+                get_balance(accounts.bob)
+                    == bob_balance_before_payment + amount_to_claim - total_not_claimed
+            );
+            assert!(
+                contract
+                    .beneficiaries
+                    .get(accounts.bob)
+                    .unwrap()
+                    .unclaimed_payments
+                    == total_not_claimed
+            );
+        }
+
+        /// Test claiming a payment
+        #[ink::test]
+        fn claim_more_payment() {
+            let total_amount = 100_000_000u128;
+            let (accounts, mut contract) = create_accounts_and_contract(total_amount);
+            contract
+                .update_beneficiary(
+                    accounts.bob,
+                    vec![(0, percent(100)), (1, percent(20))],
+                    None,
+                )
+                .unwrap();
+
+            // advance 3 blocks so a payment will be claimable
+            advance_n_blocks(3);
+
+            let bob_balance_before_payment = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+
+            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            let res = contract.claim_payment(accounts.bob, amount_to_claim + 1);
+
+            assert!(matches!(
+                res,
+                Err(Error::ClaimedAmountIsBiggerThanAvailable)
+            ));
+            assert!(get_balance(contract.owner.unwrap()) == total_amount);
+            assert!(get_balance(accounts.bob) == bob_balance_before_payment);
+        }
+
+        /// Error when trying to update periodicity with some payments not claimed
+        #[ink::test]
+        fn update_periodicity_without_all_payments_updated() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract
+                .update_beneficiary(
+                    accounts.bob,
+                    vec![(0, percent(100)), (1, percent(20))],
+                    None,
+                )
+                .unwrap();
+
+            // advance 3 blocks so a payment will be claimable
+            advance_n_blocks(3);
+
+            let res = contract.update_periodicity(10u32);
+            assert!(matches!(res, Err(Error::NotAllClaimedInPeriod)));
+        }
+
+        ///  update periodicity with all payments claimed with the param amount in 0 in the claim_payment
+        #[ink::test]
+        fn update_periodicity_with_all_payments_updated() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100)), (1, percent(20))])
+                .unwrap();
+            // advance 3 blocks so a payment will be claimable
+            advance_n_blocks(3);
+
+            // When you claim a payment with 0 amount, it will calculate the amount to claim an set it to unclaim payments.
+            contract.claim_payment(accounts.bob, 0).unwrap();
+
+            let res = contract.update_periodicity(10u32);
+
+            assert!(matches!(res, Ok(())));
+        }
+
+        /// update periodicity with all payments claimed
+        #[ink::test]
+        fn update_periodicity_with_all_payments_claimed() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100)), (1, percent(20))])
+                .unwrap();
+            // advance 3 blocks so a payment will be claimable
+            advance_n_blocks(3);
+
+            set_sender(accounts.bob);
+
+            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            contract
+                .claim_payment(accounts.bob, amount_to_claim)
+                .unwrap();
+
+            set_sender(accounts.alice);
+            let res = contract.update_periodicity(10u32);
+
+            assert_eq!(res, Ok(()));
+        }
+
+        /// test if error when trying to update base payment with some payments not claimed
+        #[ink::test]
+        fn update_base_payment_without_all_payments_updated() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100)), (1, percent(20))])
+                .unwrap();
+            // advance 3 blocks so a payment will be claimable
+            advance_n_blocks(3);
+
+            let res = contract.update_base_payment(900);
+
+            assert!(matches!(res, Err(Error::NotAllClaimedInPeriod)));
+        }
+
+        /// test if you can update a base payment with all payments claimed
+        #[ink::test]
+        fn update_base_payment_with_all_payments_claimed() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100)), (1, percent(20))])
+                .unwrap();
+            // advance 3 blocks so a payment will be claimable
+            advance_n_blocks(3);
+
+            set_sender(accounts.bob);
+            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            contract
+                .claim_payment(accounts.bob, amount_to_claim)
+                .unwrap();
+
+            set_sender(accounts.alice);
+            let res = contract.update_base_payment(900);
+
+            assert_eq!(res, Ok(()));
+        }
+
+        // test if beneficiaries are ok in the contract
+        #[ink::test]
+        fn create_contract_with_beneficiaries_ok() {
+            let (accounts, contract) = create_accounts_and_contract(100_000_000u128);
+
+            assert_eq!(contract.beneficiaries_accounts.len(), 2);
+            assert!(contract.beneficiaries.contains(accounts.bob));
+            assert!(contract.beneficiaries.contains(accounts.charlie));
+        }
+
+        // check for beneficiaries after updating it
+        #[ink::test]
+        fn update_benefiaries_created_in_create_contract() {
+            let total_balance = 100_000_000u128;
+            let (accounts, mut contract) = create_accounts_and_contract(total_balance);
+
+            contract
+                .update_beneficiary(
+                    accounts.bob,
+                    vec![(0, percent(100)), (1, percent(20))],
+                    None,
+                )
+                .unwrap();
+
+            //check if multipliers are ok
+            assert_eq!(
+                contract
+                    .beneficiaries
+                    .get(accounts.bob)
+                    .unwrap()
+                    .multipliers,
+                vec_to_btreemap(&[(0, percent(100)), (1, percent(20))])
+            );
+            assert_eq!(
+                contract
+                    .beneficiaries
+                    .get(accounts.charlie)
+                    .unwrap()
+                    .multipliers,
+                vec_to_btreemap(&[(0, percent(100)), (1, percent(3))])
+            );
+        }
+
+        // check a beneficiary moving to a new address keeps all its data
+        #[ink::test]
+        fn update_my_address() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            contract.set_claim_delegate(accounts.eve).unwrap();
+            advance_n_blocks(2);
+            let before = contract.get_beneficiary(accounts.bob).unwrap();
+
+            assert_eq!(
+                contract.update_my_address(accounts.charlie),
+                Err(Error::AccountAlreadyExists)
+            );
+            assert_eq!(
+                contract.update_my_address(contract_id()),
+                Err(Error::InvalidRoleAssignment)
+            );
+            set_sender(accounts.django);
+            assert_eq!(
+                contract.update_my_address(accounts.frank),
+                Err(Error::AccountNotFound)
+            );
+
+            set_sender(accounts.bob);
+            contract.update_my_address(accounts.django).unwrap();
+            assert_eq!(
+                contract.get_beneficiary(accounts.bob),
+                Err(Error::AccountNotFound)
+            );
+            let after = contract.get_beneficiary(accounts.django).unwrap();
+            assert_eq!(after.account_id, accounts.django);
+            assert_eq!(after.multipliers, before.multipliers);
+            assert_eq!(after.unclaimed_payments, before.unclaimed_payments);
+            assert_eq!(
+                after.last_updated_period_block,
+                before.last_updated_period_block
+            );
+            assert_eq!(
+                contract.get_list_beneficiaries(),
+                vec![accounts.django, accounts.charlie]
+            );
+            assert_eq!(contract.get_total_claimed(accounts.django), Ok(1000));
+            assert_eq!(
+                contract.get_claim_history(accounts.django),
+                Ok(vec![(2, 1000)])
+            );
+            assert_eq!(contract.get_claim_delegate(accounts.django), Ok(None));
+            assert_eq!(
+                contract.get_claim_delegate(accounts.bob),
+                Err(Error::AccountNotFound)
+            );
+
+            // the new address claims what the old one was owed
+            assert_eq!(contract.get_amount_to_claim(accounts.django), Ok(1060));
+            set_sender(accounts.django);
+            contract.claim_payment(accounts.django, 1060).unwrap();
+            assert_eq!(contract.get_total_claimed(accounts.django), Ok(2060));
+        }
+
+        // check the metadata of a beneficiary survives multiplier updates and claims
+        #[ink::test]
+        fn beneficiary_metadata() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            let name = Some("Django".to_string());
+            let notes = Some("Backend team".to_string());
+            let too_long = Some("x".repeat(MAX_BENEFICIARY_METADATA_LENGTH + 1));
+            assert_eq!(
+                contract.add_beneficiary_with_metadata(
+                    accounts.django,
+                    vec![(0, percent(100))],
+                    too_long.clone(),
+                    None
+                ),
+                Err(Error::InvalidParams)
+            );
+            contract
+                .add_beneficiary_with_metadata(
+                    accounts.django,
+                    vec![(0, percent(100))],
+                    name.clone(),
+                    None,
+                )
+                .unwrap();
+            let beneficiary = contract.get_beneficiary(accounts.django).unwrap();
+            assert_eq!((beneficiary.name, beneficiary.notes), (name.clone(), None));
+
+            // only the metadata is updated
+            assert_eq!(
+                contract.update_beneficiary_metadata(accounts.django, name.clone(), too_long),
+                Err(Error::InvalidParams)
+            );
+            assert_eq!(
+                contract.update_beneficiary_metadata(accounts.eve, None, None),
+                Err(Error::AccountNotFound)
+            );
+            advance_n_blocks(2);
+            contract
+                .update_beneficiary_metadata(accounts.django, name.clone(), notes.clone())
+                .unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.django), Ok(1000));
+
+            // it is kept by multiplier updates and claims
+            contract
+                .update_beneficiary(accounts.django, vec![(0, percent(200))], None)
+                .unwrap();
+            advance_n_blocks(2);
+            set_sender(accounts.django);
+            contract.claim_payment(accounts.django, 3000).unwrap();
+            let beneficiary = contract.get_beneficiary(accounts.django).unwrap();
+            assert_eq!((beneficiary.name, beneficiary.notes), (name, notes));
+
+            assert_eq!(
+                contract.update_beneficiary_metadata(accounts.django, None, None),
+                Err(Error::NotOwner)
+            );
+        }
+
+        // Delete a multiplier
+        #[ink::test]
+        fn check_deactivate_multiplier() {
+            let total_balance = 100_000_000u128;
+            let (_, mut contract) = create_accounts_and_contract(total_balance);
+
+            advance_n_blocks(6);
+
+            let res = contract.deactivate_multiplier(1);
+
+            advance_n_blocks(5);
+
+            assert_eq!(res, Ok(()));
+
+            let multiplier_0 = contract.base_multipliers.get(0).unwrap();
+            let multiplier_1 = contract.base_multipliers.get(1).unwrap();
+            assert_eq!(multiplier_1.valid_until_block.unwrap(), 8);
+            assert_eq!(multiplier_0.valid_until_block, None);
+        }
+
+        // Creates the default contract with unclaimed payments expiring after the given periods
+        fn create_contract_with_expiry(
+            accounts: &DefaultAccounts<DefaultEnvironment>,
+            unclaimed_expiry_periods: Option<u32>,
+        ) -> Result<OpenPayroll, Error> {
+            OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![InitialBeneficiary {
+                    account_id: accounts.bob,
+                    multipliers: vec![(0, percent(100)), (1, percent(3))],
+                    start_block: None,
+                    name: None,
+                    notes: None,
+                }],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                unclaimed_expiry_periods,
+                None,
+                None,
+            )
+        }
+
+        /// Check the owner given to the constructor replaces the caller, which keeps no rights
+        #[ink::test]
+        fn create_contract_with_explicit_owner() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let new_with_owner = |owner| {
+                OpenPayroll::new(
+                    2,
+                    1000,
+                    vec!["Seniority".to_string()],
+                    vec![],
+                    100,
+                    10,
+                    None,
+                    0,
+                    false,
+                    None,
+                    None,
+                    Some(owner),
+                    None,
+                )
+            };
+            assert_eq!(
+                new_with_owner(AccountId::from([0u8; 32])).err(),
+                Some(Error::InvalidRoleAssignment)
+            );
+            assert_eq!(
+                new_with_owner(contract_id()).err(),
+                Some(Error::InvalidRoleAssignment)
+            );
+
+            let mut contract = new_with_owner(accounts.django).unwrap();
+            assert_eq!(contract.get_owner(), Some(accounts.django));
+
+            // the deployer can not administer the instance
+            assert!(!contract.is_admin(accounts.alice));
+            assert_eq!(contract.update_periodicity(4), Err(Error::NotOwner));
+            assert_eq!(contract.add_admin(accounts.eve), Err(Error::NotOwner));
+            assert_eq!(contract.pause(), Err(Error::NotAdmin));
+
+            set_sender(accounts.django);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100))])
+                .unwrap();
+            contract.pause().unwrap();
+        }
+
+        /// Unclaimed payments expire once more periods than the expiry have elapsed since the last update
+        #[ink::test]
+        fn unclaimed_payments_expire() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+            assert_eq!(
+                create_contract_with_expiry(&accounts, Some(0)).err(),
+                Some(Error::ExpiryPeriodsCannotBeZero)
+            );
+            let mut contract = create_contract_with_expiry(&accounts, Some(2)).unwrap();
+            assert_eq!(contract.get_unclaimed_expiry_periods(), Some(2));
+
+            // bob leaves 1000 unclaimed in the period starting at block 2
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 30).unwrap();
+
+            // exactly 2 periods later they are still owed
+            advance_n_blocks(4);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(1000 + 2 * 1030)
+            );
+
+            // one period later they expire, the periods since the last update are still owed
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(3 * 1030));
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 1000 + 3 * 1030),
+                Err(Error::ClaimedAmountIsBiggerThanAvailable)
+            );
+            let bob_balance = get_balance(accounts.bob);
+            contract.claim_payment(accounts.bob, 3 * 1030).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + 3 * 1030);
+            assert_eq!(
+                contract
+                    .get_beneficiary(accounts.bob)
+                    .unwrap()
+                    .unclaimed_payments,
+                0
+            );
+        }
+
+        /// The unclaimed payments do not expire while the beneficiary is suspended
+        #[ink::test]
+        fn unclaimed_payments_do_not_expire_while_suspended() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+            let mut contract = create_contract_with_expiry(&accounts, Some(1)).unwrap();
+
+            // bob leaves 1000 unclaimed and is suspended in the next period
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 30).unwrap();
+            advance_n_blocks(2);
+            set_sender(accounts.alice);
+            contract.suspend_beneficiary(accounts.bob).unwrap();
+
+            // well past the expiry the snapshot is still owed, even after a claim of zero
+            advance_n_blocks(10);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1000 + 1030));
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1000 + 1030));
+
+            // once unsuspended the snapshot can be claimed
+            set_sender(accounts.alice);
+            contract.unsuspend_beneficiary(accounts.bob).unwrap();
+            set_sender(accounts.bob);
+            let bob_balance = get_balance(accounts.bob);
+            contract.claim_payment(accounts.bob, 1000 + 1030).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000 + 1030);
+        }
+
+        /// The unclaimed payments that expired before a suspension are reported and not kept in the snapshot
+        #[ink::test]
+        fn unclaimed_payments_expire_before_suspension() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+            let mut contract = create_contract_with_expiry(&accounts, Some(1)).unwrap();
+
+            // bob leaves 1000 unclaimed, which expires two periods later
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 30).unwrap();
+            advance_n_blocks(4);
+
+            // BeneficiaryPaymentExpired and BeneficiarySuspended are emitted
+            set_sender(accounts.alice);
+            let events_before = ink::env::test::recorded_events().count();
+            contract.suspend_beneficiary(accounts.bob).unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2 * 1030));
+
+            // nothing is left to expire once unsuspended
+            contract.unsuspend_beneficiary(accounts.bob).unwrap();
+            let events_before = ink::env::test::recorded_events().count();
+            contract.suspend_beneficiary(accounts.bob).unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 1);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2 * 1030));
+        }
+
+        /// The blocks the contract or the group of the beneficiary was paused are not counted for the expiry
+        #[ink::test]
+        fn unclaimed_payments_do_not_expire_while_paused() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+            let mut contract = create_contract_with_expiry(&accounts, Some(1)).unwrap();
+
+            // bob leaves 1000 unclaimed in the period starting at block 2
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 30).unwrap();
+
+            // the contract is paused for 4 blocks, only 1 unpaused period elapses
+            set_sender(accounts.alice);
+            contract.pause().unwrap();
+            advance_n_blocks(4);
+            contract.resume().unwrap();
+            advance_n_blocks(2);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(1000 + 3 * 1030)
+            );
+
+            // the blocks the group of bob is paused are not counted either
+            contract
+                .set_beneficiary_group(accounts.bob, Some(0))
+                .unwrap();
+            contract.pause_group(0).unwrap();
+            advance_n_blocks(10);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(1000 + 8 * 1030)
+            );
+            contract.resume_group(0).unwrap();
+
+            // once the group is resumed the snapshot can be claimed
+            set_sender(accounts.bob);
+            let bob_balance = get_balance(accounts.bob);
+            contract
+                .claim_payment(accounts.bob, 1000 + 8 * 1030)
+                .unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + 1000 + 8 * 1030);
+        }
+
+        /// The paused blocks keep delaying the expiry once their pauses are evicted from the histories
+        #[ink::test]
+        fn unclaimed_payments_expiry_does_not_move_after_many_pauses() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+            let mut contract = create_contract_with_expiry(&accounts, Some(1)).unwrap();
+
+            // bob leaves 1000 unclaimed in the period starting at block 2
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 30).unwrap();
+
+            // more pauses than the pause history keeps, 50 blocks paused in total
+            set_sender(accounts.alice);
+            for _ in 0..PAUSE_HISTORY_CAPACITY + 5 {
+                contract.pause().unwrap();
+                advance_n_blocks(2);
+                contract.resume().unwrap();
+            }
+            advance_n_blocks(2);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(1000 + 26 * 1030)
+            );
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(27 * 1030));
+
+            // the same for the pauses of the group of bob
+            set_sender(accounts.bob);
+            contract
+                .claim_payment(accounts.bob, 27 * 1030 - 1000)
+                .unwrap();
+            set_sender(accounts.alice);
+            contract
+                .set_beneficiary_group(accounts.bob, Some(0))
+                .unwrap();
+            for _ in 0..PAUSE_HISTORY_CAPACITY + 5 {
+                contract.pause_group(0).unwrap();
+                advance_n_blocks(2);
+                contract.resume_group(0).unwrap();
+            }
+            advance_n_blocks(2);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(1000 + 26 * 1030)
+            );
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(27 * 1030));
+        }
+
+        /// A scheduled deactivation can be cancelled without changing the payments
+        #[ink::test]
+        fn schedule_and_cancel_multiplier_deactivation() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(1);
+
+            // only future period boundaries are valid
+            for block in [0, 1, 5] {
+                assert_eq!(
+                    contract.schedule_multiplier_deactivation(1, block),
+                    Err(Error::InvalidParams)
+                );
+            }
+            contract.schedule_multiplier_deactivation(1, 8).unwrap();
+            assert_eq!(
+                contract.get_multiplier(1).unwrap().valid_until_block,
+                Some(8)
+            );
+            assert_eq!(
+                contract.schedule_multiplier_deactivation(1, 10),
+                Err(Error::MultiplierAlreadyDeactivated)
+            );
+
+            // the multiplier is still paid in the next period
+            assert_eq!(contract.get_total_debt_for_next_period(), 2 * 1030);
+
+            advance_n_blocks(4);
+            assert_eq!(contract.cancel_multiplier_deactivation(1), Ok(()));
+            assert_eq!(contract.get_multiplier(1).unwrap().valid_until_block, None);
+
+            advance_n_blocks(5);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(5 * 1030));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.schedule_multiplier_deactivation(1, 12),
+                Err(Error::NotOwner)
+            );
+        }
+
+        /// A scheduled deactivation expires and is pruned like an immediate one
+        #[ink::test]
+        fn scheduled_multiplier_deactivation_expires() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract.schedule_multiplier_deactivation(1, 8).unwrap();
+
+            // the period [8, 10) is the first one without the multiplier
+            advance_n_blocks(6);
+            assert_eq!(contract.get_total_debt_for_next_period(), 2 * 1000);
+
+            advance_n_blocks(3);
+            assert_eq!(
+                contract.cancel_multiplier_deactivation(1),
+                Err(Error::MultiplierExpired)
+            );
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(4 * 1030));
+
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 4 * 1030).unwrap();
+            let multipliers = contract.get_beneficiary(accounts.bob).unwrap().multipliers;
+            assert!(!multipliers.contains_key(&1));
+
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1000));
+        }
+
+        /// Only the owner can deactivate a multiplier
+        #[ink::test]
+        fn deactivate_multiplier_not_owner() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.bob);
+            assert_eq!(contract.deactivate_multiplier(1), Err(Error::NotOwner));
+            assert_eq!(contract.get_multiplier(1).unwrap().valid_until_block, None);
+        }
+
+        /// Only the owner can delete a multiplier
+        #[ink::test]
+        fn delete_unused_multiplier_not_owner() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract.deactivate_multiplier(1).unwrap();
+            advance_n_blocks(2);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            contract.claim_payment(accounts.charlie, 0).unwrap();
+
+            set_sender(accounts.bob);
+            assert_eq!(contract.delete_unused_multiplier(1), Err(Error::NotOwner));
+            assert!(contract.get_multiplier(1).is_ok());
+
+            set_sender(accounts.alice);
+            assert_eq!(contract.delete_unused_multiplier(1), Ok(()));
+        }
+
+        /// Test reactivating a multiplier before and after its expiration
+        #[ink::test]
+        fn reactivate_multiplier() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            // only deactivated multipliers can be reactivated
+            assert_eq!(
+                contract.reactivate_multiplier(1),
+                Err(Error::MultiplierNotDeactivated)
+            );
+            assert_eq!(
+                contract.reactivate_multiplier(5),
+                Err(Error::MultiplierNotFound)
+            );
+
+            // the multiplier expires at block 2
+            contract.deactivate_multiplier(1).unwrap();
+            advance_n_blocks(1);
+
+            set_sender(accounts.bob);
+            assert_eq!(contract.reactivate_multiplier(1), Err(Error::NotOwner));
+            set_sender(accounts.alice);
+
+            assert_eq!(contract.reactivate_multiplier(1), Ok(()));
+            assert_eq!(contract.get_multiplier(1).unwrap().valid_until_block, None);
+
+            // the multiplier still counts after the old expiration block
+            advance_n_blocks(3);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2 * 1030));
+            set_sender(accounts.bob);
+            let bob_balance = get_balance(accounts.bob);
+            contract.claim_payment(accounts.bob, 2 * 1030).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + 2 * 1030);
+            set_sender(accounts.alice);
+
+            // once expired it can't be reactivated
+            contract.deactivate_multiplier(1).unwrap();
+            advance_n_blocks(2);
+            assert_eq!(
+                contract.reactivate_multiplier(1),
+                Err(Error::MultiplierExpired)
+            );
+        }
+
+        /// A beneficiary absent for six periods while both of their multipliers are deactivated,
+        /// expired and deleted must still be paid what each period was worth when it ended
+        #[ink::test]
+        fn claim_after_long_absence_with_deleted_multipliers() {
+            let total_balance = 100_000_000u128;
+            let (accounts, mut contract) = create_accounts_and_contract(total_balance);
+
+            // period [2, 4): Performance is deactivated and expires at block 4
+            advance_n_blocks(2);
+            contract.deactivate_multiplier(1).unwrap();
+
+            // period [4, 6): Seniority is deactivated and expires at block 6
+            advance_n_blocks(2);
+            contract.deactivate_multiplier(0).unwrap();
+
+            // period [6, 8): the owner updates everyone's payments and deletes both multipliers
+            advance_n_blocks(3);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            contract.claim_payment(accounts.charlie, 0).unwrap();
+            contract.delete_unused_multiplier(1).unwrap();
+            contract.delete_unused_multiplier(0).unwrap();
+            assert!(contract.base_multipliers.get(0).is_none());
+            assert!(contract.base_multipliers.get(1).is_none());
+
+            // bob comes back at block 12, six periods after the contract started
+            advance_n_blocks(5);
+            let bob_balance_before_payment = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+
+            // 2 periods with both multipliers, 1 with Seniority only, 3 without multipliers
+            let expected = 2 * (103 * 1000 / 100) + (100 * 1000 / 100) + 3 * (1000 / 100);
+            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            assert_eq!(amount_to_claim, expected);
+
+            contract
+                .claim_payment(accounts.bob, amount_to_claim)
+                .unwrap();
+            assert_eq!(
+                get_balance(accounts.bob),
+                bob_balance_before_payment + expected
+            );
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+        }
+
+        /// Deleting a multiplier fails before its expiry and succeeds from the expiry block on
+        #[ink::test]
+        fn delete_multiplier_before_and_at_expiry() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100)), (1, percent(3))])
+                .unwrap();
+
+            // the multiplier expires at block 2
+            contract.deactivate_multiplier(1).unwrap();
+            advance_n_blocks(1);
+            assert_eq!(
+                contract.delete_unused_multiplier(1),
+                Err(Error::MultiplierNotExpired)
+            );
+
+            // exactly at the expiry block, once every beneficiary claimed in the period
+            advance_n_blocks(1);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            assert_eq!(contract.delete_unused_multiplier(1), Ok(()));
+            assert!(contract.base_multipliers.get(1).is_none());
+            assert_eq!(contract.multipliers_list, vec![0]);
+        }
+
+        /// Deleting a multiplier after its expiry requires every beneficiary to have claimed in the period
+        #[ink::test]
+        fn delete_multiplier_after_expiry() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100)), (1, percent(3))])
+                .unwrap();
+
+            contract.deactivate_multiplier(1).unwrap();
+            advance_n_blocks(5);
+            assert_eq!(
+                contract.delete_unused_multiplier(1),
+                Err(Error::NotAllClaimedInPeriod)
+            );
+
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            assert_eq!(contract.delete_unused_multiplier(1), Ok(()));
+            assert_eq!(
+                contract.delete_unused_multiplier(1),
+                Err(Error::MultiplierNotFound)
+            );
+        }
+
+        /// A beneficiary that skips three periods is paid the periods before the expiry with the multiplier
+        #[ink::test]
+        fn claim_splits_periods_at_multiplier_expiry() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            // deactivated during the first period, the multiplier expires at block 2
+            advance_n_blocks(1);
+            contract.deactivate_multiplier(1).unwrap();
+
+            // periods [0, 2), [2, 4) and [4, 6) are skipped
+            advance_n_blocks(5);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(1030 + 2 * 1000)
+            );
+            assert_eq!(contract.get_total_debts(), 2 * (1030 + 2 * 1000));
+
+            let bob_balance_before_payment = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+            contract
+                .claim_payment(accounts.bob, 1030 + 2 * 1000)
+                .unwrap();
+            assert_eq!(
+                get_balance(accounts.bob),
+                bob_balance_before_payment + 1030 + 2 * 1000
+            );
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+        }
+
+        /// Claiming after a multiplier held by the beneficiary was deleted pays the remaining multipliers only
+        #[ink::test]
+        fn claim_after_deleting_multiplier() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract.add_base_multiplier("Bonus".to_string()).unwrap();
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100)), (2, percent(50))])
+                .unwrap();
+
+            // the bonus expires at block 2
+            contract.deactivate_multiplier(2).unwrap();
+            advance_n_blocks(3);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            contract.delete_unused_multiplier(2).unwrap();
+
+            // the deleted id is swept from the beneficiary
+            let beneficiary = contract.beneficiaries.get(accounts.bob).unwrap();
+            assert_eq!(
+                beneficiary.multipliers,
+                vec_to_btreemap(&[(0, percent(100))])
+            );
+
+            advance_n_blocks(2);
+            let bob_balance_before_payment = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+
+            // 1 period with the bonus and 1 period without it
+            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            assert_eq!(amount_to_claim, 1500 + 1000);
+            contract
+                .claim_payment(accounts.bob, amount_to_claim)
+                .unwrap();
+            assert_eq!(
+                get_balance(accounts.bob),
+                bob_balance_before_payment + amount_to_claim
+            );
+        }
+
+        /// A beneficiary holding a multiplier id that does not exist anymore can still claim
+        #[ink::test]
+        fn claim_with_dangling_multiplier_id() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100))])
+                .unwrap();
+
+            // simulate a beneficiary written before deleted ids were swept
+            let mut beneficiary = contract.beneficiaries.get(accounts.bob).unwrap();
+            beneficiary.multipliers.insert(7, percent(50));
+            contract.beneficiaries.insert(accounts.bob, &beneficiary);
+
+            advance_n_blocks(2);
+            assert_eq!(contract.get_total_debt_for_next_period(), 1000);
+            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            assert_eq!(amount_to_claim, 1000);
+
+            set_sender(accounts.bob);
+            contract
+                .claim_payment(accounts.bob, amount_to_claim)
+                .unwrap();
+            let beneficiary = contract.beneficiaries.get(accounts.bob).unwrap();
+            assert_eq!(
+                beneficiary.multipliers,
+                vec_to_btreemap(&[(0, percent(100))])
+            );
+        }
+
+        /// Check all the base multipliers are listed in order, including the deactivated ones
+        #[ink::test]
+        fn check_all_base_multipliers() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract.add_base_multiplier("Bonus".to_string()).unwrap();
+            contract.deactivate_multiplier(1).unwrap();
+
+            let base_multipliers = contract.get_all_base_multipliers();
+            assert_eq!(base_multipliers.len(), 3);
+            assert_eq!(
+                base_multipliers,
+                vec![
+                    (0, BaseMultiplier::new("Seniority".to_string())),
+                    (
+                        1,
+                        BaseMultiplier {
+                            name: "Performance".to_string(),
+                            valid_until_block: Some(2),
+                            sponsor: None,
+                        }
+                    ),
+                    (2, BaseMultiplier::new("Bonus".to_string())),
+                ]
+            );
+        }
+
+        /// Check getting a known, a deactivated and an unknown base multiplier
+        #[ink::test]
+        fn check_get_base_multiplier() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(
+                contract.get_base_multiplier(0),
+                Ok(BaseMultiplier::new("Seniority".to_string()))
+            );
+            contract.deactivate_multiplier(1).unwrap();
+            assert_eq!(
+                contract.get_base_multiplier(1).unwrap().valid_until_block,
+                Some(2)
+            );
+            assert_eq!(
+                contract.get_base_multiplier(2),
+                Err(Error::MultiplierNotFound)
+            );
+        }
+
+        /// Check the payments near u128::MAX fail instead of wrapping
+        #[ink::test]
+        fn payment_calculation_overflow() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+
+            // the multiplier times the base payment overflows
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let mut contract = OpenPayroll::new(
+                1,
+                u128::MAX / 2,
+                vec!["Seniority".to_string()],
+                vec![beneficiary_bob.clone()],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .expect("Cannot create contract");
+            advance_n_blocks(1);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Err(Error::PaymentCalculationOverflow)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 1),
+                Err(Error::PaymentCalculationOverflow)
+            );
+            assert_eq!(contract.get_total_debts(), u128::MAX);
+
+            // the payment per period times the elapsed periods overflows
+            set_sender(accounts.alice);
+            let mut contract = OpenPayroll::new(
+                1,
+                u128::MAX / 10_000,
+                vec!["Seniority".to_string()],
+                vec![beneficiary_bob],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .expect("Cannot create contract");
+            advance_n_blocks(10_000);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(u128::MAX / 10_000 * 10_000)
+            );
+            advance_n_blocks(1);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Err(Error::PaymentCalculationOverflow)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 1),
+                Err(Error::PaymentCalculationOverflow)
+            );
+        }
+
+        /// Claim a payment with a multiplier below one percent of precision
+        #[ink::test]
+        fn claim_payment_with_bps_multiplier() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract
+                .update_beneficiary(
+                    accounts.bob,
+                    vec![(0, percent(100)), (1, MultiplierValue::from_bps(50))],
+                    None,
+                )
+                .unwrap();
+            advance_n_blocks(3);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1005));
+        }
+
+        /// Rename a base multiplier and look it up by name
+        #[ink::test]
+        fn rename_base_multiplier() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(
+                contract.get_multiplier_id_by_name("Performance".to_string()),
+                Some(1)
+            );
+
+            contract
+                .rename_base_multiplier(1, "Quality".to_string())
+                .unwrap();
+            assert_eq!(
+                contract.get_multiplier(1),
+                Ok(BaseMultiplier::new("Quality".to_string()))
+            );
+            assert_eq!(
+                contract.get_multiplier_id_by_name("Quality".to_string()),
+                Some(1)
+            );
+            assert_eq!(
+                contract.get_multiplier_id_by_name("Performance".to_string()),
+                None
+            );
+            assert_eq!(contract.get_multiplier(5), Err(Error::MultiplierNotFound));
+
+            // the name can have up to 64 characters
+            let long_name = "é".repeat(MAX_MULTIPLIER_NAME_LENGTH);
+            contract
+                .rename_base_multiplier(1, long_name.clone())
+                .unwrap();
+            assert_eq!(contract.get_multiplier(1).unwrap().name, long_name);
+            assert_eq!(
+                contract.rename_base_multiplier(1, "a".repeat(MAX_MULTIPLIER_NAME_LENGTH + 1)),
+                Err(Error::NameTooLong)
+            );
+            assert_eq!(
+                contract.add_base_multiplier("a".repeat(MAX_MULTIPLIER_NAME_LENGTH + 1)),
+                Err(Error::NameTooLong)
+            );
+
+            assert_eq!(
+                contract.rename_base_multiplier(5, "Bonus".to_string()),
+                Err(Error::MultiplierNotFound)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.rename_base_multiplier(1, "Bonus".to_string()),
+                Err(Error::NotOwner)
+            );
+        }
+
+        /// Test setting and clearing the sponsor of a multiplier
+        #[ink::test]
+        fn set_multiplier_sponsor() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(
+                contract.set_multiplier_sponsor(5, Some(accounts.django)),
+                Err(Error::MultiplierNotFound)
+            );
+
+            contract
+                .set_multiplier_sponsor(1, Some(accounts.django))
+                .unwrap();
+            assert_eq!(
+                contract.get_multiplier(1).unwrap().sponsor,
+                Some(accounts.django)
+            );
+            assert_eq!(contract.get_multiplier(0).unwrap().sponsor, None);
+
+            contract.set_multiplier_sponsor(1, None).unwrap();
+            assert_eq!(contract.get_multiplier(1).unwrap().sponsor, None);
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.set_multiplier_sponsor(1, Some(accounts.bob)),
+                Err(Error::NotOwner)
+            );
+        }
+
+        /// Test renaming only active multipliers keeps their id and expiry
+        #[ink::test]
+        fn update_base_multiplier_name() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract
+                .update_base_multiplier_name(0, "Experience".to_string())
+                .unwrap();
+            assert_eq!(
+                contract.get_multiplier(0),
+                Ok(BaseMultiplier::new("Experience".to_string()))
+            );
+            assert_eq!(
+                contract.get_multiplier_id_by_name("Experience".to_string()),
+                Some(0)
+            );
+            assert_eq!(contract.get_multipliers_list(), vec![0, 1]);
+            assert_eq!(
+                contract.get_beneficiary(accounts.bob).unwrap().multipliers[&0],
+                percent(100)
+            );
+
+            contract.deactivate_multiplier(1).unwrap();
+            assert_eq!(
+                contract.update_base_multiplier_name(1, "Quality".to_string()),
+                Err(Error::MultiplierAlreadyDeactivated)
+            );
+            assert_eq!(
+                contract.get_multiplier(1).unwrap().valid_until_block,
+                Some(2)
+            );
+            assert_eq!(
+                contract.update_base_multiplier_name(5, "Quality".to_string()),
+                Err(Error::MultiplierNotFound)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.update_base_multiplier_name(0, "Seniority".to_string()),
+                Err(Error::NotOwner)
+            );
+        }
+
+        /// Test replacing a multiplier moves its values to the new one without changing the payments
+        #[ink::test]
+        fn replace_multiplier() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract
+                .add_beneficiary(accounts.django, vec![(0, percent(100))])
+                .unwrap();
+            advance_n_blocks(3);
+            let total_debts = contract.get_total_debts();
+            assert_eq!(
+                contract.replace_multiplier(1, "Performance".to_string()),
+                Err(Error::DuplicatedMultiplierName)
+            );
+            assert_eq!(
+                contract.replace_multiplier(5, "Quality".to_string()),
+                Err(Error::MultiplierNotFound)
+            );
+
+            assert_eq!(contract.replace_multiplier(1, "Quality".to_string()), Ok(2));
+            assert_eq!(contract.get_multiplier(2).unwrap().name, "Quality");
+            assert_eq!(
+                contract.get_multiplier(1).unwrap().valid_until_block,
+                Some(4)
+            );
+            for account_id in [accounts.bob, accounts.charlie] {
+                let multipliers = contract.get_beneficiary(account_id).unwrap().multipliers;
+                assert_eq!(
+                    multipliers,
+                    vec_to_btreemap(&[(0, percent(100)), (2, percent(3))])
+                );
+            }
+            assert_eq!(
+                contract
+                    .get_beneficiary(accounts.django)
+                    .unwrap()
+                    .multipliers,
+                vec_to_btreemap(&[(0, percent(100))])
+            );
+            assert_eq!(contract.get_total_debts(), total_debts);
+            assert_eq!(
+                contract.replace_multiplier(1, "Impact".to_string()),
+                Err(Error::MultiplierAlreadyDeactivated)
+            );
+
+            // the next periods are paid as before the replacement
+            advance_n_blocks(1);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2060));
+            assert_eq!(contract.get_amount_to_claim(accounts.django), Ok(2000));
+
+            // both multipliers count towards the maximum until the old one is deleted
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            for name in 2..10 {
+                contract.add_base_multiplier(name.to_string()).unwrap();
+            }
+            assert_eq!(
+                contract.replace_multiplier(1, "Quality".to_string()),
+                Err(Error::MaxMultipliersExceeded)
+            );
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.replace_multiplier(0, "Quality".to_string()),
+                Err(Error::NotOwner)
+            );
+        }
+
+        /// Test scheduled multiplier renames are applied once their activation block is reached
+        #[ink::test]
+        fn schedule_multiplier_update() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+            for activation_block in [0, 2] {
+                assert_eq!(
+                    contract.schedule_multiplier_update(
+                        0,
+                        "Experience".to_string(),
+                        activation_block
+                    ),
+                    Err(Error::InvalidParams)
+                );
+            }
+            assert_eq!(
+                contract.schedule_multiplier_update(0, "Performance".to_string(), 4),
+                Err(Error::DuplicatedMultiplierName)
+            );
+            assert_eq!(
+                contract.schedule_multiplier_update(5, "Experience".to_string(), 4),
+                Err(Error::MultiplierNotFound)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.schedule_multiplier_update(0, "Experience".to_string(), 4),
+                Err(Error::NotOwner)
+            );
+
+            // a new schedule replaces the previous one of the multiplier
+            set_sender(accounts.alice);
+            contract
+                .schedule_multiplier_update(0, "Tenure".to_string(), 6)
+                .unwrap();
+            contract
+                .schedule_multiplier_update(0, "Experience".to_string(), 4)
+                .unwrap();
+            contract
+                .schedule_multiplier_update(1, "Quality".to_string(), 6)
+                .unwrap();
+            assert_eq!(contract.get_scheduled_multiplier_updates().len(), 2);
+
+            // nothing is applied before the activation block
+            set_sender(accounts.bob);
+            advance_n_blocks(1);
+            assert_eq!(contract.process_scheduled_multiplier_updates(), Ok(0));
+            assert_eq!(contract.get_multiplier(0).unwrap().name, "Seniority");
+            assert_eq!(contract.get_scheduled_multiplier_updates().len(), 2);
+
+            advance_n_blocks(1);
+            assert_eq!(contract.process_scheduled_multiplier_updates(), Ok(1));
+            assert_eq!(contract.get_multiplier(0).unwrap().name, "Experience");
+            assert_eq!(
+                contract.get_scheduled_multiplier_updates(),
+                vec![ScheduledMultiplierChange {
+                    multiplier_id: 1,
+                    new_name: "Quality".to_string(),
+                    activation_block: 6,
+                }]
+            );
+
+            // a change of a multiplier deactivated in between is dropped
+            set_sender(accounts.alice);
+            contract.deactivate_multiplier(1).unwrap();
+            advance_n_blocks(2);
+            assert_eq!(contract.process_scheduled_multiplier_updates(), Ok(0));
+            assert_eq!(contract.get_multiplier(1).unwrap().name, "Performance");
+            assert_eq!(contract.get_scheduled_multiplier_updates(), vec![]);
+        }
+
+        /// Multiplier names must be unique among active multipliers and not empty
+        #[ink::test]
+        fn duplicated_multiplier_names() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(
+                contract.add_base_multiplier("Seniority".to_string()),
+                Err(Error::DuplicatedMultiplierName)
+            );
+            assert_eq!(
+                contract.add_base_multiplier("".to_string()),
+                Err(Error::InvalidParams)
+            );
+            assert_eq!(
+                contract.rename_base_multiplier(1, "Seniority".to_string()),
+                Err(Error::DuplicatedMultiplierName)
+            );
+            assert_eq!(
+                contract.rename_base_multiplier(1, "".to_string()),
+                Err(Error::InvalidParams)
+            );
+            // keeping the same name is not a duplicate
+            contract
+                .rename_base_multiplier(0, "Seniority".to_string())
+                .unwrap();
+
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Seniority".to_string()],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+            assert!(matches!(res, Err(Error::DuplicatedMultiplierName)));
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["".to_string()],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+            assert!(matches!(res, Err(Error::InvalidParams)));
+        }
+
+        /// A deactivated multiplier can be looked up by name and its name reused
+        #[ink::test]
+        fn lookup_deactivated_multiplier_by_name() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract.deactivate_multiplier(1).unwrap();
+            assert_eq!(
+                contract.get_multiplier_id_by_name("Performance".to_string()),
+                Some(1)
+            );
+
+            // the name of a deactivated multiplier can be reused, and the active one is found
+            contract
+                .add_base_multiplier("Performance".to_string())
+                .unwrap();
+            assert_eq!(
+                contract.get_multiplier_id_by_name("Performance".to_string()),
+                Some(2)
+            );
+        }
+
+        /// Test accrual halts while a beneficiary is suspended and resumes after unsuspending
+        #[ink::test]
+        fn suspend_and_unsuspend_beneficiary() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+
+            // one period is earned before the suspension
+            advance_n_blocks(3);
+            contract.suspend_beneficiary(accounts.bob).unwrap();
+            assert!(matches!(
+                contract.suspend_beneficiary(accounts.bob),
+                Err(Error::BeneficiaryAlreadySuspended)
+            ));
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
+
+            // nothing is accrued while suspended
+            advance_n_blocks(4);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
+            assert_eq!(contract.get_amount_to_claim(accounts.charlie), Ok(3 * 1030));
+
+            // what was earned before the suspension is frozen
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 1030),
+                Err(Error::BeneficiaryIsSuspended)
+            );
+            // a zero claim still updates the payments so the owner can change parameters
+            set_sender(accounts.alice);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+
+            // accrual resumes from the current period without back-pay
+            contract.unsuspend_beneficiary(accounts.bob).unwrap();
+            assert!(matches!(
+                contract.unsuspend_beneficiary(accounts.bob),
+                Err(Error::BeneficiaryNotSuspended)
+            ));
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2 * 1030));
+
+            // the frozen amount and the new period can be claimed once unsuspended
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 2 * 1030).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+        }
+
+        /// A multiplier expiring while a beneficiary is suspended is paid for the periods before the suspension
+        /// and reported when the beneficiary is unsuspended
+        #[ink::test]
+        fn suspend_then_deactivate_multiplier() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+            contract.suspend_beneficiary(accounts.bob).unwrap();
+            contract.deactivate_multiplier(1).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
 
-            if (claiming_period_block == claims_in_period.period
-                && claims_in_period.total_claims == self.beneficiaries_accounts.len() as u32)
-                || claiming_period_block == 0
-            // initial period in intial block noone can claim
-            {
-                return Ok(());
-            }
+            // the multiplier expires at block 4 and the owner updates the payments
+            advance_n_blocks(3);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            assert_eq!(contract.get_pruned_multipliers(accounts.bob), Ok(vec![1]));
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
 
-            Err(Error::NotAllClaimedInPeriod)
+            contract.unsuspend_beneficiary(accounts.bob).unwrap();
+            assert_eq!(contract.get_pruned_multipliers(accounts.bob), Ok(vec![]));
+            advance_n_blocks(1);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030 + 1000));
         }
-    }
 
-    //----------------------------------------------------------------------------------------
-    // Pure functions
-    //----------------------------------------------------------------------------------------
+        /// A beneficiary suspended while a multiplier is being deactivated keeps it for the earlier periods
+        #[ink::test]
+        fn deactivate_multiplier_then_suspend() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+            contract.deactivate_multiplier(1).unwrap();
+            advance_n_blocks(1);
+            contract.suspend_beneficiary(accounts.bob).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
 
-    /// Given a vector of (id, multiplier) pairs, return a BTreeMap of (id, multiplier) pairs
-    fn vec_to_btreemap(vec: &[(MultiplierId, Multiplier)]) -> BTreeMap<MultiplierId, Multiplier> {
-        let mut btree_map = BTreeMap::new();
-        for (id, multiplier) in vec.iter() {
-            btree_map.insert(*id, *multiplier);
-        }
-        btree_map
-    }
+            // the owner updates the payments to delete the expired multiplier
+            advance_n_blocks(1);
+            contract.claim_payment(accounts.charlie, 0).unwrap();
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            contract.delete_unused_multiplier(1).unwrap();
+            assert_eq!(contract.get_pruned_multipliers(accounts.bob), Ok(vec![1]));
 
-    /// Given a list of beneficiaries it ensures there are no duplicates
-    #[allow(clippy::all)]
-    fn ensure_no_duplicate_beneficiaries(beneficiaries: &Vec<AccountId>) -> Result<(), Error> {
-        let mut sorted_beneficiaries = beneficiaries.clone();
-        sorted_beneficiaries.sort_by_key(|&beneficiary| beneficiary);
+            contract.unsuspend_beneficiary(accounts.bob).unwrap();
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030 + 1000));
 
-        for i in 1..sorted_beneficiaries.len() {
-            if sorted_beneficiaries[i - 1] == sorted_beneficiaries[i] {
-                return Err(Error::DuplicatedBeneficiaries);
-            }
+            // charlie was not suspended, so nothing is recorded
+            assert_eq!(
+                contract.get_pruned_multipliers(accounts.charlie),
+                Ok(vec![])
+            );
         }
 
-        Ok(())
-    }
+        /// Test no payments are accrued before the vesting cliff, nor for a partial first period
+        #[ink::test]
+        fn add_beneficiary_with_start() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
 
-    /// Given a list of multipliers it ensures there are no duplicates
-    #[allow(clippy::all)]
-    fn ensure_no_duplicate_multipliers(
-        multipliers: &Vec<(MultiplierId, Multiplier)>,
-    ) -> Result<(), Error> {
-        let mut sorted_multipliers = multipliers.clone();
-        sorted_multipliers.sort_by_key(|&(multiplier_id, _)| multiplier_id);
+            // the cliff at block 5 falls in the middle of the period [4, 6)
+            contract
+                .add_beneficiary_with_start(accounts.bob, vec![(0, percent(100))], 5)
+                .unwrap();
+            assert_eq!(
+                contract
+                    .beneficiaries
+                    .get(accounts.bob)
+                    .unwrap()
+                    .start_block,
+                Some(5)
+            );
 
-        for i in 1..sorted_multipliers.len() {
-            if sorted_multipliers[i - 1].0 == sorted_multipliers[i].0 {
-                return Err(Error::DuplicatedMultipliers);
-            }
-        }
+            advance_n_blocks(6);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
 
-        Ok(())
-    }
+            // the first full period after the cliff is [6, 8)
+            advance_n_blocks(1);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+            advance_n_blocks(1);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1000));
 
-    //----------------------------------------------------------------------------------------
-    // Tests
-    //----------------------------------------------------------------------------------------
-    #[cfg(test)]
-    mod tests {
-        use ink::{
-            env::{test::DefaultAccounts, DefaultEnvironment},
-            primitives::AccountId,
-        };
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1000));
 
-        use super::*;
+            // the start block can't be before the current period [10, 12)
+            set_sender(accounts.alice);
+            advance_n_blocks(1);
+            assert_eq!(
+                contract.add_beneficiary_with_start(accounts.charlie, vec![(0, percent(100))], 9),
+                Err(Error::InvalidParams)
+            );
+            contract
+                .add_beneficiary_with_start(accounts.charlie, vec![(0, percent(100))], 10)
+                .unwrap();
+            advance_n_blocks(1);
+            assert_eq!(contract.get_amount_to_claim(accounts.charlie), Ok(1000));
+        }
 
-        // UTILITY FUNCTIONS TO MAKE TESTING EASIER
-        fn create_contract(
-            initial_balance: Balance,
-            accounts: &DefaultAccounts<DefaultEnvironment>,
-        ) -> OpenPayroll {
-            set_balance(contract_id(), initial_balance);
+        /// Test the vesting cliff of the initial beneficiaries
+        #[ink::test]
+        fn create_contract_with_start_block() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
             let beneficiary_bob = InitialBeneficiary {
                 account_id: accounts.bob,
-                multipliers: vec![(0, 100), (1, 3)],
+                multipliers: vec![(0, percent(100))],
+                start_block: Some(4),
+                name: None,
+                notes: None,
             };
             let beneficiary_charlie = InitialBeneficiary {
                 account_id: accounts.charlie,
-                multipliers: vec![(0, 100), (1, 3)],
+                multipliers: vec![(0, percent(100))],
+                start_block: None,
+                name: None,
+                notes: None,
             };
-            OpenPayroll::new(
+            let contract = OpenPayroll::new(
                 2,
                 1000,
-                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec!["Seniority".to_string()],
                 vec![beneficiary_bob, beneficiary_charlie],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
             )
-            .expect("Cannot create contract")
-        }
+            .unwrap();
 
-        fn create_contract_with_no_beneficiaries(initial_balance: Balance) -> OpenPayroll {
-            set_balance(contract_id(), initial_balance);
-            OpenPayroll::new(
-                2,
-                1000,
-                vec!["Seniority".to_string(), "Performance".to_string()],
-                vec![],
-            )
-            .expect("Cannot create contract")
+            advance_n_blocks(4);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+            assert_eq!(contract.get_amount_to_claim(accounts.charlie), Ok(2000));
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1000));
+            assert_eq!(contract.get_amount_to_claim(accounts.charlie), Ok(3000));
         }
 
-        fn create_contract_with_no_beneficiaries_periodicity(
-            initial_balance: Balance,
-            periodicity: u32,
-        ) -> OpenPayroll {
-            set_balance(contract_id(), initial_balance);
-            OpenPayroll::new(
-                periodicity,
-                1000,
-                vec!["Seniority".to_string(), "Performance".to_string()],
-                vec![],
-            )
-            .expect("Cannot create contract")
-        }
+        /// Test a claim above the max per claim is clamped and the rest stays claimable
+        #[ink::test]
+        fn claim_payment_clamped_to_max_per_claim() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(
+                contract.set_max_per_claim(accounts.bob, Some(0)),
+                Err(Error::InvalidParams)
+            );
+            contract.set_max_per_claim(accounts.bob, Some(500)).unwrap();
 
-        fn create_accounts_and_contract(
-            initial_balance: Balance,
-        ) -> (
-            ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment>,
-            OpenPayroll,
-        ) {
-            let accounts = default_accounts();
+            advance_n_blocks(2);
+            let bob_balance_before_payment = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance_before_payment + 500);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(530));
+
+            // the remainder can be claimed right away, still clamped per claim
+            contract.claim_payment(accounts.bob, 530).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance_before_payment + 1000);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(30));
+            contract.claim_payment(accounts.bob, 30).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance_before_payment + 1030);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+
+            // without a limit the full amount is transferred
             set_sender(accounts.alice);
-
-            let contract = create_contract(initial_balance, &accounts);
-            (accounts, contract)
+            contract.set_max_per_claim(accounts.bob, None).unwrap();
+            advance_n_blocks(4);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 2060).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance_before_payment + 3090);
         }
 
-        fn contract_id() -> AccountId {
-            ink::env::test::callee::<ink::env::DefaultEnvironment>()
+        /// Test anyone can top up the treasury
+        #[ink::test]
+        fn top_up() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.django);
+            assert_eq!(contract.top_up(), Err(Error::InvalidParams));
+
+            // the off-chain environment does not move the value, so the transfer is simulated
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(500);
+            set_balance(contract_id(), 100_000_000u128 + 500);
+            let events_before = ink::env::test::recorded_events().count();
+            contract.top_up().unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 1);
+            assert_eq!(contract.get_contract_balance(), 100_000_000u128 + 500);
+
+            // a contract paying in a token is not funded with the native token
+            let (_, mut token_contract, _) = create_token_contract(1000u128);
+            assert_eq!(token_contract.top_up(), Err(Error::InvalidParams));
         }
 
-        fn set_sender(sender: AccountId) {
-            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(sender);
+        /// Test the number of periods the treasury can fund
+        #[ink::test]
+        fn get_liquidity_runway() {
+            // no beneficiaries
+            let contract = create_contract_with_no_beneficiaries(10_000u128);
+            assert_eq!(contract.get_liquidity_runway(), Ok(u32::MAX));
+
+            // the periods cost 2060
+            let (accounts, mut contract) = create_accounts_and_contract(2060u128);
+            assert_eq!(contract.get_liquidity_runway(), Ok(1));
+            set_balance(contract_id(), 10_000u128);
+            assert_eq!(contract.get_liquidity_runway(), Ok(4));
+
+            // the debts are paid first
+            advance_n_blocks(2);
+            assert_eq!(contract.get_liquidity_runway(), Ok(3));
+            set_balance(contract_id(), 2000u128);
+            assert_eq!(contract.get_liquidity_runway(), Ok(0));
+
+            // suspended beneficiaries cost nothing
+            contract.suspend_beneficiary(accounts.bob).unwrap();
+            contract.suspend_beneficiary(accounts.charlie).unwrap();
+            assert_eq!(contract.get_liquidity_runway(), Ok(u32::MAX));
         }
 
-        fn default_accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
-            ink::env::test::default_accounts::<ink::env::DefaultEnvironment>()
-        }
+        /// Test the claims of a period can't exceed the period budget
+        #[ink::test]
+        fn claim_payment_over_period_budget() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.bob);
+            assert_eq!(contract.set_period_budget(Some(2500)), Err(Error::NotOwner));
+            set_sender(accounts.alice);
+            contract.set_period_budget(Some(2500)).unwrap();
+            assert_eq!(contract.get_period_budget(), Some(2500));
 
-        fn set_balance(account_id: AccountId, balance: Balance) {
-            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(account_id, balance)
-        }
+            // two claims within the budget succeed, the third one exceeds it
+            advance_n_blocks(4);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            set_sender(accounts.charlie);
+            contract.claim_payment(accounts.charlie, 1000).unwrap();
+            assert_eq!(contract.get_claims_in_period().total_amount, 2000);
+            assert_eq!(
+                contract.claim_payment(accounts.charlie, 1000),
+                Err(Error::PeriodBudgetExceeded)
+            );
+            contract.claim_payment(accounts.charlie, 500).unwrap();
 
-        fn advance_n_blocks(n: u32) {
-            for _ in 0..n {
-                ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
-            }
+            // the budget is reset in the next period
+            advance_n_blocks(2);
+            contract.claim_payment(accounts.charlie, 1590).unwrap();
+            assert_eq!(contract.get_claims_in_period().total_amount, 1590);
         }
 
-        fn get_current_block() -> u32 {
-            ink::env::block_number::<ink::env::DefaultEnvironment>()
+        /// Test no payment accrues after the end block
+        #[ink::test]
+        fn set_end_block() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.bob);
+            assert_eq!(contract.set_end_block(Some(5)), Err(Error::NotOwner));
+            set_sender(accounts.alice);
+            advance_n_blocks(1);
+            assert_eq!(contract.set_end_block(Some(0)), Err(Error::InvalidParams));
+
+            // the periods [0, 2) and [2, 4) end before block 5, [4, 6) straddles it and is not paid
+            contract.set_end_block(Some(5)).unwrap();
+            assert_eq!(contract.get_end_block(), Some(5));
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+            advance_n_blocks(7);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
+            assert_eq!(contract.get_amount_to_claim(accounts.charlie), Ok(2 * 1030));
+            assert_eq!(contract.get_total_debt_for_next_period(), 0);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+
+            // the accrued payments stay claimable, nothing more accrues
+            advance_n_blocks(20);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+            assert_eq!(contract.get_amount_to_claim(accounts.charlie), Ok(2 * 1030));
+
+            // once removed the payments accrue again
+            set_sender(accounts.alice);
+            contract.set_end_block(None).unwrap();
+            assert_eq!(contract.get_total_debt_for_next_period(), 2 * 1030);
         }
 
-        fn get_balance(account_id: AccountId) -> Balance {
-            ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account_id)
-                .expect("Cannot get account balance")
+        /// Test a beneficiary without multipliers is not owed a next period ending after the end block either
+        #[ink::test]
+        fn set_end_block_without_multipliers() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            contract.add_beneficiary(accounts.bob, vec![]).unwrap();
+            assert_eq!(contract.get_total_debt_for_next_period(), 10);
+
+            // the period [2, 4) ends at block 4, [4, 6) straddles the end block and is not paid
+            contract.set_end_block(Some(5)).unwrap();
+            assert_eq!(contract.get_total_debt_for_next_period(), 10);
+            advance_n_blocks(3);
+            assert_eq!(contract.get_total_debt_for_next_period(), 0);
+            advance_n_blocks(10);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2 * 10));
+            assert_eq!(contract.get_total_debt_for_next_period(), 0);
+
+            contract.set_end_block(None).unwrap();
+            assert_eq!(contract.get_total_debt_for_next_period(), 10);
         }
 
-        fn vec_to_btreemap(
-            vec: &[(MultiplierId, Multiplier)],
-        ) -> BTreeMap<MultiplierId, Multiplier> {
-            let mut btree_map = BTreeMap::new();
-            for (id, multiplier) in vec.iter() {
-                btree_map.insert(*id, *multiplier);
-            }
-            btree_map
+        /// Test the funds required for the debts and the next periods
+        #[ink::test]
+        fn get_funding_required_for_n_periods() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            // the periods cost 1030 + 2500 + 1000
+            contract
+                .update_beneficiary(
+                    accounts.charlie,
+                    vec![(0, percent(200)), (1, percent(50))],
+                    None,
+                )
+                .unwrap();
+            contract
+                .add_beneficiary(accounts.django, vec![(0, percent(100))])
+                .unwrap();
+            assert_eq!(contract.get_funding_required_for_n_periods(0), Ok(0));
+            assert_eq!(contract.get_funding_required_for_n_periods(1), Ok(4530));
+            assert_eq!(contract.get_funding_required_for_n_periods(3), Ok(3 * 4530));
+
+            // the debts are added
+            advance_n_blocks(2);
+            assert_eq!(contract.get_funding_required_for_n_periods(0), Ok(4530));
+            assert_eq!(contract.get_funding_required_for_n_periods(1), Ok(2 * 4530));
+            assert_eq!(contract.get_funding_required_for_n_periods(3), Ok(4 * 4530));
+
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            contract.update_base_payment(Balance::MAX / 4).unwrap();
+            assert_eq!(
+                contract.get_funding_required_for_n_periods(2),
+                Err(Error::PaymentCalculationOverflow)
+            );
         }
 
-        /// We test if the default constructor does its job.
+        /// Test returning the balance above the maximum runway
         #[ink::test]
-        fn default_works() {
+        fn return_excess() {
             let accounts = default_accounts();
             set_sender(accounts.alice);
-            create_contract(100_000_000u128, &accounts)
+            set_balance(contract_id(), 10_000u128);
+            let mut contract = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![InitialBeneficiary {
+                    account_id: accounts.bob,
+                    multipliers: vec![(0, percent(100))],
+                    start_block: None,
+                    name: None,
+                    notes: None,
+                }],
+                100,
+                10,
+                None,
+                0,
+                false,
+                Some(accounts.django),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(contract.get_refund_address(), Some(accounts.django));
+
+            // without a maximum runway nothing is returned
+            assert_eq!(contract.get_returnable_excess(), 0);
+            assert_eq!(contract.return_excess(), Ok(0));
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.set_max_runway_periods(Some(3)),
+                Err(Error::NotOwner)
+            );
+            set_sender(accounts.alice);
+            contract.set_max_runway_periods(Some(3)).unwrap();
+            assert_eq!(contract.get_max_runway_periods(), Some(3));
+
+            // 3 periods of 1000 are kept, anyone can return the rest
+            assert_eq!(contract.get_returnable_excess(), 7000);
+            let django_balance = get_balance(accounts.django);
+            set_sender(accounts.eve);
+            assert_eq!(contract.return_excess(), Ok(7000));
+            assert_eq!(get_balance(accounts.django), django_balance + 7000);
+            assert_eq!(get_balance(contract_id()), 3000);
+
+            // the debts are kept too
+            advance_n_blocks(2);
+            assert_eq!(contract.get_returnable_excess(), 0);
+            assert_eq!(contract.return_excess(), Ok(0));
+            set_balance(contract_id(), 5000u128);
+            assert_eq!(contract.get_returnable_excess(), 1000);
         }
 
+        /// Test a failing instantiation writes nothing to the storage
+        /// The contracts of a test share the storage, so what a failing constructor wrote would be
+        /// seen by the contract instantiated after it
         #[ink::test]
-        fn create_contract_ok() {
+        fn failing_constructor_writes_nothing() {
             let accounts = default_accounts();
-            let beneficiary_bob = InitialBeneficiary {
-                account_id: accounts.bob,
-                multipliers: vec![(0, 100), (1, 3)],
+            let initial_beneficiaries = |last: InitialBeneficiary| {
+                vec![
+                    InitialBeneficiary {
+                        account_id: accounts.bob,
+                        multipliers: vec![(0, percent(100))],
+                        start_block: None,
+                        name: None,
+                        notes: None,
+                    },
+                    last,
+                ]
             };
-            let beneficiary_charlie = InitialBeneficiary {
-                account_id: accounts.charlie,
-                multipliers: vec![(0, 100), (1, 10)],
+            let new_contract = |initial_beneficiaries, refund_address| {
+                OpenPayroll::new(
+                    2,
+                    1000,
+                    vec!["Seniority".to_string()],
+                    initial_beneficiaries,
+                    100,
+                    10,
+                    None,
+                    0,
+                    false,
+                    refund_address,
+                    None,
+                    None,
+                    None,
+                )
             };
-            let res = OpenPayroll::new(
-                2,
-                1000,
-                vec!["Seniority".to_string(), "Performance".to_string()],
-                vec![beneficiary_bob, beneficiary_charlie],
+
+            // the last beneficiary is invalid
+            let res = new_contract(
+                initial_beneficiaries(InitialBeneficiary {
+                    account_id: accounts.charlie,
+                    multipliers: vec![(0, percent(100))],
+                    start_block: None,
+                    name: Some("a".repeat(MAX_BENEFICIARY_METADATA_LENGTH + 1)),
+                    notes: None,
+                }),
+                None,
             );
-            assert!(matches!(res, Ok(_)));
-            let contract = res.unwrap();
+            assert!(matches!(res, Err(Error::InvalidParams)));
+            let res = new_contract(
+                initial_beneficiaries(InitialBeneficiary {
+                    account_id: accounts.charlie,
+                    multipliers: vec![(1, percent(100))],
+                    start_block: None,
+                    name: None,
+                    notes: None,
+                }),
+                None,
+            );
+            assert!(matches!(res, Err(Error::MultiplierNotFound)));
 
-            // check that base_multipliers are set correctly
-            let data_0 = contract.base_multipliers.get(0).unwrap();
-            let data_1 = contract.base_multipliers.get(1).unwrap();
-            assert_eq!(
-                data_0,
-                BaseMultiplier {
-                    name: "Seniority".to_string(),
-                    valid_until_block: None,
-                }
+            // the refund address is the last beneficiary
+            let res = new_contract(
+                initial_beneficiaries(InitialBeneficiary {
+                    account_id: accounts.charlie,
+                    multipliers: vec![(0, percent(100))],
+                    start_block: None,
+                    name: None,
+                    notes: None,
+                }),
+                Some(accounts.charlie),
+            );
+            assert!(matches!(res, Err(Error::InvalidRoleAssignment)));
+
+            // the multiplier names are duplicated
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Seniority".to_string()],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
             );
+            assert!(matches!(res, Err(Error::DuplicatedMultiplierName)));
+
+            let contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            assert!(!contract.is_beneficiary(accounts.bob));
+            assert!(!contract.is_beneficiary(accounts.charlie));
+            assert_eq!(contract.get_list_beneficiaries(), vec![]);
+            assert_eq!(contract.get_multipliers_list(), vec![0, 1]);
+        }
+
+        /// Test the rules of every role assignment
+        #[ink::test]
+        fn role_assignment_rules() {
+            let accounts = default_accounts();
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+            let contract_account = contract_id();
+
+            // the contract itself can't hold any role
             assert_eq!(
-                data_1,
-                BaseMultiplier {
-                    name: "Performance".to_string(),
-                    valid_until_block: None,
-                }
+                contract.add_admin(contract_account),
+                Err(Error::InvalidRoleAssignment)
             );
-
-            // check that beneficiaries are set correctly
-            let data_bob = contract.beneficiaries.get(accounts.bob).unwrap();
-            let data_charlie = contract.beneficiaries.get(accounts.charlie).unwrap();
             assert_eq!(
-                data_bob,
-                Beneficiary {
-                    account_id: accounts.bob,
-                    multipliers: vec_to_btreemap(&[(0, 100), (1, 3)]),
-                    unclaimed_payments: 0,
-                    last_updated_period_block: 0,
-                }
+                contract.propose_transfer_ownership(contract_account),
+                Err(Error::InvalidRoleAssignment)
             );
             assert_eq!(
-                data_charlie,
-                Beneficiary {
-                    account_id: accounts.charlie,
-                    multipliers: vec_to_btreemap(&[(0, 100), (1, 10)]),
-                    unclaimed_payments: 0,
-                    last_updated_period_block: 0,
-                }
+                contract.add_beneficiary(contract_account, vec![]),
+                Err(Error::InvalidRoleAssignment)
             );
-
-            // check accounts are set correctly
+            set_sender(accounts.bob);
             assert_eq!(
-                contract.beneficiaries_accounts.get(0).unwrap(),
-                &accounts.bob
+                contract.set_claim_delegate(contract_account),
+                Err(Error::InvalidRoleAssignment)
             );
             assert_eq!(
-                contract.beneficiaries_accounts.get(1).unwrap(),
-                &accounts.charlie
+                contract.set_claim_delegate(accounts.bob),
+                Err(Error::InvalidParams)
             );
+            assert_eq!(contract.set_claim_delegate(accounts.eve), Ok(()));
 
-            // check claims in period are set correctly
+            // the owner can't be an admin
+            set_sender(accounts.alice);
+            contract
+                .propose_transfer_ownership(accounts.django)
+                .unwrap();
+            set_sender(accounts.django);
+            contract.accept_ownership().unwrap();
             assert_eq!(
-                contract.claims_in_period,
-                ClaimsInPeriod {
-                    period: 0,
-                    total_claims: 0,
-                }
+                contract.add_admin(accounts.django),
+                Err(Error::InvalidRoleAssignment)
             );
-        }
+            assert_eq!(contract.add_admin(accounts.eve), Ok(()));
 
-        #[ink::test]
-        fn create_contract_with_invalid_amount_of_multipliers() {
-            let accounts = default_accounts();
-            let beneficiary_bob = InitialBeneficiary {
-                account_id: accounts.bob,
-                multipliers: vec![(0, 100), (1, 3)],
-            };
-            let beneficiary_charlie = InitialBeneficiary {
-                account_id: accounts.charlie,
-                multipliers: vec![(0, 100)],
-            };
+            // the refund address can't be a beneficiary
             let res = OpenPayroll::new(
                 2,
                 1000,
-                vec!["Seniority".to_string(), "Performance".to_string()],
-                vec![beneficiary_bob, beneficiary_charlie],
+                vec!["Seniority".to_string()],
+                vec![InitialBeneficiary {
+                    account_id: accounts.bob,
+                    multipliers: vec![(0, percent(100))],
+                    start_block: None,
+                    name: None,
+                    notes: None,
+                }],
+                100,
+                10,
+                None,
+                0,
+                false,
+                Some(accounts.bob),
+                None,
+                None,
+                None,
             );
-
-            assert!(matches!(res, Err(Error::InvalidMultipliersLength)));
-
-            let beneficiary_bob = InitialBeneficiary {
-                account_id: accounts.bob,
-                multipliers: vec![(0, 100)],
-            };
-            let beneficiary_charlie = InitialBeneficiary {
-                account_id: accounts.charlie,
-                multipliers: vec![(0, 100)],
-            };
+            assert!(matches!(res, Err(Error::InvalidRoleAssignment)));
             let res = OpenPayroll::new(
                 2,
                 1000,
-                vec!["Seniority".to_string(), "Performance".to_string()],
-                vec![beneficiary_bob, beneficiary_charlie],
+                vec![],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                Some(contract_account),
+                None,
+                None,
+                None,
             );
+            assert!(matches!(res, Err(Error::InvalidRoleAssignment)));
 
-            assert!(matches!(res, Err(Error::InvalidMultipliersLength)));
-
-            let beneficiary_bob = InitialBeneficiary {
-                account_id: accounts.bob,
-                multipliers: vec![],
-            };
-            let beneficiary_charlie = InitialBeneficiary {
-                account_id: accounts.charlie,
-                multipliers: vec![],
-            };
-            let res = OpenPayroll::new(
+            set_sender(accounts.alice);
+            let mut contract = OpenPayroll::new(
                 2,
                 1000,
-                vec!["Seniority".to_string(), "Performance".to_string()],
-                vec![beneficiary_bob, beneficiary_charlie],
+                vec![],
+                vec![],
+                100,
+                10,
+                None,
+                0,
+                false,
+                Some(accounts.frank),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(
+                contract.add_beneficiary(accounts.frank, vec![]),
+                Err(Error::InvalidRoleAssignment)
             );
+        }
 
-            assert!(matches!(res, Err(Error::InvalidMultipliersLength)));
+        /// Returning the excess needs a refund address
+        #[ink::test]
+        fn return_excess_without_refund_address() {
+            let mut contract = create_contract_with_no_beneficiaries(10_000u128);
+            contract.set_max_runway_periods(Some(1)).unwrap();
+            assert_eq!(contract.get_returnable_excess(), 10_000);
+            assert_eq!(contract.return_excess(), Err(Error::RefundAddressNotSet));
+        }
 
-            let beneficiary_bob = InitialBeneficiary {
-                account_id: accounts.bob,
-                multipliers: vec![(0, 10), (1, 3), (2, 3)],
-            };
-            let beneficiary_charlie = InitialBeneficiary {
-                account_id: accounts.charlie,
-                multipliers: vec![(0, 10), (1, 3)],
-            };
-            let res = OpenPayroll::new(
-                2,
-                1000,
-                vec![
-                    "Seniority".to_string(),
-                    "Performance".to_string(),
-                    "Years_at_company".to_string(),
-                ],
-                vec![beneficiary_bob, beneficiary_charlie],
+        /// Test the treasury getters when the debts are bigger than the balance
+        #[ink::test]
+        fn debts_bigger_than_balance() {
+            let (accounts, mut contract) = create_accounts_and_contract(1000u128);
+            advance_n_blocks(2);
+            assert_eq!(contract.get_total_debts(), 2060);
+            assert_eq!(
+                contract.get_balance_with_debts(),
+                Err(Error::ArithmeticUnderflow)
+            );
+            assert_eq!(contract.get_withdrawable_balance(), 0);
+            assert_eq!(
+                contract.withdraw_surplus(1),
+                Err(Error::NotEnoughBalanceInTreasury)
             );
 
-            assert!(matches!(res, Err(Error::InvalidMultipliersLength)));
+            // the balance is paid to the first claim
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            set_sender(accounts.charlie);
+            assert_eq!(
+                contract.claim_payment(accounts.charlie, 1030),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
         }
 
+        /// Test a beneficiary updated in a future period accrues nothing instead of panicking
         #[ink::test]
-        fn create_contract_with_duplicated_beneficiaries() {
-            let accounts = default_accounts();
-            let beneficiary_1 = InitialBeneficiary {
-                account_id: accounts.bob,
-                multipliers: vec![(0, 100), (1, 3)],
-            };
-            let beneficiary_2 = InitialBeneficiary {
-                account_id: accounts.bob,
-                multipliers: vec![(0, 100), (1, 3)],
-            };
-            let res = OpenPayroll::new(
-                2,
-                1000,
-                vec!["Seniority".to_string(), "Performance".to_string()],
-                vec![beneficiary_1, beneficiary_2],
+        fn beneficiary_updated_in_the_future() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            let mut beneficiary = contract.beneficiaries.get(accounts.bob).unwrap();
+            beneficiary.last_updated_period_block = 100;
+            beneficiary.unclaimed_payments = 10;
+            contract.beneficiaries.insert(accounts.bob, &beneficiary);
+
+            advance_n_blocks(2);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(10));
+            assert_eq!(contract.get_total_debts(), 10 + 1030);
+            assert_eq!(
+                contract.get_balance_with_debts(),
+                Ok(100_000_000u128 - 1040)
+            );
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 10).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+        }
+
+        /// Test the owner can only withdraw the balance not needed for the debts and the next period
+        #[ink::test]
+        fn withdraw_excess_balance() {
+            let (accounts, mut contract) = create_accounts_and_contract(10_000u128);
+            let django_balance = get_balance(accounts.django);
+
+            // the next period debts are 2 * 1030
+            assert_eq!(contract.get_withdrawable_balance(), 7940);
+            assert_eq!(
+                contract.withdraw_excess_balance(7941, accounts.django),
+                Err(Error::WithdrawalExceedsSafeLimit)
+            );
+            assert_eq!(
+                contract.withdraw_excess_balance(0, accounts.django),
+                Err(Error::InvalidParams)
             );
+            contract
+                .withdraw_excess_balance(7940, accounts.django)
+                .unwrap();
+            assert_eq!(get_balance(accounts.django), django_balance + 7940);
+            assert_eq!(contract.get_contract_balance(), 2060);
 
-            assert!(matches!(res, Err(Error::DuplicatedBeneficiaries)));
+            // after a period the debts up-to-date are kept too
+            advance_n_blocks(2);
+            assert_eq!(contract.get_withdrawable_balance(), 0);
+            assert_eq!(
+                contract.withdraw_excess_balance(1, accounts.django),
+                Err(Error::WithdrawalExceedsSafeLimit)
+            );
+
+            set_balance(contract_id(), 5000);
+            assert_eq!(contract.get_withdrawable_balance(), 880);
+            assert_eq!(
+                contract.withdraw_excess_balance(881, accounts.django),
+                Err(Error::WithdrawalExceedsSafeLimit)
+            );
+            contract
+                .withdraw_excess_balance(880, accounts.django)
+                .unwrap();
+            assert_eq!(contract.get_contract_balance(), 4120);
         }
 
-        /// Add a new beneficiary and check that it is added
+        /// Test the owner can withdraw exactly the surplus over the debts up-to-date
         #[ink::test]
-        fn add_beneficiary() {
-            let accounts = default_accounts();
-            set_sender(accounts.alice);
-            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+        fn withdraw_surplus() {
+            let (accounts, mut contract) = create_accounts_and_contract(10_000u128);
+            // alice is the contract account in the off-chain environment, so django becomes the owner
             contract
-                .add_beneficiary(accounts.bob, vec![(0, 200), (1, 100)])
+                .propose_transfer_ownership(accounts.django)
                 .unwrap();
+            set_sender(accounts.django);
+            contract.accept_ownership().unwrap();
+            let django_balance = get_balance(accounts.django);
+
+            // bob and charlie are owed 1030 each
+            advance_n_blocks(2);
+            assert_eq!(contract.get_balance_with_debts(), Ok(7940));
             assert_eq!(
-                contract
-                    .beneficiaries
-                    .get(accounts.bob)
-                    .unwrap()
-                    .multipliers,
-                vec_to_btreemap(&[(0, 200), (1, 100)])
+                contract.withdraw_surplus(7941),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
+            contract.withdraw_surplus(7940).unwrap();
+            assert_eq!(get_balance(accounts.django), django_balance + 7940);
+            assert_eq!(contract.get_balance_with_debts(), Ok(0));
+            assert_eq!(
+                contract.withdraw_surplus(1),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
+
+            // the debts can still be claimed
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+            set_sender(accounts.bob);
+            assert_eq!(contract.withdraw_surplus(1), Err(Error::NotOwner));
+        }
+
+        /// Test the withdrawals keep the existential deposit in the treasury
+        #[ink::test]
+        fn withdraw_keeps_existential_deposit() {
+            let (accounts, mut contract) = create_accounts_and_contract(10_000u128);
+            assert_eq!(contract.get_existential_deposit(), 0);
+
+            contract.set_existential_deposit_hint(500).unwrap();
+            assert_eq!(contract.get_existential_deposit(), 500);
+            assert_eq!(contract.get_withdrawable_balance(), 7440);
+            assert_eq!(
+                contract.withdraw_excess_balance(7441, accounts.django),
+                Err(Error::WithdrawalExceedsSafeLimit)
             );
+
+            contract.set_existential_deposit_hint(1000).unwrap();
+            assert_eq!(contract.get_withdrawable_balance(), 6940);
             contract
-                .update_beneficiary(accounts.bob, vec![(0, 200), (1, 50)])
+                .withdraw_excess_balance(6940, accounts.django)
                 .unwrap();
+            assert_eq!(contract.get_contract_balance(), 3060);
+
+            // the surplus over the debts up-to-date keeps it too
+            advance_n_blocks(2);
             assert_eq!(
-                contract
-                    .beneficiaries
-                    .get(accounts.bob)
-                    .unwrap()
-                    .multipliers,
-                vec_to_btreemap(&[(0, 200), (1, 50)])
+                contract.withdraw_surplus(1),
+                Err(Error::NotEnoughBalanceInTreasury)
             );
 
-            // check if account was added to the vector
+            set_sender(accounts.bob);
             assert_eq!(
-                contract.beneficiaries_accounts.get(0).unwrap(),
-                &accounts.bob
+                contract.set_existential_deposit_hint(0),
+                Err(Error::NotOwner)
             );
+
+            // a treasury in a PSP22 token has no existential deposit
+            set_sender(accounts.alice);
+            let (_, mut token_contract, _) = create_token_contract(1000u128);
+            token_contract.set_existential_deposit_hint(500).unwrap();
+            assert_eq!(token_contract.get_existential_deposit(), 0);
+
+            // the hint is given in the constructor
+            let contract = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![],
+                100,
+                10,
+                None,
+                700,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .expect("Cannot create contract");
+            assert_eq!(contract.get_existential_deposit(), 700);
         }
 
-        /// Add a new beneficiary and fails because the sender is not the owner
+        /// Test withdrawing excess balance without access
         #[ink::test]
-        fn add_beneficiary_without_access() {
-            let accounts = default_accounts();
-            set_sender(accounts.alice);
-            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+        fn withdraw_excess_balance_without_access() {
+            let (accounts, mut contract) = create_accounts_and_contract(10_000u128);
             set_sender(accounts.bob);
-            assert!(matches!(
-                contract.add_beneficiary(accounts.bob, vec![(0, 100), (1, 100)]),
+            assert_eq!(
+                contract.withdraw_excess_balance(1, accounts.bob),
                 Err(Error::NotOwner)
-            ));
-            // check if account was NOT added to the vector
-            assert_eq!(contract.beneficiaries_accounts.len(), 0);
+            );
         }
 
-        /// Add a new beneficiary and fails because the multiplies is 0
+        /// Test anyone can donate to the treasury unless the contract is paused
         #[ink::test]
-        fn add_beneficiary_with_no_multipliers() {
-            let accounts = default_accounts();
+        fn donate_to_treasury() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.django);
+            assert_eq!(contract.donate_to_treasury(), Err(Error::InvalidParams));
+
+            // the off-chain environment does not move the value, so the transfer is simulated
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(700);
+            set_balance(contract_id(), 100_000_000u128 + 700);
+            let events_before = ink::env::test::recorded_events().count();
+            contract.donate_to_treasury().unwrap();
+            assert_eq!(ink::env::test::recorded_events().count(), events_before + 1);
+            assert_eq!(contract.get_contract_balance(), 100_000_000u128 + 700);
+
             set_sender(accounts.alice);
-            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
-            assert!(matches!(
-                contract.add_beneficiary(accounts.bob, vec![]),
-                Ok(_)
-            ));
+            contract.pause().unwrap();
+            set_sender(accounts.django);
+            assert_eq!(contract.donate_to_treasury(), Err(Error::ContractIsPaused));
+        }
+
+        /// Test a bonus is claimable on top of the periodic payments
+        #[ink::test]
+        fn add_bonus() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(1);
+            contract
+                .add_bonus(accounts.bob, 500, "Release".to_string())
+                .unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(500));
+
+            // the periodic payments keep accruing as before
+            advance_n_blocks(1);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1530));
+            assert_eq!(contract.get_amount_to_claim(accounts.charlie), Ok(1030));
+
+            let bob_balance_before_payment = get_balance(accounts.bob);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1530).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance_before_payment + 1530);
+        }
+
+        /// Test adding a bonus fails without access, balance or a valid beneficiary
+        #[ink::test]
+        fn add_bonus_errors() {
+            let (accounts, mut contract) = create_accounts_and_contract(10_000u128);
+            assert_eq!(
+                contract.add_bonus(accounts.bob, 0, "Release".to_string()),
+                Err(Error::InvalidParams)
+            );
+            assert_eq!(
+                contract.add_bonus(accounts.django, 500, "Release".to_string()),
+                Err(Error::AccountNotFound)
+            );
+            // bob and charlie are owed 2 * 1030 after a period
+            advance_n_blocks(2);
+            assert_eq!(
+                contract.add_bonus(accounts.bob, 10_000 - 2 * 1030 + 1, "Release".to_string()),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
+            contract
+                .add_bonus(accounts.bob, 10_000 - 2 * 1030, "Release".to_string())
+                .unwrap();
+
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.add_bonus(accounts.bob, 500, "Release".to_string()),
+                Err(Error::NotOwner)
+            );
         }
 
-        /// Remove a beneficiary and check that it is removed
+        /// Test a bonus added to a beneficiary whose unclaimed payments expired does not expire with them
         #[ink::test]
-        fn remove_beneficiary() {
+        fn add_bonus_after_expiry() {
             let accounts = default_accounts();
             set_sender(accounts.alice);
-            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+            set_balance(contract_id(), 100_000_000u128);
+            let mut contract = create_contract_with_expiry(&accounts, Some(1)).unwrap();
+
+            // bob leaves 1000 unclaimed at block 2, they expire after block 6
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 30).unwrap();
+            advance_n_blocks(6);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(3 * 1030));
+
+            // the bonus is claimable on top of the periods since the last update
+            set_sender(accounts.alice);
             contract
-                .add_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
+                .add_bonus(accounts.bob, 500, "Release".to_string())
                 .unwrap();
-            assert_eq!(contract.beneficiaries_accounts.len(), 1);
             assert_eq!(
-                contract.beneficiaries_accounts.get(0).unwrap(),
-                &accounts.bob
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(500 + 3 * 1030)
             );
+
+            // the bonus left unclaimed never expires
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 3 * 1030).unwrap();
+            advance_n_blocks(6);
+            assert_eq!(
+                contract.get_amount_to_claim(accounts.bob),
+                Ok(500 + 3 * 1030)
+            );
+            let bob_balance = get_balance(accounts.bob);
+            contract
+                .claim_payment(accounts.bob, 500 + 3 * 1030)
+                .unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + 500 + 3 * 1030);
             assert_eq!(
                 contract
-                    .beneficiaries
-                    .get(accounts.bob)
+                    .get_beneficiary(accounts.bob)
                     .unwrap()
-                    .multipliers,
-                vec_to_btreemap(&[(0, 100), (1, 20)])
+                    .unclaimed_bonuses,
+                0
             );
-            contract.remove_beneficiary(accounts.bob).unwrap();
-            assert!(!contract.beneficiaries.contains(accounts.bob));
-            // check if account was removed from the vector
-            assert_eq!(contract.beneficiaries_accounts.len(), 0);
         }
 
-        /// Remove a beneficiary and fails because the sender is not the owner
+        /// Test a bonus is claimable mid-period, counted in the debts and kept in the history
         #[ink::test]
-        fn remove_beneficiary_without_access() {
-            let accounts = default_accounts();
-            set_sender(accounts.alice);
-            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
+        fn bonus_history() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(3);
             contract
-                .add_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
+                .add_bonus(accounts.bob, 500, "Release".to_string())
                 .unwrap();
-            set_sender(accounts.bob);
-            assert!(matches!(
-                contract.remove_beneficiary(accounts.bob),
-                Err(Error::NotOwner)
-            ));
-            assert_eq!(contract.beneficiaries_accounts.len(), 1);
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1530));
+            assert_eq!(contract.get_total_debts(), 1530 + 1030);
+
+            // the reason is bounded
             assert_eq!(
-                contract.beneficiaries_accounts.get(0).unwrap(),
-                &accounts.bob
+                contract.add_bonus(accounts.bob, 500, "a".repeat(MAX_BONUS_REASON_LENGTH + 1)),
+                Err(Error::InvalidParams)
+            );
+
+            // older bonuses are evicted
+            for _ in 0..BONUS_HISTORY_CAPACITY {
+                contract
+                    .add_bonus(accounts.bob, 1, "Referral".to_string())
+                    .unwrap();
+            }
+            let records = contract.get_bonus_history(accounts.bob).unwrap();
+            assert_eq!(records.len(), BONUS_HISTORY_CAPACITY);
+            assert_eq!(
+                records[0],
+                BonusRecord {
+                    added_at_block: 3,
+                    amount: 1,
+                    reason: "Referral".to_string(),
+                }
             );
+            assert_eq!(contract.get_bonus_history(accounts.charlie), Ok(vec![]));
+
+            // bob claims the bonuses mid-period
+            set_sender(accounts.bob);
+            let amount = 1530 + BONUS_HISTORY_CAPACITY as Balance;
+            contract.claim_payment(accounts.bob, amount).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+            assert_eq!(contract.get_total_debts(), 1030);
         }
 
-        /// Remove a beneficiary and fails because the beneficiary does not exist
+        /// Test a claim delegate can claim on behalf of the beneficiary until revoked
         #[ink::test]
-        fn remove_beneficiary_not_found() {
-            let accounts = default_accounts();
-            set_sender(accounts.alice);
-            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
-            assert!(matches!(
-                contract.remove_beneficiary(accounts.bob),
+        fn claim_payment_by_delegate() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+            let bob_balance_before_payment = get_balance(accounts.bob);
+
+            // a non delegate non beneficiary is rejected
+            set_sender(accounts.django);
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 1000),
+                Err(Error::NotAllowedToClaim)
+            );
+            assert_eq!(
+                contract.set_claim_delegate(accounts.eve),
                 Err(Error::AccountNotFound)
-            ));
-        }
+            );
 
-        /// Update the base payment and check that it is updated
-        #[ink::test]
-        fn update_base_payment_in_initial_block() {
-            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
-            contract.update_base_payment(200_000_000u128).unwrap();
-            assert_eq!(contract.base_payment, 200_000_000u128);
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.set_claim_delegate(accounts.bob),
+                Err(Error::InvalidParams)
+            );
+            contract.set_claim_delegate(accounts.django).unwrap();
+            assert_eq!(
+                contract.get_claim_delegate(accounts.bob),
+                Ok(Some(accounts.django))
+            );
+
+            // the delegate claims and the payment goes to the beneficiary
+            set_sender(accounts.django);
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance_before_payment + 1000);
+            assert_eq!(
+                contract.claim_payment(accounts.charlie, 1000),
+                Err(Error::NotAllowedToClaim)
+            );
+
+            // a revoked delegate cannot claim anymore
+            set_sender(accounts.bob);
+            contract.revoke_claim_delegate().unwrap();
+            assert_eq!(
+                contract.revoke_claim_delegate(),
+                Err(Error::AccountNotFound)
+            );
+            assert_eq!(contract.get_claim_delegate(accounts.bob), Ok(None));
+            set_sender(accounts.django);
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 30),
+                Err(Error::NotAllowedToClaim)
+            );
         }
 
-        /// Update the base payment and check that it is updated
+        /// Test the payments go to the payout address of the beneficiary while it is set
         #[ink::test]
-        fn update_base_payment() {
-            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+        fn claim_payment_to_payout_address() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(2);
+            let bob_balance = get_balance(accounts.bob);
+            let eve_balance = get_balance(accounts.eve);
 
-            advance_n_blocks(1);
+            set_sender(accounts.django);
+            assert_eq!(
+                contract.set_payout_address(Some(accounts.eve)),
+                Err(Error::AccountNotFound)
+            );
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.set_payout_address(Some(contract_id())),
+                Err(Error::InvalidRoleAssignment)
+            );
 
-            contract.update_base_payment(200_000_000u128).unwrap();
-            assert_eq!(contract.base_payment, 200_000_000u128);
+            // the payout address gets the payment, the accounting stays with the beneficiary
+            contract.set_payout_address(Some(accounts.eve)).unwrap();
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            assert_eq!(get_balance(accounts.eve), eve_balance + 1000);
+            assert_eq!(get_balance(accounts.bob), bob_balance);
+            assert_eq!(contract.get_total_claimed(accounts.bob), Ok(1000));
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(30));
+
+            // once cleared the payments go back to the beneficiary
+            contract.set_payout_address(None).unwrap();
+            assert_eq!(
+                contract
+                    .get_beneficiary(accounts.bob)
+                    .unwrap()
+                    .payout_override,
+                None
+            );
+            contract.claim_payment(accounts.bob, 30).unwrap();
+            assert_eq!(get_balance(accounts.bob), bob_balance + 30);
+            assert_eq!(get_balance(accounts.eve), eve_balance + 1000);
         }
 
+        /// Test the periods settled by partial claims are paid once nothing is left unclaimed
         #[ink::test]
-        fn update_base_payment_error() {
-            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+        fn was_period_paid_with_partial_claims() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.bob);
+            assert_eq!(contract.was_period_paid(accounts.bob, 0), Ok(false));
 
-            advance_n_blocks(3);
+            // period [0, 2) is settled but only partially paid
+            advance_n_blocks(2);
+            contract.claim_payment(accounts.bob, 500).unwrap();
+            assert_eq!(
+                contract.get_claim_records(accounts.bob),
+                Ok(vec![ClaimRecord {
+                    claimed_at_block: 2,
+                    amount: 500,
+                    covered_periods: (0, 2),
+                    unclaimed_after: 530,
+                }])
+            );
+            assert_eq!(contract.was_period_paid(accounts.bob, 0), Ok(false));
 
-            assert!(matches!(
-                contract.update_base_payment(200_000_000u128),
-                Err(Error::NotAllClaimedInPeriod)
-            ));
+            // the remainder is paid in the next period, which is settled partially
+            advance_n_blocks(2);
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            assert_eq!(contract.was_period_paid(accounts.bob, 0), Ok(false));
+            contract.claim_payment(accounts.bob, 560).unwrap();
+            assert_eq!(contract.was_period_paid(accounts.bob, 0), Ok(true));
+            assert_eq!(contract.was_period_paid(accounts.bob, 2), Ok(true));
+            assert_eq!(contract.was_period_paid(accounts.bob, 4), Ok(false));
+            assert_eq!(
+                contract.was_period_paid(accounts.django, 0),
+                Err(Error::AccountNotFound)
+            );
         }
 
-        /// Update the base payment but fails because the sender is not the owner
+        /// Test a beneficiary that skipped periods and caught up in a single claim
         #[ink::test]
-        fn update_base_payment_without_access() {
+        fn was_period_paid_after_catching_up() {
             let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
             set_sender(accounts.bob);
-            assert!(matches!(
-                contract.update_base_payment(200_000_000u128),
-                Err(Error::NotOwner)
-            ));
-        }
 
-        /// Update the base payment but fails because the base payment is 0
-        #[ink::test]
-        fn update_base_payment_invalid_base_payment() {
-            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
-            assert!(matches!(
-                contract.update_base_payment(0u128),
-                Err(Error::InvalidParams)
-            ));
-        }
+            // a zero claim in between only updates the unclaimed payments and is not recorded
+            advance_n_blocks(2);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            assert!(contract.get_claim_records(accounts.bob).unwrap().is_empty());
 
-        /// Update the periodicity and check that it is updated
-        #[ink::test]
-        fn update_periodicity() {
-            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
-            contract.update_periodicity(100u32).unwrap();
-            assert_eq!(contract.periodicity, 100u32);
+            advance_n_blocks(4);
+            contract.claim_payment(accounts.bob, 3090).unwrap();
+            let records = contract.get_claim_records(accounts.bob).unwrap();
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0].covered_periods, (2, 6));
+
+            assert_eq!(contract.was_period_paid(accounts.bob, 2), Ok(true));
+            assert_eq!(contract.was_period_paid(accounts.bob, 4), Ok(true));
+            assert_eq!(contract.was_period_paid(accounts.bob, 6), Ok(false));
+            // the zero claim is not in the history, so the first period is outside it
+            assert_eq!(
+                contract.was_period_paid(accounts.bob, 0),
+                Err(Error::PeriodOutsideHistory)
+            );
         }
 
-        /// Update the periodicity but fails because the sender is not the owner
+        /// Test the oldest claims are evicted from the history
         #[ink::test]
-        fn update_periodicity_without_access() {
+        fn claim_history_eviction() {
             let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
             set_sender(accounts.bob);
-            assert!(matches!(
-                contract.update_periodicity(100u32),
-                Err(Error::NotOwner)
-            ));
+            for _ in 0..CLAIM_HISTORY_CAPACITY + 1 {
+                advance_n_blocks(2);
+                contract.claim_payment(accounts.bob, 1030).unwrap();
+            }
+
+            let records = contract.get_claim_records(accounts.bob).unwrap();
+            assert_eq!(records.len(), CLAIM_HISTORY_CAPACITY as usize);
+            assert_eq!(records[0].covered_periods, (2, 4));
+            assert_eq!(
+                contract.was_period_paid(accounts.bob, 0),
+                Err(Error::PeriodOutsideHistory)
+            );
+            assert_eq!(contract.was_period_paid(accounts.bob, 2), Ok(true));
         }
 
-        /// Update the periodicity but fails because the periodicity is 0
+        /// Test the claim history with a custom capacity and the total claimed
         #[ink::test]
-        fn update_periodicity_invalid_periodicity() {
-            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
-
-            assert!(matches!(
-                contract.update_periodicity(0u32),
+        fn claim_history_with_capacity() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(
+                contract.get_claim_history_capacity(),
+                CLAIM_HISTORY_CAPACITY
+            );
+            assert_eq!(
+                contract.set_claim_history_capacity(0),
                 Err(Error::InvalidParams)
-            ));
+            );
+            assert_eq!(
+                contract.set_claim_history_capacity(MAX_CLAIM_HISTORY_CAPACITY + 1),
+                Err(Error::InvalidParams)
+            );
+            contract.set_claim_history_capacity(3).unwrap();
+
+            set_sender(accounts.bob);
+            assert_eq!(contract.set_claim_history_capacity(5), Err(Error::NotOwner));
+            for block in [2, 4, 6, 8] {
+                advance_n_blocks(2);
+                contract.claim_payment(accounts.bob, block * 10).unwrap();
+            }
+            assert_eq!(
+                contract.get_claim_history(accounts.bob),
+                Ok(vec![(4, 40), (6, 60), (8, 80)])
+            );
+            assert_eq!(contract.get_total_claimed(accounts.bob), Ok(200));
+
+            // zero claims do not pollute the history but still update the period
+            advance_n_blocks(2);
+            contract.claim_payment(accounts.bob, 0).unwrap();
+            assert_eq!(contract.get_claim_history(accounts.bob).unwrap().len(), 3);
+            assert_eq!(
+                contract
+                    .get_beneficiary(accounts.bob)
+                    .unwrap()
+                    .last_updated_period_block,
+                10
+            );
+            assert_eq!(
+                contract.get_claimed_accounts_in_period(),
+                vec![accounts.bob]
+            );
+
+            // lowering the capacity evicts the oldest claims on the next claim
+            set_sender(accounts.alice);
+            contract.set_claim_history_capacity(1).unwrap();
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 100).unwrap();
+            assert_eq!(
+                contract.get_claim_history(accounts.bob),
+                Ok(vec![(10, 100)])
+            );
+            assert_eq!(contract.get_total_claimed(accounts.bob), Ok(300));
+
+            assert_eq!(contract.get_claim_history(accounts.charlie), Ok(vec![]));
+            assert_eq!(contract.get_total_claimed(accounts.charlie), Ok(0));
+            assert_eq!(
+                contract.get_claim_history(accounts.django),
+                Err(Error::AccountNotFound)
+            );
         }
 
-        /// Test pausing and unpausing the contract
+        /// Test the beneficiaries with outstanding payments differ from the unclaimed beneficiaries
         #[ink::test]
-        fn pause_and_resume() {
-            let starting_block = get_current_block();
-            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
+        fn beneficiaries_with_outstanding() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(contract.get_beneficiaries_with_outstanding(), Ok(vec![]));
+            advance_n_blocks(2);
 
-            contract.pause().unwrap();
-            assert!(contract.is_paused());
-            advance_n_blocks(1);
-            contract.resume().unwrap();
-            assert!(!contract.is_paused());
-            // check for the starting block to be the same
-            assert_eq!(contract.initial_block, starting_block);
+            // a partial claim leaves bob with outstanding payments but claimed in the period
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            assert_eq!(
+                contract.get_unclaimed_beneficiaries(),
+                vec![accounts.charlie]
+            );
+            assert_eq!(
+                contract.get_beneficiaries_with_outstanding(),
+                Ok(vec![(accounts.bob, 30), (accounts.charlie, 1030)])
+            );
+
+            // a zero claim does the same for charlie
+            set_sender(accounts.charlie);
+            contract.claim_payment(accounts.charlie, 0).unwrap();
+            assert_eq!(contract.get_unclaimed_beneficiaries(), vec![]);
+            assert_eq!(contract.get_count_of_unclaim_beneficiaries(), 0);
+            assert_eq!(
+                contract.get_beneficiaries_with_outstanding(),
+                Ok(vec![(accounts.bob, 30), (accounts.charlie, 1030)])
+            );
+
+            // both are unclaimed again after the period rollover
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 30).unwrap();
+            advance_n_blocks(2);
+            assert_eq!(
+                contract.get_unclaimed_beneficiaries(),
+                vec![accounts.bob, accounts.charlie]
+            );
+            assert_eq!(
+                contract.get_beneficiaries_with_outstanding(),
+                Ok(vec![(accounts.bob, 1030), (accounts.charlie, 2060)])
+            );
         }
 
-        /// Test pausing and resuming without access
+        /// Test the total paid out sums the claims of every beneficiary
         #[ink::test]
-        fn pause_and_resume_without_access() {
+        fn total_paid_out() {
             let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(contract.get_total_paid_out(), 0);
+            advance_n_blocks(2);
             set_sender(accounts.bob);
-            assert!(matches!(contract.pause(), Err(Error::NotOwner)));
-            assert!(matches!(contract.resume(), Err(Error::NotOwner)));
+            contract.claim_payment(accounts.bob, 1000).unwrap();
+            set_sender(accounts.charlie);
+            contract.claim_payment(accounts.charlie, 1030).unwrap();
+            assert_eq!(contract.get_total_paid_out(), 2030);
+
+            // a zero claim pays nothing and a settlement is paid out
+            contract.claim_payment(accounts.charlie, 0).unwrap();
+            set_sender(accounts.alice);
+            contract.remove_beneficiary(accounts.bob, false).unwrap();
+            assert_eq!(contract.get_total_paid_out(), 2060);
         }
 
-        /// Test claiming a payment
+        /// Test the total claimed accumulates partial claims across periods and updates
         #[ink::test]
-        fn claim_payment() {
+        fn total_claimed_with_partial_claims() {
             let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(contract.get_total_claimed(accounts.bob), Ok(0));
+            assert_eq!(
+                contract.get_total_claimed(accounts.django),
+                Err(Error::AccountNotFound)
+            );
+
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 300).unwrap();
+            contract.claim_payment(accounts.bob, 200).unwrap();
+            assert_eq!(contract.get_total_claimed(accounts.bob), Ok(500));
+
+            // an update keeps the total
+            set_sender(accounts.alice);
             contract
-                .update_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
+                .update_beneficiary(accounts.bob, vec![(0, percent(100))], None)
                 .unwrap();
-            // advance 3 blocks so a payment will be claimable
-            advance_n_blocks(3);
+            assert_eq!(contract.get_total_claimed(accounts.bob), Ok(500));
 
-            let contract_balance_before_payment = get_balance(contract.owner);
-            let bob_balance_before_payment = get_balance(accounts.bob);
+            // the rest of the first period and the second one
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1530).unwrap();
+            assert_eq!(contract.get_total_claimed(accounts.bob), Ok(2030));
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+        }
+
+        /// Test setting the max per claim without access
+        #[ink::test]
+        fn set_max_per_claim_without_access() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
             set_sender(accounts.bob);
-
-            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
-            contract
-                .claim_payment(accounts.bob, amount_to_claim)
-                .unwrap();
-            assert!(get_balance(contract.owner) < contract_balance_before_payment);
-            assert!(get_balance(accounts.bob) > bob_balance_before_payment);
+            assert_eq!(
+                contract.set_max_per_claim(accounts.bob, Some(500)),
+                Err(Error::NotOwner)
+            );
         }
 
-        /// Test claiming a payment
+        /// Test granting back-pay by moving the accrual start of a beneficiary
         #[ink::test]
-        fn claim_parcial_payment() {
-            let total_amount = 100_000_000u128;
-            let total_not_claimed = 10;
-            let (accounts, mut contract) = create_accounts_and_contract(total_amount);
+        fn set_beneficiary_accrual_start() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(6);
             contract
-                .update_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
+                .add_beneficiary(accounts.django, vec![(0, percent(100)), (1, percent(3))])
                 .unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.django), Ok(0));
 
-            // advance 3 blocks so a payment will be claimable
-            advance_n_blocks(3);
-
-            let bob_balance_before_payment = get_balance(accounts.bob);
-            set_sender(accounts.bob);
-
-            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            // two periods of back-pay
             contract
-                .claim_payment(accounts.bob, amount_to_claim - total_not_claimed)
+                .set_beneficiary_accrual_start(accounts.django, 2)
                 .unwrap();
-            assert!(
-                get_balance(contract.owner) == total_amount - amount_to_claim + total_not_claimed
+            assert_eq!(contract.get_amount_to_claim(accounts.django), Ok(2060));
+
+            // future, misaligned or unknown
+            assert_eq!(
+                contract.set_beneficiary_accrual_start(accounts.django, 8),
+                Err(Error::InvalidParams)
             );
-            assert!(
-                get_balance(accounts.bob)
-                    == bob_balance_before_payment + amount_to_claim - total_not_claimed
+            assert_eq!(
+                contract.set_beneficiary_accrual_start(accounts.django, 3),
+                Err(Error::InvalidParams)
             );
-            assert!(
-                contract
-                    .beneficiaries
-                    .get(accounts.bob)
-                    .unwrap()
-                    .unclaimed_payments
-                    == total_not_claimed
+            assert_eq!(
+                contract.set_beneficiary_accrual_start(accounts.eve, 2),
+                Err(Error::AccountNotFound)
+            );
+
+            set_sender(accounts.django);
+            contract.claim_payment(accounts.django, 2060).unwrap();
+            assert_eq!(
+                contract.set_beneficiary_accrual_start(accounts.django, 0),
+                Err(Error::NotOwner)
             );
         }
 
-        /// Test claiming a payment
+        /// The periods already snapshotted in the unclaimed payments can't be paid again by back-dating
         #[ink::test]
-        fn claim_more_payment() {
-            let total_amount = 100_000_000u128;
-            let (accounts, mut contract) = create_accounts_and_contract(total_amount);
-            contract
-                .update_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
-                .unwrap();
+        fn set_beneficiary_accrual_start_does_not_pay_unclaimed_periods_twice() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(4);
 
-            // advance 3 blocks so a payment will be claimable
-            advance_n_blocks(3);
+            // the two periods of bob are moved to his unclaimed payments
+            contract.suspend_beneficiary(accounts.bob).unwrap();
+            contract.unsuspend_beneficiary(accounts.bob).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2060));
 
-            let bob_balance_before_payment = get_balance(accounts.bob);
-            set_sender(accounts.bob);
+            assert_eq!(
+                contract.set_beneficiary_accrual_start(accounts.bob, 0),
+                Err(Error::InvalidParams)
+            );
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2060));
 
-            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
-            let res = contract.claim_payment(accounts.bob, amount_to_claim + 1);
+            // from the last update on nothing is paid twice
+            contract
+                .set_beneficiary_accrual_start(accounts.bob, 4)
+                .unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(2060));
+        }
 
+        /// Test suspending and unsuspending without access
+        #[ink::test]
+        fn suspend_and_unsuspend_beneficiary_without_access() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.bob);
             assert!(matches!(
-                res,
-                Err(Error::ClaimedAmountIsBiggerThanAvailable)
+                contract.suspend_beneficiary(accounts.bob),
+                Err(Error::NotOwner)
+            ));
+            assert!(matches!(
+                contract.unsuspend_beneficiary(accounts.bob),
+                Err(Error::NotOwner)
             ));
-            assert!(get_balance(contract.owner) == total_amount);
-            assert!(get_balance(accounts.bob) == bob_balance_before_payment);
         }
 
-        /// Error when trying to update periodicity with some payments not claimed
+        /// Test a paused group can't claim while the other groups claim normally
         #[ink::test]
-        fn update_periodicity_without_all_payments_updated() {
+        fn pause_and_resume_group() {
             let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
             contract
-                .update_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
+                .set_beneficiary_group(accounts.bob, Some(1))
                 .unwrap();
-
-            // advance 3 blocks so a payment will be claimable
-            advance_n_blocks(3);
-
-            let res = contract.update_periodicity(10u32);
-            assert!(matches!(res, Err(Error::NotAllClaimedInPeriod)));
-        }
-
-        ///  update periodicity with all payments claimed with the param amount in 0 in the claim_payment
-        #[ink::test]
-        fn update_periodicity_with_all_payments_updated() {
-            let accounts = default_accounts();
-            set_sender(accounts.alice);
-            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
             contract
-                .add_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
+                .set_beneficiary_group(accounts.charlie, Some(2))
                 .unwrap();
-            // advance 3 blocks so a payment will be claimable
-            advance_n_blocks(3);
+            assert_eq!(contract.get_group_members(1), vec![accounts.bob]);
+            assert_eq!(contract.get_group_members(2), vec![accounts.charlie]);
+            assert_eq!(contract.get_group_members(3), vec![]);
 
-            // When you claim a payment with 0 amount, it will calculate the amount to claim an set it to unclaim payments.
-            contract.claim_payment(accounts.bob, 0).unwrap();
+            contract.pause_group(1).unwrap();
+            assert!(contract.is_group_paused(1));
+            assert!(!contract.is_group_paused(2));
 
-            let res = contract.update_periodicity(10u32);
+            advance_n_blocks(2);
+            assert_eq!(contract.get_group_total_debts(1), 1030);
+            assert_eq!(contract.get_group_total_debts(2), 1030);
 
-            assert!(matches!(res, Ok(())));
-        }
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 1030),
+                Err(Error::GroupIsPaused)
+            );
+            set_sender(accounts.charlie);
+            contract.claim_payment(accounts.charlie, 1030).unwrap();
+            assert_eq!(contract.get_group_total_debts(2), 0);
 
-        /// update periodicity with all payments claimed
-        #[ink::test]
-        fn update_periodicity_with_all_payments_claimed() {
-            let accounts = default_accounts();
+            // the global pause still overrides the groups
             set_sender(accounts.alice);
-            let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
-            contract
-                .add_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
-                .unwrap();
-            // advance 3 blocks so a payment will be claimable
-            advance_n_blocks(3);
+            contract.resume_group(1).unwrap();
+            contract.pause().unwrap();
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 1030),
+                Err(Error::ContractIsPaused)
+            );
 
+            set_sender(accounts.alice);
+            contract.resume().unwrap();
             set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+            assert_eq!(contract.get_group_total_debts(1), 0);
 
-            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
+            // a beneficiary removed from its group is not affected by its pause
+            set_sender(accounts.alice);
+            contract.pause_group(2).unwrap();
             contract
-                .claim_payment(accounts.bob, amount_to_claim)
+                .set_beneficiary_group(accounts.charlie, None)
                 .unwrap();
+            assert_eq!(contract.get_group_members(2), vec![]);
+            advance_n_blocks(2);
+            set_sender(accounts.charlie);
+            contract.claim_payment(accounts.charlie, 1030).unwrap();
+        }
 
-            set_sender(accounts.alice);
-            let res = contract.update_periodicity(10u32);
-
-            assert_eq!(res, Ok(()));
+        /// Test managing the groups without access
+        #[ink::test]
+        fn pause_and_resume_group_without_access() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            set_sender(accounts.bob);
+            assert_eq!(contract.pause_group(1), Err(Error::NotOwner));
+            assert_eq!(contract.resume_group(1), Err(Error::NotOwner));
+            assert_eq!(
+                contract.set_beneficiary_group(accounts.bob, Some(1)),
+                Err(Error::NotOwner)
+            );
         }
 
-        /// test if error when trying to update base payment with some payments not claimed
+        /// Test the beneficiaries are listed by pages
         #[ink::test]
-        fn update_base_payment_without_all_payments_updated() {
-            let accounts = default_accounts();
-            set_sender(accounts.alice);
+        fn get_list_beneficiaries_paged() {
             let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
-            contract
-                .add_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
-                .unwrap();
-            // advance 3 blocks so a payment will be claimable
-            advance_n_blocks(3);
+            let mut beneficiaries = Vec::new();
+            // clear of the default accounts, the contract account can't be a beneficiary
+            for i in 0..60u8 {
+                let account_id = AccountId::from([100 + i; 32]);
+                contract
+                    .add_beneficiary(account_id, vec![(0, percent(100))])
+                    .unwrap();
+                beneficiaries.push(account_id);
+            }
 
-            let res = contract.update_base_payment(900);
+            // first page, capped at MAX_PAGE_SIZE
+            assert_eq!(
+                contract.get_list_beneficiaries_paged(0, 10),
+                beneficiaries[..10]
+            );
+            assert_eq!(
+                contract.get_list_beneficiaries_paged(0, 100),
+                beneficiaries[..50]
+            );
 
-            assert!(matches!(res, Err(Error::NotAllClaimedInPeriod)));
+            // last partial page
+            assert_eq!(
+                contract.get_list_beneficiaries_paged(50, 20),
+                beneficiaries[50..]
+            );
+
+            // past the end
+            assert_eq!(contract.get_list_beneficiaries_paged(60, 10), vec![]);
+            assert_eq!(contract.get_list_beneficiaries_paged(u32::MAX, 10), vec![]);
+            assert_eq!(contract.get_list_beneficiaries(), beneficiaries);
         }
 
-        /// test if you can update a base payment with all payments claimed
+        /// Test the beneficiaries are read with their data by pages
         #[ink::test]
-        fn update_base_payment_with_all_payments_claimed() {
-            let accounts = default_accounts();
-            set_sender(accounts.alice);
+        fn get_beneficiaries_page() {
             let mut contract = create_contract_with_no_beneficiaries(100_000_000u128);
-            contract
-                .add_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
-                .unwrap();
-            // advance 3 blocks so a payment will be claimable
-            advance_n_blocks(3);
+            let mut beneficiaries = Vec::new();
+            // clear of the default accounts, the contract account can't be a beneficiary
+            for i in 0..60u8 {
+                let account_id = AccountId::from([100 + i; 32]);
+                contract
+                    .add_beneficiary(account_id, vec![(0, percent(100))])
+                    .unwrap();
+                beneficiaries.push(contract.get_beneficiary(account_id).unwrap());
+            }
 
-            set_sender(accounts.bob);
-            let amount_to_claim = contract.get_amount_to_claim(accounts.bob).unwrap();
-            contract
-                .claim_payment(accounts.bob, amount_to_claim)
-                .unwrap();
+            // first page, capped at MAX_PAGE_SIZE
+            assert_eq!(
+                contract.get_beneficiaries_page(0, 10).unwrap(),
+                beneficiaries[..10]
+            );
+            assert_eq!(
+                contract.get_beneficiaries_page(0, 100).unwrap(),
+                beneficiaries[..50]
+            );
 
-            set_sender(accounts.alice);
-            let res = contract.update_base_payment(900);
+            // last partial page
+            assert_eq!(
+                contract.get_beneficiaries_page(50, 20).unwrap(),
+                beneficiaries[50..]
+            );
 
-            assert_eq!(res, Ok(()));
+            // single item pages
+            assert_eq!(
+                contract.get_beneficiaries_page(0, 1).unwrap(),
+                beneficiaries[..1]
+            );
+            assert_eq!(
+                contract.get_beneficiaries_page(59, 1).unwrap(),
+                beneficiaries[59..]
+            );
+
+            // past the end
+            assert_eq!(contract.get_beneficiaries_page(60, 10).unwrap(), vec![]);
+            assert_eq!(
+                contract.get_beneficiaries_page(u32::MAX, 10).unwrap(),
+                vec![]
+            );
         }
 
-        // test if beneficiaries are ok in the contract
+        /// Check the getters of the configuration return the values given to the constructor
         #[ink::test]
-        fn create_contract_with_beneficiaries_ok() {
+        fn check_configuration_getters() {
             let (accounts, contract) = create_accounts_and_contract(100_000_000u128);
-
-            assert_eq!(contract.beneficiaries_accounts.len(), 2);
-            assert!(contract.beneficiaries.contains(accounts.bob));
-            assert!(contract.beneficiaries.contains(accounts.charlie));
+            assert_eq!(contract.get_owner(), Some(accounts.alice));
+            assert_eq!(contract.get_periodicity(), 2);
+            assert_eq!(contract.get_base_payment(), 1000);
+            assert!(!contract.is_paused());
+            assert_eq!(contract.get_paused_at(), None);
         }
 
-        // check for beneficiaries after updating it
+        /// Check the contract info matches the individual getters
         #[ink::test]
-        fn update_benefiaries_created_in_create_contract() {
-            let total_balance = 100_000_000u128;
-            let (accounts, mut contract) = create_accounts_and_contract(total_balance);
+        fn check_contract_info() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            let info = contract.get_contract_info();
+            assert_eq!(info.owner, contract.get_owner());
+            assert_eq!(info.owner, Some(accounts.alice));
+            assert_eq!(info.proposed_owner, None);
+            assert_eq!(info.periodicity, contract.get_periodicity());
+            assert_eq!(info.base_payment, contract.get_base_payment());
+            assert_eq!(info.initial_block, contract.get_initial_block());
+            assert_eq!(info.paused_block_at, contract.get_paused_at());
+            assert_eq!(info.paused_block_at, None);
+            assert_eq!(
+                info.beneficiary_count,
+                contract.get_list_beneficiaries().len() as u32
+            );
+            assert_eq!(info.beneficiary_count, 2);
+            assert_eq!(
+                info.multiplier_count,
+                contract.get_multipliers_list().len() as u32
+            );
+            assert_eq!(
+                info.current_period_initial_block,
+                contract.get_current_period_initial_block()
+            );
 
-            contract
-                .update_beneficiary(accounts.bob, vec![(0, 100), (1, 20)])
-                .unwrap();
+            advance_n_blocks(3);
+            contract.pause().unwrap();
+            contract.propose_transfer_ownership(accounts.bob).unwrap();
+            let info = contract.get_contract_info();
+            assert_eq!(info.paused_block_at, Some(3));
+            assert_eq!(info.proposed_owner, Some(accounts.bob));
+            assert_eq!(info.current_period_initial_block, 2);
+        }
 
-            //check if multipliers are ok
+        /// Check the balances are formatted as decimal strings
+        #[ink::test]
+        fn check_contract_info_display() {
+            let (_, mut contract) = create_accounts_and_contract(100_000_000u128);
             assert_eq!(
-                contract
-                    .beneficiaries
-                    .get(accounts.bob)
-                    .unwrap()
-                    .multipliers,
-                vec_to_btreemap(&[(0, 100), (1, 20)])
+                contract.get_contract_info_display(),
+                ContractInfoDisplay {
+                    base_payment: "1000".to_string(),
+                }
             );
+            contract.update_base_payment((1 << 53) + 1).unwrap();
             assert_eq!(
-                contract
-                    .beneficiaries
-                    .get(accounts.charlie)
-                    .unwrap()
-                    .multipliers,
-                vec_to_btreemap(&[(0, 100), (1, 3)])
+                contract.get_contract_info_display().base_payment,
+                "9007199254740993"
+            );
+
+            assert_eq!(balance_to_decimal_string(0), "0");
+            assert_eq!(balance_to_decimal_string(10), "10");
+            assert_eq!(
+                balance_to_decimal_string(Balance::MAX),
+                "340282366920938463463374607431768211455"
             );
         }
 
-        // Delete a multiplier
+        /// Check only the owner can update the code and migrate the storage
         #[ink::test]
-        fn check_deactivate_multiplier() {
-            let total_balance = 100_000_000u128;
-            let (_, mut contract) = create_accounts_and_contract(total_balance);
-
-            advance_n_blocks(6);
+        fn set_code_and_migrate() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            let code_hash = Hash::from([0x01; 32]);
+            set_sender(accounts.bob);
+            assert_eq!(contract.set_code(code_hash), Err(Error::NotOwner));
+            assert_eq!(contract.migrate(), Err(Error::NotOwner));
+            set_sender(accounts.alice);
+            contract.set_code(code_hash).unwrap();
 
-            let res = contract.deactivate_multiplier(1);
+            // a new contract is already at the version of the code
+            assert_eq!(contract.get_version(), CONTRACT_VERSION);
+            assert_eq!(contract.migrate(), Err(Error::MigrationFailed));
 
-            advance_n_blocks(5);
+            // the storage of an older version is migrated only once
+            contract.contract_version = CONTRACT_VERSION - 1;
+            contract.migrate().unwrap();
+            assert_eq!(contract.get_version(), CONTRACT_VERSION);
+            assert_eq!(contract.migrate(), Err(Error::MigrationFailed));
+        }
 
-            assert_eq!(res, Ok(()));
+        /// Check the contract returns the embedded build id
+        #[ink::test]
+        fn check_build_id() {
+            let (_, contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(contract.get_build_id(), BUILD_ID);
+            if option_env!("OPEN_PAYROLL_BUILD_ID").is_none() {
+                assert_eq!(contract.get_build_id(), [0; 32]);
+            }
+        }
 
-            let multiplier_0 = contract.base_multipliers.get(0).unwrap();
-            let multiplier_1 = contract.base_multipliers.get(1).unwrap();
-            assert_eq!(multiplier_1.valid_until_block.unwrap(), 8);
-            assert_eq!(multiplier_0.valid_until_block, None);
+        /// Check the build id is parsed from hex characters
+        #[ink::test]
+        fn check_parse_build_id() {
+            let mut expected = [0u8; 32];
+            expected[0] = 0xab;
+            expected[31] = 0x0f;
+            assert_eq!(
+                parse_build_id("AB0000000000000000000000000000000000000000000000000000000000000f"),
+                expected
+            );
         }
 
         // Check current block period
@@ -1962,6 +10008,34 @@ mod open_payroll {
             assert_eq!(current_block_period, 9);
         }
 
+        // Check the period number of a block matches the period initial block
+        #[ink::test]
+        fn check_period_number() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            advance_n_blocks(5);
+            let contract = create_contract_with_no_beneficiaries_periodicity(100_000_000u128, 3);
+            assert_eq!(contract.get_initial_block(), 5);
+            assert_eq!(contract.get_period_number(4), Err(Error::InvalidParams));
+            assert_eq!(contract.get_period_number(5), Ok(0));
+            assert_eq!(contract.get_period_number(7), Ok(0));
+            assert_eq!(contract.get_period_number(8), Ok(1));
+
+            for _ in 0..4 {
+                advance_n_blocks(3);
+                let current_block = contract.get_current_period_initial_block();
+                let period_number = contract.get_period_number(current_block).unwrap();
+                assert_eq!(
+                    period_number * contract.get_periodicity() + contract.get_initial_block(),
+                    current_block
+                );
+                assert_eq!(
+                    contract.get_period_number(current_block + 2),
+                    Ok(period_number)
+                );
+            }
+        }
+
         // Check the fn next_block_period
         #[ink::test]
         fn check_next_block_period() {
@@ -2062,7 +10136,7 @@ mod open_payroll {
             let (_, contract) = create_accounts_and_contract(100_000_001u128);
             let total_debts = contract.get_total_debts();
             assert_eq!(total_debts, 0);
-            assert_eq!(contract.get_balance_with_debts(), total_balance);
+            assert_eq!(contract.get_balance_with_debts(), Ok(total_balance));
         }
 
         /// Test 2 readonly function related with total debts and balance
@@ -2087,7 +10161,59 @@ mod open_payroll {
             // check if the balance with debts is correct (total_balance - total_debts)
             assert_eq!(
                 contract.get_balance_with_debts(),
-                total_balance - (bob_amount_claim + charlie_amount_claim)
+                Ok(total_balance - (bob_amount_claim + charlie_amount_claim))
+            );
+        }
+
+        /// Test the debts of each beneficiary add up to the total debts
+        #[ink::test]
+        fn check_total_debt_breakdown() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_000u128);
+            advance_n_blocks(4);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 2060).unwrap();
+
+            let breakdown = contract.get_total_debt_breakdown().unwrap();
+            assert_eq!(
+                breakdown,
+                vec![
+                    (
+                        accounts.bob,
+                        contract.get_amount_to_claim(accounts.bob).unwrap()
+                    ),
+                    (
+                        accounts.charlie,
+                        contract.get_amount_to_claim(accounts.charlie).unwrap()
+                    ),
+                ]
+            );
+            assert_eq!(breakdown, vec![(accounts.bob, 0), (accounts.charlie, 2060)]);
+            assert_eq!(
+                breakdown.iter().map(|(_, amount)| amount).sum::<Balance>(),
+                contract.get_total_debts()
+            );
+        }
+
+        /// Check the simulated payments match the amounts to claim and the solvency of the treasury
+        #[ink::test]
+        fn check_simulate_period_payment() {
+            let (accounts, mut contract) = create_accounts_and_contract(3000);
+            advance_n_blocks(2);
+            assert_eq!(
+                contract.simulate_period_payment(),
+                Ok((vec![(accounts.bob, 1030), (accounts.charlie, 1030)], true))
+            );
+            // nothing changed
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(1030));
+            assert_eq!(contract.get_total_debts(), 2060);
+
+            // the treasury can not pay the next period
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 2060).unwrap();
+            assert_eq!(
+                contract.simulate_period_payment(),
+                Ok((vec![(accounts.bob, 0), (accounts.charlie, 2060)], false))
             );
         }
 
@@ -2147,8 +10273,9 @@ mod open_payroll {
             let mut contract = create_contract_with_no_beneficiaries(100_000_001u128);
             let max_beneficiaries = 100u8;
 
+            // clear of the default accounts, the contract account can't be a beneficiary
             for u8_number in 0..max_beneficiaries {
-                let arr_of_32: [u8; 32] = [u8_number; 32];
+                let arr_of_32: [u8; 32] = [100 + u8_number; 32];
                 contract
                     .add_beneficiary(AccountId::from(arr_of_32), vec![])
                     .unwrap();
@@ -2182,7 +10309,7 @@ mod open_payroll {
             // check no transfered ownership was called yet
             assert_eq!(contract.proposed_owner, None);
             // check if owner is alice
-            assert_eq!(contract.owner, accounts.alice);
+            assert_eq!(contract.owner, Some(accounts.alice));
 
             // change owner to bob
             set_sender(accounts.alice);
@@ -2197,10 +10324,69 @@ mod open_payroll {
             let accept_ownsership_result = contract.accept_ownership();
             assert!(accept_ownsership_result.is_ok());
 
-            assert_eq!(contract.owner, accounts.bob);
+            assert_eq!(contract.owner, Some(accounts.bob));
             assert_eq!(contract.proposed_owner, None);
         }
 
+        // Test a cancelled ownership transfer can not be accepted
+        #[ink::test]
+        fn cancel_ownership_transfer() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_001u128);
+            assert_eq!(
+                contract.cancel_ownership_transfer(),
+                Err(Error::NoPendingOwnershipTransfer)
+            );
+            contract.propose_transfer_ownership(accounts.bob).unwrap();
+            assert_eq!(contract.get_pending_owner(), Some(accounts.bob));
+
+            // only the owner can cancel it
+            set_sender(accounts.bob);
+            assert_eq!(contract.cancel_ownership_transfer(), Err(Error::NotOwner));
+            set_sender(accounts.alice);
+            contract.cancel_ownership_transfer().unwrap();
+            assert_eq!(contract.get_pending_owner(), None);
+
+            set_sender(accounts.bob);
+            assert_eq!(contract.accept_ownership(), Err(Error::NotOwner));
+            assert_eq!(contract.get_owner(), Some(accounts.alice));
+        }
+
+        // Test a renounced contract has no owner and keeps paying the beneficiaries
+        #[ink::test]
+        fn renounce_ownership() {
+            let (accounts, mut contract) = create_accounts_and_contract(100_000_001u128);
+            contract.add_admin(accounts.eve).unwrap();
+            contract.propose_transfer_ownership(accounts.bob).unwrap();
+            set_sender(accounts.bob);
+            assert_eq!(contract.renounce_ownership(), Err(Error::NotOwner));
+
+            set_sender(accounts.alice);
+            contract.renounce_ownership().unwrap();
+            assert_eq!(contract.get_owner(), None);
+            assert_eq!(contract.get_pending_owner(), None);
+            assert!(!contract.is_admin(accounts.alice));
+
+            // the pending transfer is cancelled and the owner only messages fail
+            set_sender(accounts.bob);
+            assert_eq!(contract.accept_ownership(), Err(Error::NotOwner));
+            set_sender(accounts.alice);
+            assert_eq!(contract.renounce_ownership(), Err(Error::NotOwner));
+            assert_eq!(contract.update_base_payment(2000), Err(Error::NotOwner));
+            assert_eq!(
+                contract.propose_transfer_ownership(accounts.alice),
+                Err(Error::NotOwner)
+            );
+
+            // the admins keep their rights and the claims go on
+            set_sender(accounts.eve);
+            contract.pause().unwrap();
+            contract.resume().unwrap();
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+            assert_eq!(contract.get_amount_to_claim(accounts.bob), Ok(0));
+        }
+
         // Check if dispatch error when adding more beneficiaries allowed from creation
         #[ink::test]
         fn check_max_beneficiaries_from_creation() {
@@ -2213,6 +10399,9 @@ mod open_payroll {
                 let beneficiary = InitialBeneficiary {
                     account_id: AccountId::from(arr_of_32),
                     multipliers: vec![],
+                    start_block: None,
+                    name: None,
+                    notes: None,
                 };
                 beneficiaries.push(beneficiary);
             }
@@ -2222,6 +10411,15 @@ mod open_payroll {
                 1000,
                 vec!["Seniority".to_string(), "Performance".to_string()],
                 beneficiaries,
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
             );
 
             assert!(matches!(res, Err(Error::MaxBeneficiariesExceeded)));
@@ -2241,9 +10439,26 @@ mod open_payroll {
             let beneficiary = InitialBeneficiary {
                 account_id: AccountId::from([1; 32]),
                 multipliers: vec![],
+                start_block: None,
+                name: None,
+                notes: None,
             };
 
-            let res = OpenPayroll::new(2, 1000, multipliers, vec![beneficiary]);
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                multipliers,
+                vec![beneficiary],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
 
             assert!(matches!(res, Err(Error::MaxMultipliersExceeded)));
         }
@@ -2265,5 +10480,971 @@ mod open_payroll {
 
             assert!(matches!(res, Err(Error::MaxMultipliersExceeded)));
         }
+
+        // Check the maximums passed to the constructor are validated
+        #[ink::test]
+        fn create_contract_with_invalid_maximums() {
+            for (max_beneficiaries, max_multipliers) in [(0, 10), (1001, 10), (100, 0), (100, 51)] {
+                let res = OpenPayroll::new(
+                    2,
+                    1000,
+                    vec!["Seniority".to_string()],
+                    vec![],
+                    max_beneficiaries,
+                    max_multipliers,
+                    None,
+                    0,
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                );
+                assert!(matches!(res, Err(Error::InvalidParams)));
+            }
+
+            let res = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![],
+                1000,
+                50,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            );
+            assert!(matches!(res, Ok(_)));
+        }
+
+        // Check the maximums passed to the constructor are enforced
+        #[ink::test]
+        fn check_custom_maximums() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![],
+                1,
+                2,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+
+            contract
+                .add_beneficiary(accounts.bob, vec![(0, percent(100))])
+                .unwrap();
+            let res = contract.add_beneficiary(accounts.charlie, vec![(0, percent(100))]);
+            assert!(matches!(res, Err(Error::MaxBeneficiariesExceeded)));
+
+            contract
+                .add_base_multiplier("Performance".to_string())
+                .unwrap();
+            let res = contract.add_base_multiplier("max+1".to_string());
+            assert!(matches!(res, Err(Error::MaxMultipliersExceeded)));
+        }
+
+        // Check a custom maximum of beneficiaries is exposed and enforced
+        #[ink::test]
+        fn check_custom_max_beneficiaries() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let mut contract = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string()],
+                vec![],
+                3,
+                5,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+            assert_eq!(contract.get_max_beneficiaries(), 3);
+            assert_eq!(contract.get_max_multipliers(), 5);
+
+            for account_id in [accounts.bob, accounts.charlie, accounts.django] {
+                contract
+                    .add_beneficiary(account_id, vec![(0, percent(100))])
+                    .unwrap();
+            }
+            let res = contract.add_beneficiary(accounts.eve, vec![(0, percent(100))]);
+            assert!(matches!(res, Err(Error::MaxBeneficiariesExceeded)));
+        }
+
+        // Check the maximum of multipliers per beneficiary is enforced below the maximum of multipliers
+        #[ink::test]
+        fn check_max_multipliers_per_beneficiary() {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            set_balance(contract_id(), 100_000_000u128);
+            let new_with_max = |max, initial_beneficiaries| {
+                OpenPayroll::new(
+                    2,
+                    1000,
+                    vec!["A".to_string(), "B".to_string(), "C".to_string()],
+                    initial_beneficiaries,
+                    100,
+                    5,
+                    None,
+                    0,
+                    false,
+                    None,
+                    None,
+                    None,
+                    max,
+                )
+            };
+            for max in [0, 6] {
+                assert_eq!(
+                    new_with_max(Some(max), vec![]).err(),
+                    Some(Error::InvalidParams)
+                );
+            }
+            assert_eq!(
+                new_with_max(None, vec![])
+                    .unwrap()
+                    .get_max_multipliers_per_beneficiary(),
+                5
+            );
+            let too_many = vec![(0, percent(10)), (1, percent(10)), (2, percent(10))];
+            assert_eq!(
+                new_with_max(
+                    Some(2),
+                    vec![InitialBeneficiary {
+                        account_id: accounts.bob,
+                        multipliers: too_many.clone(),
+                        start_block: None,
+                        name: None,
+                        notes: None,
+                    }]
+                )
+                .err(),
+                Some(Error::MaxMultipliersPerBeneficiaryExceeded)
+            );
+
+            let mut contract = new_with_max(Some(2), vec![]).unwrap();
+            assert_eq!(contract.get_max_multipliers_per_beneficiary(), 2);
+            let at_max = vec![(0, percent(10)), (1, percent(10))];
+            contract
+                .add_beneficiary(accounts.bob, at_max.clone())
+                .unwrap();
+            assert_eq!(
+                contract.add_beneficiary(accounts.charlie, too_many.clone()),
+                Err(Error::MaxMultipliersPerBeneficiaryExceeded)
+            );
+            assert_eq!(
+                contract.update_beneficiary(accounts.bob, too_many.clone(), None),
+                Err(Error::MaxMultipliersPerBeneficiaryExceeded)
+            );
+            assert_eq!(
+                contract.simulate_salary(too_many),
+                Err(Error::MaxMultipliersPerBeneficiaryExceeded)
+            );
+            contract
+                .update_beneficiary(accounts.bob, at_max, None)
+                .unwrap();
+        }
+
+        fn create_token_contract(
+            token_balance: Balance,
+        ) -> (DefaultAccounts<DefaultEnvironment>, OpenPayroll, AccountId) {
+            let accounts = default_accounts();
+            set_sender(accounts.alice);
+            let token = AccountId::from([0x42; 32]);
+            psp22_mock::set_balance(token, contract_id(), token_balance);
+            let beneficiary_bob = InitialBeneficiary {
+                account_id: accounts.bob,
+                multipliers: vec![(0, percent(100)), (1, percent(3))],
+                start_block: None,
+                name: None,
+                notes: None,
+            };
+            let contract = OpenPayroll::new(
+                2,
+                1000,
+                vec!["Seniority".to_string(), "Performance".to_string()],
+                vec![beneficiary_bob],
+                100,
+                10,
+                Some(token),
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+            .expect("Cannot create contract");
+            (accounts, contract, token)
+        }
+
+        /// Claim a payment in a PSP22 token
+        #[ink::test]
+        fn claim_payment_in_token() {
+            let (accounts, mut contract, token) = create_token_contract(100_000u128);
+            assert_eq!(contract.get_token(), Some(token));
+            let native_balance_before_payment = get_balance(accounts.bob);
+
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            contract.claim_payment(accounts.bob, 1030).unwrap();
+
+            assert_eq!(psp22_mock::balance_of(token, accounts.bob), 1030);
+            assert_eq!(contract.get_contract_balance(), 100_000 - 1030);
+            // the native balance is not touched
+            assert_eq!(get_balance(accounts.bob), native_balance_before_payment);
+        }
+
+        /// Claim a payment in a PSP22 token without enough tokens in the treasury
+        #[ink::test]
+        fn claim_payment_in_token_without_balance() {
+            let (accounts, mut contract, token) = create_token_contract(1000u128);
+
+            advance_n_blocks(2);
+            set_sender(accounts.bob);
+            assert_eq!(
+                contract.claim_payment(accounts.bob, 1030),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
+            assert_eq!(psp22_mock::balance_of(token, accounts.bob), 0);
+
+            // a failing token transfer is reported as TokenTransferFailed
+            assert_eq!(
+                contract._transfer(accounts.bob, 1030),
+                Err(Error::TokenTransferFailed)
+            );
+        }
+
+        /// Check the treasury balance uses the token balance
+        #[ink::test]
+        fn check_token_treasury_balance() {
+            let (_accounts, contract, token) = create_token_contract(5000u128);
+            assert_eq!(contract.get_token(), Some(token));
+            assert_eq!(contract.get_contract_balance(), 5000);
+
+            advance_n_blocks(2);
+            assert_eq!(contract.get_balance_with_debts(), Ok(5000 - 1030));
+
+            // a contract without token keeps using the native balance
+            let (_, native_contract) = create_accounts_and_contract(100_000_000u128);
+            assert_eq!(native_contract.get_token(), None);
+            assert_eq!(native_contract.get_contract_balance(), 100_000_000u128);
+        }
+
+        /// Golden vectors of the payments computed by the contract
+        /// Each case is a payroll with a single beneficiary, a timeline of steps and the amount available at each claim.
+        /// The expected amounts are checked against both the contract and a reference model in plain Rust,
+        /// so any change to the payments has to update the vectors deliberately
+        mod pricing_vectors {
+            use super::*;
+
+            /// A step in the timeline of a case
+            enum Step {
+                /// Advance a number of blocks
+                Advance(u32),
+                /// Claim an amount, or everything if None, expecting the amount available before the claim
+                Claim {
+                    amount: Option<Balance>,
+                    available: Balance,
+                },
+                /// Deactivate a base multiplier
+                DeactivateMultiplier(MultiplierId),
+                /// Update the base payment
+                UpdateBasePayment(Balance),
+            }
+
+            /// A payroll configuration and its timeline
+            /// The beneficiary has one multiplier per base multiplier, in basis points
+            struct PricingCase {
+                name: &'static str,
+                periodicity: u32,
+                base_payment: Balance,
+                multipliers: &'static [u128],
+                steps: &'static [Step],
+            }
+
+            const CASES: &[PricingCase] = &[
+                PricingCase {
+                    name: "accrual",
+                    periodicity: 2,
+                    base_payment: 1000,
+                    multipliers: &[10_000, 300],
+                    steps: &[
+                        Step::Advance(2),
+                        Step::Claim {
+                            amount: None,
+                            available: 1030,
+                        },
+                        Step::Advance(5),
+                        Step::Claim {
+                            amount: Some(60),
+                            available: 2060,
+                        },
+                        Step::Advance(1),
+                        Step::Claim {
+                            amount: None,
+                            available: 3030,
+                        },
+                    ],
+                },
+                PricingCase {
+                    name: "rounding",
+                    periodicity: 3,
+                    base_payment: 333,
+                    multipliers: &[3333],
+                    steps: &[
+                        Step::Advance(3),
+                        Step::Claim {
+                            amount: None,
+                            available: 110,
+                        },
+                        Step::Advance(9),
+                        Step::Claim {
+                            amount: None,
+                            available: 330,
+                        },
+                    ],
+                },
+                PricingCase {
+                    name: "multiplier_expiry",
+                    periodicity: 2,
+                    base_payment: 1000,
+                    multipliers: &[10_000, 5000],
+                    steps: &[
+                        Step::Advance(3),
+                        Step::DeactivateMultiplier(1),
+                        Step::Advance(5),
+                        Step::Claim {
+                            amount: None,
+                            available: 5000,
+                        },
+                        Step::Advance(2),
+                        Step::Claim {
+                            amount: None,
+                            available: 1000,
+                        },
+                    ],
+                },
+                PricingCase {
+                    name: "base_payment_update",
+                    periodicity: 2,
+                    base_payment: 1000,
+                    multipliers: &[10_000],
+                    steps: &[
+                        Step::Advance(2),
+                        Step::Claim {
+                            amount: None,
+                            available: 1000,
+                        },
+                        Step::UpdateBasePayment(2000),
+                        Step::Advance(4),
+                        Step::Claim {
+                            amount: Some(1000),
+                            available: 4000,
+                        },
+                        Step::Advance(2),
+                        Step::Claim {
+                            amount: None,
+                            available: 5000,
+                        },
+                    ],
+                },
+            ];
+
+            fn get_case(name: &str) -> &'static PricingCase {
+                CASES
+                    .iter()
+                    .find(|case| case.name == name)
+                    .expect("Unknown pricing case")
+            }
+
+            /// Reference model of the payments of a single beneficiary, with blocks relative to the initial block
+            struct Model {
+                periodicity: u32,
+                base_payment: Balance,
+                // (value in basis points, valid until block)
+                multipliers: Vec<(u128, Option<u32>)>,
+                now: u32,
+                last_updated_period_block: u32,
+                unclaimed_payments: Balance,
+            }
+
+            impl Model {
+                fn new(case: &PricingCase) -> Self {
+                    Self {
+                        periodicity: case.periodicity,
+                        base_payment: case.base_payment,
+                        multipliers: case.multipliers.iter().map(|bps| (*bps, None)).collect(),
+                        now: 0,
+                        last_updated_period_block: 0,
+                        unclaimed_payments: 0,
+                    }
+                }
+
+                fn period_block(&self) -> u32 {
+                    self.now - self.now % self.periodicity
+                }
+
+                // Every elapsed period is paid with the multipliers valid in it, 1% if there are none
+                fn available(&self) -> Balance {
+                    let mut total = self.unclaimed_payments;
+                    let mut period = self.last_updated_period_block;
+                    while period < self.period_block() {
+                        let valid: Vec<u128> = self
+                            .multipliers
+                            .iter()
+                            .filter(|(_, valid_until)| match valid_until {
+                                Some(valid_until_block) => period < *valid_until_block,
+                                None => true,
+                            })
+                            .map(|(bps, _)| *bps)
+                            .collect();
+                        let bps: u128 = if valid.is_empty() {
+                            100
+                        } else {
+                            valid.iter().sum()
+                        };
+                        total += bps * self.base_payment / 10_000;
+                        period += self.periodicity;
+                    }
+                    total
+                }
+
+                fn run(&mut self, step: &Step) -> Option<Balance> {
+                    match step {
+                        Step::Advance(blocks) => self.now += blocks,
+                        Step::Claim { amount, .. } => {
+                            let available = self.available();
+                            self.unclaimed_payments = available - amount.unwrap_or(available);
+                            self.last_updated_period_block = self.period_block();
+                            return Some(available);
+                        }
+                        Step::DeactivateMultiplier(multiplier_id) => {
+                            self.multipliers[*multiplier_id as usize].1 =
+                                Some(self.period_block() + self.periodicity);
+                        }
+                        Step::UpdateBasePayment(base_payment) => {
+                            self.base_payment = *base_payment;
+                        }
+                    }
+                    None
+                }
+            }
+
+            // Runs a case against the contract, checking the available amount at every claim
+            fn run_case(name: &str) {
+                let case = get_case(name);
+                let accounts = default_accounts();
+                set_sender(accounts.alice);
+                set_balance(contract_id(), 100_000_000u128);
+                let beneficiary = InitialBeneficiary {
+                    account_id: accounts.bob,
+                    multipliers: case
+                        .multipliers
+                        .iter()
+                        .enumerate()
+                        .map(|(id, bps)| (id as MultiplierId, MultiplierValue::from_bps(*bps)))
+                        .collect(),
+                    start_block: None,
+                    name: None,
+                    notes: None,
+                };
+                let mut contract = OpenPayroll::new(
+                    case.periodicity,
+                    case.base_payment,
+                    (0..case.multipliers.len())
+                        .map(|id| format!("Multiplier {}", id))
+                        .collect(),
+                    vec![beneficiary],
+                    100,
+                    10,
+                    None,
+                    0,
+                    false,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .expect("Cannot create contract");
+
+                for step in case.steps {
+                    match step {
+                        Step::Advance(blocks) => advance_n_blocks(*blocks),
+                        Step::Claim { amount, available } => {
+                            assert_eq!(
+                                contract.get_amount_to_claim(accounts.bob),
+                                Ok(*available),
+                                "{}",
+                                case.name
+                            );
+                            set_sender(accounts.bob);
+                            contract
+                                .claim_payment(accounts.bob, amount.unwrap_or(*available))
+                                .unwrap();
+                            set_sender(accounts.alice);
+                        }
+                        Step::DeactivateMultiplier(multiplier_id) => {
+                            contract.deactivate_multiplier(*multiplier_id).unwrap()
+                        }
+                        Step::UpdateBasePayment(base_payment) => {
+                            contract.update_base_payment(*base_payment).unwrap()
+                        }
+                    }
+                }
+            }
+
+            /// Check the reference model produces the vectors
+            #[test]
+            fn reference_model_matches_vectors() {
+                for case in CASES {
+                    let mut model = Model::new(case);
+                    for step in case.steps {
+                        if let (
+                            Some(available),
+                            Step::Claim {
+                                available: expected,
+                                ..
+                            },
+                        ) = (model.run(step), step)
+                        {
+                            assert_eq!(available, *expected, "{}", case.name);
+                        }
+                    }
+                }
+            }
+
+            #[ink::test]
+            fn accrual() {
+                run_case("accrual");
+            }
+
+            #[ink::test]
+            fn rounding() {
+                run_case("rounding");
+            }
+
+            #[ink::test]
+            fn multiplier_expiry() {
+                run_case("multiplier_expiry");
+            }
+
+            #[ink::test]
+            fn base_payment_update() {
+                run_case("base_payment_update");
+            }
+        }
+
+        /// Check the constructors and messages keep their selectors and labels,
+        /// clients built from the metadata of a previous version call them by selector
+        #[ink::test]
+        fn dispatchable_selectors_are_stable() {
+            macro_rules! assert_constructor {
+                ($selector:literal, $label:literal) => {
+                    assert_eq!(
+                        <OpenPayroll as ink::reflect::DispatchableConstructorInfo<$selector>>::LABEL,
+                        $label
+                    );
+                };
+            }
+            macro_rules! assert_message {
+                ($selector:literal, $label:literal) => {
+                    assert_eq!(
+                        <OpenPayroll as ink::reflect::DispatchableMessageInfo<$selector>>::LABEL,
+                        $label
+                    );
+                };
+            }
+            assert_constructor!(0x9BAE9D5E, "new");
+            assert_constructor!(0x06E25277, "new_time_based");
+            assert_message!(0x05AC0C19, "claim_payment");
+            assert_message!(0x3B69CAF1, "push_payment");
+            assert_message!(0xA1919547, "claim_leaving");
+            assert_message!(0x381B1590, "claim_all");
+            assert_message!(0x29B2765C, "top_up");
+            assert_message!(0x529D1F3E, "donate_to_treasury");
+            assert_message!(0xA5651C6F, "withdraw_excess_balance");
+            assert_message!(0x802B6B8D, "withdraw_surplus");
+            assert_message!(0x5EA31021, "return_excess");
+            assert_message!(0xC4F6486C, "deactivate_multiplier");
+            assert_message!(0x56E04C9A, "schedule_multiplier_deactivation");
+            assert_message!(0x3EC45EC5, "cancel_multiplier_deactivation");
+            assert_message!(0xEDC66345, "reactivate_multiplier");
+            assert_message!(0xAAFC7985, "delete_unused_multiplier");
+            assert_message!(0x1FE6D1D3, "propose_transfer_ownership");
+            assert_message!(0xB55BE9F0, "accept_ownership");
+            assert_message!(0x07144662, "cancel_ownership_transfer");
+            assert_message!(0x8C90065B, "renounce_ownership");
+            assert_message!(0x694FB50F, "set_code");
+            assert_message!(0x060D3F50, "migrate");
+            assert_message!(0xDE3B11F5, "add_beneficiary");
+            assert_message!(0x56A3644F, "add_beneficiary_with_metadata");
+            assert_message!(0xE5533F1B, "add_beneficiary_with_start");
+            assert_message!(0xE09F27DE, "bulk_add_beneficiaries");
+            assert_message!(0x7E28D5C8, "update_beneficiary");
+            assert_message!(0xB56826BC, "update_beneficiary_metadata");
+            assert_message!(0x1619C647, "deactivate_beneficiary");
+            assert_message!(0x393FB7A5, "remove_beneficiary");
+            assert_message!(0xFAF4520A, "remove_and_settle_beneficiary");
+            assert_message!(0xDC726B16, "add_bonus");
+            assert_message!(0x1BC39F25, "set_claim_delegate");
+            assert_message!(0xAD42C4D1, "revoke_claim_delegate");
+            assert_message!(0xC8DB9B86, "set_payout_address");
+            assert_message!(0x9CDEB3E7, "update_my_address");
+            assert_message!(0xBCAD56D8, "set_claim_history_capacity");
+            assert_message!(0x39262B95, "set_max_per_claim");
+            assert_message!(0x4209F757, "set_beneficiary_group");
+            assert_message!(0xA831F0AD, "set_beneficiary_accrual_start");
+            assert_message!(0x55F84BF4, "suspend_beneficiary");
+            assert_message!(0x3A6A9E88, "unsuspend_beneficiary");
+            assert_message!(0xB8E6F2B9, "update_base_payment");
+            assert_message!(0xFCACA6E7, "add_base_multiplier");
+            assert_message!(0xD0BC71CA, "replace_multiplier");
+            assert_message!(0x3ADBBA8E, "set_multiplier_sponsor");
+            assert_message!(0x9A3F8ED3, "rename_base_multiplier");
+            assert_message!(0x2DD6FC58, "update_base_multiplier_name");
+            assert_message!(0xE2CE5212, "schedule_multiplier_update");
+            assert_message!(0xDF00C916, "process_scheduled_multiplier_updates");
+            assert_message!(0x3BCB4184, "get_scheduled_multiplier_updates");
+            assert_message!(0xA552C910, "update_periodicity");
+            assert_message!(0xEC29582D, "set_max_runway_periods");
+            assert_message!(0xB26A4B9B, "set_end_block");
+            assert_message!(0xFD90E2C7, "set_period_budget");
+            assert_message!(0xEBDFB49F, "set_existential_deposit_hint");
+            assert_message!(0x8FB4AC4F, "set_target_block_time");
+            assert_message!(0x81E0C604, "pause");
+            assert_message!(0xD8F926C1, "pause_for");
+            assert_message!(0xC3A8F39E, "resume");
+            assert_message!(0x4F9CAF21, "pause_group");
+            assert_message!(0x228CD214, "resume_group");
+            assert_message!(0x561367C2, "add_admin");
+            assert_message!(0xC0F5E751, "remove_admin");
+            assert_message!(0xEC4F0F62, "ensure_all_payments_uptodate");
+            assert_message!(0x827ED2DD, "is_all_claimed_in_period");
+            assert_message!(0x8D3C9EC3, "get_claims_in_period");
+            assert_message!(0x72AD154D, "get_claimed_accounts_in_period");
+            assert_message!(0xFA7D505B, "is_paused");
+            assert_message!(0x7DA61DA9, "get_lifecycle_state");
+            assert_message!(0xDEF4CC8F, "get_paused_at");
+            assert_message!(0x520B8717, "get_resume_at");
+            assert_message!(0x6B937A2B, "get_pause_history");
+            assert_message!(0x943D93C8, "get_total_paused_blocks");
+            assert_message!(0xE44A436F, "is_group_paused");
+            assert_message!(0xE17D8336, "get_claimable_periods_count");
+            assert_message!(0x1EBBAD2A, "get_amount_to_claim");
+            assert_message!(0x020CBA38, "get_amount_to_claim_at_block");
+            assert_message!(0x9510A8C5, "get_beneficiary");
+            assert_message!(0x90F5F9D8, "is_beneficiary");
+            assert_message!(0x32737003, "is_active_multiplier");
+            assert_message!(0x55EFDBA9, "get_current_period_initial_block");
+            assert_message!(0x70305427, "get_period_number");
+            assert_message!(0x8D4BC80B, "get_next_block_period");
+            assert_message!(0x9B16BC1F, "get_total_debts");
+            assert_message!(0x582D74CE, "get_total_debt_breakdown");
+            assert_message!(0x6A9F6DB0, "simulate_period_payment");
+            assert_message!(0x8063ECE5, "get_group_total_debts");
+            assert_message!(0x58DCD4F2, "simulate_salary");
+            assert_message!(0x73B8E09D, "get_total_debt_for_next_period");
+            assert_message!(0xE39B3B27, "get_total_debt_with_unclaimed_for_next_period");
+            assert_message!(0x4F5A10DB, "get_total_debts_at_block");
+            assert_message!(0x263B0523, "get_list_beneficiaries");
+            assert_message!(0xECB149C2, "get_group_members");
+            assert_message!(0x54AC41A8, "get_list_beneficiaries_paged");
+            assert_message!(0xB75C43E0, "get_beneficiaries_page");
+            assert_message!(0xBE15A422, "get_contract_balance");
+            assert_message!(0x14D608F2, "get_token");
+            assert_message!(0x9306D52F, "get_balance_with_debts");
+            assert_message!(0xDA019452, "get_liquidity_runway");
+            assert_message!(0x7BD5A33A, "get_funding_required_for_n_periods");
+            assert_message!(0xB1091783, "get_returnable_excess");
+            assert_message!(0x0100A63E, "get_withdrawable_balance");
+            assert_message!(0x3920E773, "get_existential_deposit");
+            assert_message!(0x355E9DF0, "get_unclaimed_beneficiaries");
+            assert_message!(0x0022C1C7, "get_count_of_unclaim_beneficiaries");
+            assert_message!(0x48B772BA, "get_beneficiaries_with_outstanding");
+            assert_message!(0xF56917B6, "get_max_beneficiaries");
+            assert_message!(0xC4537742, "get_max_multipliers");
+            assert_message!(0x0A1E6F01, "get_max_multipliers_per_beneficiary");
+            assert_message!(0x2A07E2D7, "get_base_payment");
+            assert_message!(0x4F24DCA2, "get_periodicity");
+            assert_message!(0xA8783CFF, "get_initial_block");
+            assert_message!(0x3ADDDB72, "get_multipliers_list");
+            assert_message!(0xF7765F14, "get_base_multiplier");
+            assert_message!(0xB937B65E, "get_multiplier");
+            assert_message!(0x43D058B5, "get_multiplier_id_by_name");
+            assert_message!(0x0CC1C102, "get_all_base_multipliers");
+            assert_message!(0x1B6DF326, "get_claim_records");
+            assert_message!(0x242F0BB4, "get_pruned_multipliers");
+            assert_message!(0x87AA5392, "get_scheduled_beneficiary_update");
+            assert_message!(0xE0A0F67D, "get_bonus_history");
+            assert_message!(0x561B1AE3, "get_claim_history");
+            assert_message!(0x450C5BA8, "get_total_paid_out");
+            assert_message!(0x14646AAC, "get_total_claimed");
+            assert_message!(0x520E1501, "get_claim_history_capacity");
+            assert_message!(0xE524D7CD, "was_period_paid");
+            assert_message!(0xADBEC938, "get_claim_delegate");
+            assert_message!(0x37795167, "get_estimated_next_period_timestamp");
+            assert_message!(0x6D186DE2, "get_target_block_time");
+            assert_message!(0x68BAB409, "get_max_runway_periods");
+            assert_message!(0x9FF60673, "get_refund_address");
+            assert_message!(0xBC6CF427, "get_unclaimed_expiry_periods");
+            assert_message!(0xE6455062, "get_period_budget");
+            assert_message!(0x32A5FD54, "get_end_block");
+            assert_message!(0xB23ABFE0, "is_private");
+            assert_message!(0x3DB635C5, "is_time_based");
+            assert_message!(0x07FCD0B1, "get_owner");
+            assert_message!(0x78A510CE, "get_pending_owner");
+            assert_message!(0x57F2226D, "get_contract_info");
+            assert_message!(0x7ADCCECF, "get_contract_info_display");
+            assert_message!(0x0C1A1D77, "get_version");
+            assert_message!(0xF1133F9C, "get_build_id");
+            assert_message!(0x660C375A, "is_admin");
+            assert_eq!(
+                <OpenPayroll as ink::reflect::ContractAmountDispatchables>::CONSTRUCTORS,
+                2
+            );
+            assert_eq!(
+                <OpenPayroll as ink::reflect::ContractAmountDispatchables>::MESSAGES,
+                142
+            );
+        }
+    }
+
+    /// End to end tests running against a substrate-contracts-node
+    /// They need the node running and are enabled with the e2e-tests feature:
+    /// cargo test --features e2e-tests
+    /// The node seals a block for every extrinsic, so the blocks are advanced funding new accounts
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::build_message;
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        const BASE_PAYMENT: Balance = 1_000_000_000;
+
+        fn bob() -> AccountId {
+            ink_e2e::account_id(ink_e2e::AccountKeyring::Bob)
+        }
+
+        fn charlie() -> AccountId {
+            ink_e2e::account_id(ink_e2e::AccountKeyring::Charlie)
+        }
+
+        // A contract paying the base payment to bob every block
+        fn constructor() -> OpenPayrollRef {
+            OpenPayrollRef::new(
+                1,
+                BASE_PAYMENT,
+                vec!["Seniority".to_string()],
+                vec![InitialBeneficiary {
+                    account_id: bob(),
+                    multipliers: vec![(0, MultiplierValue::from_percent(100))],
+                    start_block: None,
+                    name: None,
+                    notes: None,
+                }],
+                100,
+                10,
+                None,
+                0,
+                false,
+                None,
+                None,
+                None,
+                None,
+            )
+        }
+
+        #[ink_e2e::test]
+        async fn claim_payment(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let contract_id = client
+                .instantiate(
+                    "open_payroll",
+                    &ink_e2e::alice(),
+                    constructor(),
+                    1_000 * BASE_PAYMENT,
+                    None,
+                )
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            // the owner adds charlie as a beneficiary
+            let add_beneficiary =
+                build_message::<OpenPayrollRef>(contract_id.clone()).call(|payroll| {
+                    payroll
+                        .add_beneficiary(charlie(), vec![(0, MultiplierValue::from_percent(200))])
+                });
+            client
+                .call(&ink_e2e::alice(), add_beneficiary, 0, None)
+                .await
+                .expect("add_beneficiary failed");
+
+            for _ in 0..3 {
+                client
+                    .create_and_fund_account(&ink_e2e::alice(), 1_000_000_000)
+                    .await;
+            }
+
+            // bob claims what he earned, the contract pays no fees so its balance drops by the exact amount
+            let get_amount_to_claim = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.get_amount_to_claim(bob()));
+            let amount = client
+                .call_dry_run(&ink_e2e::bob(), &get_amount_to_claim, 0, None)
+                .await
+                .return_value()
+                .expect("bob is a beneficiary");
+            assert!(amount >= 3 * BASE_PAYMENT);
+
+            let contract_balance = client.balance(contract_id.clone()).await?;
+            let bob_balance = client.balance(bob()).await?;
+            let claim_payment = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.claim_payment(bob(), amount));
+            client
+                .call(&ink_e2e::bob(), claim_payment, 0, None)
+                .await
+                .expect("claim_payment failed");
+            assert_eq!(
+                client.balance(contract_id.clone()).await?,
+                contract_balance - amount
+            );
+            let bob_balance_after = client.balance(bob()).await?;
+            assert!(bob_balance_after > bob_balance && bob_balance_after <= bob_balance + amount);
+
+            // claimed by a delegate the payee pays no fees, so the payee gets the exact amount
+            let set_claim_delegate = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.set_claim_delegate(bob()));
+            client
+                .call(&ink_e2e::charlie(), set_claim_delegate, 0, None)
+                .await
+                .expect("set_claim_delegate failed");
+            let get_amount_to_claim = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.get_amount_to_claim(charlie()));
+            let amount = client
+                .call_dry_run(&ink_e2e::bob(), &get_amount_to_claim, 0, None)
+                .await
+                .return_value()
+                .expect("charlie is a beneficiary");
+            let charlie_balance = client.balance(charlie()).await?;
+            let claim_payment = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.claim_payment(charlie(), amount));
+            client
+                .call(&ink_e2e::bob(), claim_payment, 0, None)
+                .await
+                .expect("claim_payment failed");
+            assert_eq!(client.balance(charlie()).await?, charlie_balance + amount);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn claim_payment_with_small_endowment(
+            mut client: ink_e2e::Client<C, E>,
+        ) -> E2EResult<()> {
+            let contract_id = client
+                .instantiate(
+                    "open_payroll",
+                    &ink_e2e::alice(),
+                    constructor(),
+                    BASE_PAYMENT,
+                    None,
+                )
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            for _ in 0..2 {
+                client
+                    .create_and_fund_account(&ink_e2e::alice(), 1_000_000_000)
+                    .await;
+            }
+
+            // 2 periods are owed but the treasury only holds 1
+            let claim_payment = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.claim_payment(bob(), 2 * BASE_PAYMENT));
+            let result = client
+                .call_dry_run(&ink_e2e::bob(), &claim_payment, 0, None)
+                .await;
+            assert_eq!(
+                result.return_value(),
+                Err(Error::NotEnoughBalanceInTreasury)
+            );
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn owner_only_messages(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let contract_id = client
+                .instantiate(
+                    "open_payroll",
+                    &ink_e2e::alice(),
+                    constructor(),
+                    1_000 * BASE_PAYMENT,
+                    None,
+                )
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            // bob is not the owner
+            let update_base_payment = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.update_base_payment(2 * BASE_PAYMENT));
+            let result = client
+                .call_dry_run(&ink_e2e::bob(), &update_base_payment, 0, None)
+                .await;
+            assert_eq!(result.return_value(), Err(Error::NotOwner));
+
+            // alice proposes bob as the new owner and bob accepts
+            let propose_transfer_ownership = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.propose_transfer_ownership(bob()));
+            client
+                .call(&ink_e2e::alice(), propose_transfer_ownership, 0, None)
+                .await
+                .expect("propose_transfer_ownership failed");
+            let accept_ownership = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.accept_ownership());
+            client
+                .call(&ink_e2e::bob(), accept_ownership, 0, None)
+                .await
+                .expect("accept_ownership failed");
+
+            let get_owner = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.get_owner());
+            let owner = client
+                .call_dry_run(&ink_e2e::alice(), &get_owner, 0, None)
+                .await
+                .return_value();
+            assert_eq!(owner, Some(bob()));
+
+            // now alice is the one not allowed
+            let update_base_payment = build_message::<OpenPayrollRef>(contract_id.clone())
+                .call(|payroll| payroll.update_base_payment(2 * BASE_PAYMENT));
+            let result = client
+                .call_dry_run(&ink_e2e::alice(), &update_base_payment, 0, None)
+                .await;
+            assert_eq!(result.return_value(), Err(Error::NotOwner));
+
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,116 @@
+//! Multiplier math of the payroll
+//!
+//! Pure functions over multiplier values and explicit block numbers, so they can be tested without the ink environment.
+
+use crate::types::{Balance, BlockNumber};
+#[cfg(feature = "std")]
+use ink::storage::traits::StorageLayout;
+
+/// Basis points in one unit, a multiplier of 10_000 basis points pays exactly the base payment
+pub const BPS_PER_UNIT: u128 = 10_000;
+
+/// Multiplier value in basis points, 10_000 is x1 the base payment
+/// It is encoded as the underlying u128, so it is built with from_percent or from_bps to make the unit explicit
+/// The encoding is the same as the percentages used before 0.2.0, clients have to multiply their values by 100, see CHANGELOG.md
+#[derive(scale::Encode, scale::Decode, Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub struct MultiplierValue(u128);
+
+impl MultiplierValue {
+    /// Build a multiplier from a percentage, 100 is x1 the base payment
+    pub const fn from_percent(percent: u128) -> Self {
+        Self(percent.saturating_mul(BPS_PER_UNIT / 100))
+    }
+
+    /// Build a multiplier from basis points, 10_000 is x1 the base payment
+    pub const fn from_bps(bps: u128) -> Self {
+        Self(bps)
+    }
+
+    /// Get the multiplier in basis points
+    pub const fn bps(&self) -> u128 {
+        self.0
+    }
+
+    /// Apply the multiplier to an amount, or None if it overflows
+    pub fn apply_to(&self, amount: Balance) -> Option<Balance> {
+        Some(self.0.checked_mul(amount)? / BPS_PER_UNIT)
+    }
+}
+
+impl core::iter::Sum for MultiplierValue {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self(0), |total, multiplier| {
+            Self(total.0.saturating_add(multiplier.0))
+        })
+    }
+}
+
+/// Check if a multiplier is still paid for a period ending at the block
+/// A multiplier is expired at its valid until block, so it is paid for the periods ending at or before it
+pub fn is_paid_until(
+    valid_until_block: Option<BlockNumber>,
+    period_end_block: BlockNumber,
+) -> bool {
+    match valid_until_block {
+        Some(valid_until_block) => valid_until_block >= period_end_block,
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiplier_value_units() {
+        assert_eq!(
+            MultiplierValue::from_percent(100),
+            MultiplierValue::from_bps(10_000)
+        );
+        assert_eq!(MultiplierValue::from_percent(3).bps(), 300);
+        assert_eq!(
+            MultiplierValue::from_percent(100).apply_to(1000),
+            Some(1000)
+        );
+        assert_eq!(MultiplierValue::from_bps(150).apply_to(1000), Some(15));
+        assert_eq!(MultiplierValue::from_bps(2).apply_to(u128::MAX), None);
+        assert_eq!(
+            vec![
+                MultiplierValue::from_percent(100),
+                MultiplierValue::from_bps(50)
+            ]
+            .into_iter()
+            .sum::<MultiplierValue>(),
+            MultiplierValue::from_bps(10_050)
+        );
+    }
+
+    #[test]
+    fn multiplier_applied_is_proportional_to_its_basis_points() {
+        for bps in [0, 1, 99, 100, 10_000, 12_345, 1_000_000] {
+            for amount in [0, 1, 7, 1000, 123_456_789] {
+                let multiplier = MultiplierValue::from_bps(bps);
+                assert_eq!(
+                    multiplier.apply_to(amount),
+                    Some(bps * amount / BPS_PER_UNIT)
+                );
+                assert!(
+                    MultiplierValue::from_bps(bps + 1).apply_to(amount).unwrap()
+                        >= multiplier.apply_to(amount).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn multiplier_is_paid_until_its_valid_until_block() {
+        for period_end_block in 0..20 {
+            assert!(is_paid_until(None, period_end_block));
+            assert_eq!(
+                is_paid_until(Some(10), period_end_block),
+                period_end_block <= 10
+            );
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Period arithmetic of the payroll
+//!
+//! Pure functions over explicit block numbers, so the period math can be tested without the ink environment.
+//! The block numbers are timestamps in seconds in time based mode, the math is the same.
+
+/// Get the block starting the period the block belongs to
+/// The blocks before the initial block belong to the first period
+pub fn period_initial_block(initial_block: u32, periodicity: u32, block: u32) -> u32 {
+    block - (block.saturating_sub(initial_block) % periodicity)
+}
+
+/// Get the index of the period the block belongs to, the first period being 0
+/// Returns None for a block before the initial block
+pub fn period_number(initial_block: u32, periodicity: u32, block: u32) -> Option<u32> {
+    Some(block.checked_sub(initial_block)? / periodicity)
+}
+
+/// Get the block starting the period with the index, the first period being 0
+/// Returns None if the block overflows
+pub fn period_block(initial_block: u32, periodicity: u32, number: u32) -> Option<u32> {
+    number.checked_mul(periodicity)?.checked_add(initial_block)
+}
+
+/// Check if the block starts a period
+pub fn is_period_start(initial_block: u32, periodicity: u32, block: u32) -> bool {
+    match block.checked_sub(initial_block) {
+        Some(elapsed) => elapsed % periodicity == 0,
+        None => false,
+    }
+}
+
+/// Get the first block starting a period at or after the block
+pub fn first_period_block_from(initial_block: u32, periodicity: u32, block: u32) -> u32 {
+    if block <= initial_block {
+        return initial_block;
+    }
+    match (block - initial_block) % periodicity {
+        0 => block,
+        blocks_into_period => block - blocks_into_period + periodicity,
+    }
+}
+
+/// Get the number of full periods elapsed from a block to another, 0 if the second is not after the first
+pub fn elapsed_periods(from_block: u32, to_block: u32, periodicity: u32) -> u32 {
+    to_block.saturating_sub(from_block) / periodicity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every combination of a few initial blocks, periodicities and blocks around them
+    fn cases() -> impl Iterator<Item = (u32, u32, u32)> {
+        [0, 1, 7, 100].into_iter().flat_map(|initial_block| {
+            [1, 2, 3, 10].into_iter().flat_map(move |periodicity| {
+                (initial_block..initial_block + 5 * periodicity + 3)
+                    .map(move |block| (initial_block, periodicity, block))
+            })
+        })
+    }
+
+    #[test]
+    fn period_initial_block_is_aligned_and_not_after_the_block() {
+        for (initial_block, periodicity, block) in cases() {
+            let start = period_initial_block(initial_block, periodicity, block);
+            assert!(start <= block);
+            assert!(block - start < periodicity);
+            assert!(is_period_start(initial_block, periodicity, start));
+        }
+    }
+
+    #[test]
+    fn period_number_matches_period_initial_block() {
+        for (initial_block, periodicity, block) in cases() {
+            let number = period_number(initial_block, periodicity, block).unwrap();
+            assert_eq!(
+                period_block(initial_block, periodicity, number),
+                Some(period_initial_block(initial_block, periodicity, block))
+            );
+        }
+        assert_eq!(period_number(5, 3, 4), None);
+        assert_eq!(period_block(5, 3, u32::MAX), None);
+    }
+
+    #[test]
+    fn first_period_block_from_is_the_next_period_start() {
+        for (initial_block, periodicity, block) in cases() {
+            let first = first_period_block_from(initial_block, periodicity, block);
+            assert!(first >= block);
+            assert!(first - block < periodicity);
+            assert!(is_period_start(initial_block, periodicity, first));
+        }
+        assert_eq!(first_period_block_from(5, 3, 0), 5);
+        assert!(!is_period_start(5, 3, 2));
+    }
+
+    #[test]
+    fn elapsed_periods_between_period_starts() {
+        for (initial_block, periodicity, block) in cases() {
+            let start = period_initial_block(initial_block, periodicity, block);
+            let number = period_number(initial_block, periodicity, block).unwrap();
+            assert_eq!(elapsed_periods(initial_block, start, periodicity), number);
+            assert_eq!(elapsed_periods(initial_block, block, periodicity), number);
+            assert_eq!(elapsed_periods(block, initial_block, periodicity), 0);
+        }
+    }
+}
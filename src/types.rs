@@ -0,0 +1,92 @@
+//! Types stored and returned by the payroll
+//!
+//! They live outside the contract module so the pure modules of the crate can use them
+//! without the ink environment.
+
+use crate::multipliers::MultiplierValue;
+use ink::prelude::collections::BTreeMap;
+use ink::prelude::string::String;
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+#[cfg(feature = "std")]
+use ink::storage::traits::StorageLayout;
+
+/// The balance type of the environment of the contract
+pub type Balance = <ink::env::DefaultEnvironment as ink::env::Environment>::Balance;
+
+/// The block number type of the environment of the contract
+pub type BlockNumber = <ink::env::DefaultEnvironment as ink::env::Environment>::BlockNumber;
+
+/// The id of a base multiplier
+pub type MultiplierId = u32;
+
+/// The id of a group of beneficiaries
+pub type GroupId = u8;
+
+/// Base multiplier structure containg a name, an option block number for being used when deactivating the multiplier
+/// and the account sponsoring the payments made with it, if any
+#[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub struct BaseMultiplier {
+    pub(crate) name: String,
+    pub(crate) valid_until_block: Option<BlockNumber>,
+    pub(crate) sponsor: Option<AccountId>,
+}
+
+impl BaseMultiplier {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            valid_until_block: None,
+            sponsor: None,
+        }
+    }
+}
+
+/// Beneficiary structure containing the account id, the multipliers, the unclaimed payments, the last updated period block,
+/// the number of blocks the contract and its group had been paused when that block was set,
+/// whether the beneficiary is suspended, the optional block from which payments start to accrue (vesting cliff),
+/// the optional max amount transferred in a single claim, the optional group the beneficiary belongs to,
+/// whether the beneficiary is deactivated before being removed, the optional name and notes used in reports,
+/// the optional account its payments are sent to instead of its own,
+/// and the part of the unclaimed payments that comes from bonuses, which never expires
+#[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub struct Beneficiary {
+    pub(crate) account_id: AccountId,
+    pub(crate) multipliers: BTreeMap<MultiplierId, MultiplierValue>,
+    pub(crate) unclaimed_payments: Balance,
+    pub(crate) last_updated_period_block: BlockNumber,
+    pub(crate) paused_blocks_at_update: BlockNumber,
+    pub(crate) suspended: bool,
+    pub(crate) start_block: Option<BlockNumber>,
+    pub(crate) max_per_claim: Option<Balance>,
+    pub(crate) group_id: Option<GroupId>,
+    pub(crate) deactivated: bool,
+    pub(crate) name: Option<String>,
+    pub(crate) notes: Option<String>,
+    pub(crate) payout_override: Option<AccountId>,
+    pub(crate) unclaimed_bonuses: Balance,
+}
+
+/// Initial beneficiary structure containing the account id, the multipliers, the optional vesting cliff block,
+/// and the optional name and notes of the beneficiary
+#[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub struct InitialBeneficiary {
+    pub(crate) account_id: AccountId,
+    // Vector rather than BTreeMap because its easier to buid from the frontend
+    pub(crate) multipliers: Vec<(MultiplierId, MultiplierValue)>,
+    pub(crate) start_block: Option<BlockNumber>,
+    pub(crate) name: Option<String>,
+    pub(crate) notes: Option<String>,
+}
+
+/// Claims in period structure containing the period, the total claims and the total amount claimed
+#[derive(scale::Encode, scale::Decode, Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "std", derive(scale_info::TypeInfo, StorageLayout))]
+pub struct ClaimsInPeriod {
+    pub(crate) period: u32,
+    pub(crate) total_claims: u32,
+    pub(crate) total_amount: Balance,
+}